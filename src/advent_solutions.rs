@@ -1,6 +1,12 @@
 use std::fs;
 use std::path::Path;
 use anyhow::Context;
+use num_bigint::BigUint;
+use serde::Serialize;
+
+use crate::animate::Recorder;
+use crate::generate::Generator;
+use crate::visualize::Scene;
 
 
 pub mod day1;
@@ -38,18 +44,305 @@ where
     Ok(file_contents)
 }
 
+///The answer to a single part of a days puzzle. Solutions return
+/// one of these rather than printing, so that answers can be compared and
+/// formatted by the caller. Serializes as a bare number, string or `{caption, rows}` object.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Answer {
+    ///Most puzzles have a whole number answer. `i128` is wide enough to
+    /// losslessly hold every integer type the solutions compute with.
+    Integer(i128),
+    ///Puzzles whose answer is text, such as Day 17's out buffer or Day 23's LAN password.
+    Text(String),
+    ///A multi-line drawing (such as Day 14's christmas tree), along
+    /// with a caption describing what was drawn.
+    Grid {
+        caption: String,
+        rows: Vec<String>
+    },
+}
+
+impl std::fmt::Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Integer(value) => write!(f, "{}", value),
+            Answer::Text(value) => write!(f, "{}", value),
+            Answer::Grid { caption, rows } => {
+                write!(f, "{}", caption)?;
+                for row in rows {
+                    write!(f, "\n{}", row)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+macro_rules! answer_from_integer {
+    ($($integer:ty),*) => {
+        $(
+            impl From<$integer> for Answer {
+                fn from(value: $integer) -> Self {
+                    Answer::Integer(value as i128)
+                }
+            }
+        )*
+    };
+}
+
+answer_from_integer!(i32, i64, u32, u64, usize);
+
+impl From<BigUint> for Answer {
+    fn from(value: BigUint) -> Self {
+        //! Written out as text if it is too large to be an `Integer`.
+        match i128::try_from(&value) {
+            Ok(value) => Answer::Integer(value),
+            Err(_) => Answer::Text(value.to_string()),
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Text(value.to_string())
+    }
+}
+
 ///trait representing how to solve the days challenge for the advent calendar.
 ///Obviously, part1 is for part1 and part2 is for part2.
-/// The trait methods do not return anything useful, the answer to the problem
-/// should simply be printed.
+/// The trait methods take the puzzle input text (already normalized to `\n` line endings
+/// with trailing whitespace removed) rather than a path, so that input can come from anywhere.
+/// They return the `Answer` rather than printing it, formatting
+/// is left to the caller. Parts that have not been solved yet return an error.
 pub trait SolveAdvent {
+    ///How to parse the days puzzle input without solving anything, so that parsing can be
+    /// timed on its own. Days that parse as they solve (such as Day 3) have no separate parse phase.
+    fn parse(_input: &str) -> anyhow::Result<()> {
+        anyhow::bail!("Parsing is not a separate phase of this solution")
+    }
     ///How to solve part1 of the days puzzle.
-    fn solve_part1(_path_to_file: &str) -> anyhow::Result<()> {
-        Ok(())
+    fn solve_part1(_input: &str) -> anyhow::Result<Answer> {
+        anyhow::bail!("Part 1 has not been solved yet")
     }
     ///How to solve part2 of the days puzzle.
-    fn solve_part2(_path_to_file: &str) -> anyhow::Result<()> {
-        Ok(())
+    fn solve_part2(_input: &str) -> anyhow::Result<Answer> {
+        anyhow::bail!("Part 2 has not been solved yet")
+    }
+}
+
+///The signature shared by every `solve_part1`/`solve_part2` implementation.
+pub type SolveFn = fn(&str) -> anyhow::Result<Answer>;
+
+///The signature shared by every `parse` implementation.
+pub type ParseFn = fn(&str) -> anyhow::Result<()>;
+
+///A single day of the advent calendar described as data, so that
+/// every solution can be enumerated at runtime instead of being named in `main.rs`.
+#[derive(Debug, Clone, Copy)]
+pub struct DayEntry {
+    ///The day of the advent calendar, 1-25
+    pub day: u8,
+    ///The title of the days puzzle
+    pub title: &'static str,
+    ///Entry point for parsing the input on its own, `None` if parsing is not a separate phase.
+    pub parse: Option<ParseFn>,
+    ///Entry point for part1, `None` if it has not been solved yet.
+    pub part1: Option<SolveFn>,
+    ///Entry point for part2, `None` if it has not been solved yet.
+    pub part2: Option<SolveFn>,
+}
+
+impl DayEntry {
+    const fn new(day: u8, title: &'static str, parse: Option<ParseFn>, part1: Option<SolveFn>, part2: Option<SolveFn>) -> Self {
+        DayEntry {
+            day,
+            title,
+            parse,
+            part1,
+            part2
+        }
+    }
+
+    pub fn part(&self, part: u8) -> Option<SolveFn> {
+        //! The entry point for the requested `part`, if it exists and has been solved.
+        match part {
+            1 => self.part1,
+            2 => self.part2,
+            _ => None
+        }
+    }
+
+    pub fn solve(&self, part: u8, input: &str) -> anyhow::Result<Answer> {
+        //! Solve the given `part` using the puzzle `input` text.
+        anyhow::ensure!(part == 1 || part == 2, "Part {} does not exist, each day only has parts 1 and 2", part);
+        let solve_fn = self.part(part).ok_or(anyhow::anyhow!("Day {} part {} has not been solved yet", self.day, part))?;
+        solve_fn(input)
     }
 }
 
+///Every day of the advent calendar, in order. Parts that have not been
+/// solved (Day 24 part 2, and the non-existent Day 25 part 2) are `None`, as is
+/// the parse phase of Day 3, which parses as it solves.
+pub const REGISTRY: [DayEntry; 25] = [
+    DayEntry::new(1, "Historian Hysteria", Some(day1::Day1::parse), Some(day1::Day1::solve_part1), Some(day1::Day1::solve_part2)),
+    DayEntry::new(2, "Red-Nosed Reports", Some(day2::Day2::parse), Some(day2::Day2::solve_part1), Some(day2::Day2::solve_part2)),
+    DayEntry::new(3, "Mull It Over", None, Some(day3::Day3::solve_part1), Some(day3::Day3::solve_part2)),
+    DayEntry::new(4, "Ceres Search", Some(day4::Day4::parse), Some(day4::Day4::solve_part1), Some(day4::Day4::solve_part2)),
+    DayEntry::new(5, "Print Queue", Some(day5::Day5::parse), Some(day5::Day5::solve_part1), Some(day5::Day5::solve_part2)),
+    DayEntry::new(6, "Guard Gallivant", Some(day6::Day6::parse), Some(day6::Day6::solve_part1), Some(day6::Day6::solve_part2)),
+    DayEntry::new(7, "Bridge Repair", Some(day7::Day7::parse), Some(day7::Day7::solve_part1), Some(day7::Day7::solve_part2)),
+    DayEntry::new(8, "Resonant Collinearity", Some(day8::Day8::parse), Some(day8::Day8::solve_part1), Some(day8::Day8::solve_part2)),
+    DayEntry::new(9, "Disk Fragmenter", Some(day9::Day9::parse), Some(day9::Day9::solve_part1), Some(day9::Day9::solve_part2)),
+    DayEntry::new(10, "Hoof It", Some(day10::Day10::parse), Some(day10::Day10::solve_part1), Some(day10::Day10::solve_part2)),
+    DayEntry::new(11, "Plutonian Pebbles", Some(day11::Day11::parse), Some(day11::Day11::solve_part1), Some(day11::Day11::solve_part2)),
+    DayEntry::new(12, "Garden Groups", Some(day12::Day12::parse), Some(day12::Day12::solve_part1), Some(day12::Day12::solve_part2)),
+    DayEntry::new(13, "Claw Contraption", Some(day13::Day13::parse), Some(day13::Day13::solve_part1), Some(day13::Day13::solve_part2)),
+    DayEntry::new(14, "Restroom Redoubt", Some(day14::Day14::parse), Some(day14::Day14::solve_part1), Some(day14::Day14::solve_part2)),
+    DayEntry::new(15, "Warehouse Woes", Some(day15::Day15::parse), Some(day15::Day15::solve_part1), Some(day15::Day15::solve_part2)),
+    DayEntry::new(16, "Reindeer Maze", Some(day16::Day16::parse), Some(day16::Day16::solve_part1), Some(day16::Day16::solve_part2)),
+    DayEntry::new(17, "Chronospatial Computer", Some(day17::Day17::parse), Some(day17::Day17::solve_part1), Some(day17::Day17::solve_part2)),
+    DayEntry::new(18, "RAM Run", Some(day18::Day18::parse), Some(day18::Day18::solve_part1), Some(day18::Day18::solve_part2)),
+    DayEntry::new(19, "Linen Layout", Some(day19::Day19::parse), Some(day19::Day19::solve_part1), Some(day19::Day19::solve_part2)),
+    DayEntry::new(20, "Race Condition", Some(day20::Day20::parse), Some(day20::Day20::solve_part1), Some(day20::Day20::solve_part2)),
+    DayEntry::new(21, "Keypad Conundrum", Some(day21::Day21::parse), Some(day21::Day21::solve_part1), Some(day21::Day21::solve_part2)),
+    DayEntry::new(22, "Monkey Market", Some(day22::Day22::parse), Some(day22::Day22::solve_part1), Some(day22::Day22::solve_part2)),
+    DayEntry::new(23, "LAN Party", Some(day23::Day23::parse), Some(day23::Day23::solve_part1), Some(day23::Day23::solve_part2)),
+    DayEntry::new(24, "Crossed Wires", Some(day24::Day24::parse), Some(day24::Day24::solve_part1), None),
+    DayEntry::new(25, "Code Chronicle", Some(day25::Day25::parse), Some(day25::Day25::solve_part1), None),
+];
+
+pub fn find_day(day: u8) -> anyhow::Result<&'static DayEntry> {
+    //! Look up the registry entry for `day`.
+    REGISTRY.iter().find(|entry| entry.day == day).ok_or(anyhow::anyhow!("Day {} is not a valid advent calendar day, expected 1-25", day))
+}
+
+pub fn solve_day(day: u8, part: u8, input: &str) -> anyhow::Result<Answer> {
+    //! Solve the given `part` of the given `day` using the puzzle `input` text.
+    find_day(day)?.solve(part, input)
+}
+
+///The signature shared by every day's `visualize`, which draws the puzzle input with
+/// the interesting parts of its solution highlighted.
+pub type VisualizeFn = fn(&str) -> anyhow::Result<Scene>;
+
+///The days that can be visualized, in order.
+pub const VISUALIZATIONS: [(u8, VisualizeFn); 6] = [
+    (6, day6::visualize),
+    (10, day10::visualize),
+    (12, day12::visualize),
+    (16, day16::visualize),
+    (18, day18::visualize),
+    (20, day20::visualize),
+];
+
+pub fn find_visualization(day: u8) -> anyhow::Result<VisualizeFn> {
+    //! Look up how to visualize `day`.
+    let visualizable_days = VISUALIZATIONS.iter().map(|(day, _)| day.to_string()).collect::<Vec<_>>();
+    VISUALIZATIONS.iter()
+        .find(|(visualizable_day, _)| *visualizable_day == day)
+        .map(|(_, visualize)| *visualize)
+        .ok_or_else(|| anyhow::anyhow!("Day {} cannot be visualized, only days {} can", day, visualizable_days.join(", ")))
+}
+
+///The signature shared by every day's `animate`, which records the frames of a time-stepped simulation.
+pub type AnimateFn = fn(&str, &mut Recorder) -> anyhow::Result<()>;
+
+///The days whose simulations can be animated, in order.
+pub const ANIMATIONS: [(u8, AnimateFn); 3] = [
+    (6, day6::animate),
+    (14, day14::animate),
+    (15, day15::animate),
+];
+
+pub fn find_animation(day: u8) -> anyhow::Result<AnimateFn> {
+    //! Look up how to animate `day`.
+    let animated_days = ANIMATIONS.iter().map(|(day, _)| day.to_string()).collect::<Vec<_>>();
+    ANIMATIONS.iter()
+        .find(|(animated_day, _)| *animated_day == day)
+        .map(|(_, animate)| *animate)
+        .ok_or_else(|| anyhow::anyhow!("Day {} cannot be animated, only days {} can", day, animated_days.join(", ")))
+}
+
+
+///How to generate random inputs for every day, in order. Sizes default to those of the real inputs.
+pub const GENERATORS: [Generator; 25] = [
+    Generator::new(1, "lines", 1..=100_000, 1000, day1::generate),
+    Generator::new(2, "reports", 1..=100_000, 1000, day2::generate),
+    Generator::new(3, "instructions", 1..=100_000, 700, day3::generate),
+    Generator::new(4, "letters wide", 1..=1000, 140, day4::generate),
+    Generator::new(5, "updates", 1..=10_000, 200, day5::generate),
+    Generator::new(6, "positions wide", 1..=1000, 130, day6::generate),
+    Generator::new(7, "equations", 1..=100_000, 850, day7::generate),
+    Generator::new(8, "positions wide", 1..=1000, 50, day8::generate),
+    Generator::new(9, "digits", 1..=100_000, 19_999, day9::generate),
+    Generator::new(10, "positions wide", 1..=1000, 50, day10::generate),
+    Generator::new(11, "stones", 1..=1000, 8, day11::generate),
+    Generator::new(12, "plots wide", 1..=1000, 140, day12::generate),
+    Generator::new(13, "claw machines", 1..=10_000, 320, day13::generate),
+    Generator::new(14, "robots", 1..=10_000, 500, day14::generate),
+    Generator::new(15, "positions wide", 3..=1000, 50, day15::generate),
+    Generator::new(16, "tiles wide", 5..=1001, 141, day16::generate),
+    Generator::new(17, "output values", 1..=20, 9, day17::generate),
+    Generator::new(18, "bytes", 2500..=5039, 3450, day18::generate),
+    Generator::new(19, "designs", 1..=10_000, 400, day19::generate),
+    Generator::new(20, "positions wide", 5..=1001, 141, day20::generate),
+    Generator::new(21, "codes", 1..=1000, 5, day21::generate),
+    Generator::new(22, "buyers", 1..=100_000, 2000, day22::generate),
+    Generator::new(23, "computers", 3..=676, 520, day23::generate),
+    Generator::new(24, "bits", 1..=63, 45, day24::generate),
+    Generator::new(25, "schematics", 1..=10_000, 500, day25::generate),
+];
+
+pub fn find_generator(day: u8) -> anyhow::Result<&'static Generator> {
+    //! Look up how to generate inputs for `day`.
+    GENERATORS.iter().find(|generator| generator.day == day).ok_or(anyhow::anyhow!("Day {} is not a valid advent calendar day, expected 1-25", day))
+}
+
+#[cfg(test)]
+pub(crate) fn solve_example(solve_fn: SolveFn, example: &str) -> Answer {
+    //! Solve a puzzle example the same way the runner solves a real input, normalizing it first.
+    solve_fn(&crate::input::normalize_input(example)).expect("Example could not be solved")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_covers_every_day_in_order() {
+        let days = REGISTRY.iter().map(|entry| entry.day).collect::<Vec<_>>();
+        assert_eq!(days, (1..=25).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn unsolved_parts_are_reported() {
+        let error = solve_day(24, 2, "").unwrap_err();
+        assert_eq!(error.to_string(), "Day 24 part 2 has not been solved yet");
+        assert!(solve_day(26, 1, "").is_err());
+    }
+
+    #[test]
+    fn only_map_days_can_be_visualized_or_animated() {
+        assert!(find_visualization(16).is_ok());
+        let error = find_visualization(7).unwrap_err();
+        assert_eq!(error.to_string(), "Day 7 cannot be visualized, only days 6, 10, 12, 16, 18, 20 can");
+        assert!(find_animation(14).is_ok());
+        assert!(find_animation(16).is_err());
+    }
+
+    #[test]
+    fn grid_answers_display_caption_then_rows() {
+        let answer = Answer::Grid {
+            caption: "Tree".to_string(),
+            rows: vec!["*".to_string(), "***".to_string()]
+        };
+        assert_eq!(answer.to_string(), "Tree\n*\n***");
+    }
+}
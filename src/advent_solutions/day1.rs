@@ -1,45 +1,67 @@
 use std::collections::HashMap;
-use anyhow::anyhow;
+use rand::seq::IndexedRandom;
+use rand::RngExt;
 
-use super::{read_input_file, SolveAdvent};
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::input_error::{parse_lines, parse_number, InputError};
 
 pub struct Day1;
 
-fn preprocess_lists(path_to_file: &str) -> anyhow::Result<(Vec<i64>, Vec<i64>)> {
-    //! Performs the preprocessing to read the `.txt` file, and parse the strings into integers.
-    //! Returns the two lists sorted!
-    let file_contents = read_input_file(path_to_file)?;
-    let mut list1 = Vec::new();
-    let mut list2 = Vec::new();
-    for line in file_contents.lines() {
-        let [num1, num2]: [&str; 2] = line.trim().split(' ').filter(|substr| !substr.is_empty()).collect::<Vec<_>>().try_into().map_err(|_| anyhow!("Failed to coerce to length 2 array!"))?;
-        let num1 = num1.parse::<i64>()?;
-        let num2 = num2.parse::<i64>()?;
-        list1.push(num1);
-        list2.push(num2);
+const DAY: u8 = 1;
+
+fn parse_location_ids(line: &str) -> Result<(i64, i64), InputError> {
+    //! Parse a line holding one location ID from each list.
+    let numbers = line.split(' ').filter(|substr| !substr.is_empty()).collect::<Vec<_>>();
+    match numbers[..] {
+        [num1, num2] => Ok((parse_number(DAY, line, num1)?, parse_number(DAY, line, num2)?)),
+        [_] | [] => Err(InputError::at_end(DAY, line, "two location IDs separated by spaces")),
+        [_, _, extra, ..] => Err(InputError::at(DAY, line, extra, "only two location IDs")),
     }
+}
+
+pub fn preprocess_lists(file_contents: &str) -> anyhow::Result<(Vec<i64>, Vec<i64>)> {
+    //! Performs the preprocessing to parse the strings of the input file into integers.
+    //! Returns the two lists sorted!
+    let (mut list1, mut list2): (Vec<_>, Vec<_>) = parse_lines(file_contents, parse_location_ids)?.into_iter().unzip();
     list1.sort();
     list2.sort();
     Ok((list1, list2))
 }
 
 
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! `size` lines of five digit location IDs. Some IDs in the right list are copied from the left list,
+    //! so that the similarity score is not always 0.
+    let left_ids = (0..size).map(|_| rng.random_range(10000..100000)).collect::<Vec<u32>>();
+    let mut lines = Vec::with_capacity(size);
+    for left_id in left_ids.iter() {
+        let right_id = if rng.random_bool(0.3) { *left_ids.choose(rng).unwrap() } else { rng.random_range(10000..100000) };
+        lines.push(format!("{}   {}", left_id, right_id));
+    }
+    lines.join("\n")
+}
+
 impl SolveAdvent for Day1 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
+    fn parse(input: &str) -> anyhow::Result<()> {
+        preprocess_lists(input)?;
+        Ok(())
+    }
+
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
         //! We just need to sum the absolute difference between the two lists.
-    let (list1, list2) = preprocess_lists(path_to_file)?;
+    let (list1, list2) = preprocess_lists(input)?;
     let mut running_delta = 0;
-    for (n1, n2) in list1.into_iter().zip(list2.into_iter()) {
+    for (n1, n2) in list1.into_iter().zip(list2) {
         running_delta += (n2 - n1).abs()
     }
-    println!("Running delta is {:?}", running_delta);
-     Ok(())   
+    Ok(running_delta.into())
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
         //! Multiply each value in `list1` but how often it occurs in `list2`.
         //! A hashmap trivially handles this situation.
-        let (list1, list2) = preprocess_lists(path_to_file)?;
+        let (list1, list2) = preprocess_lists(input)?;
         let mut list2_counter: HashMap<i64, i64> = HashMap::new();
         for num2 in list2.iter() {
             *list2_counter.entry(*num2).or_default() += 1;
@@ -50,7 +72,40 @@ impl SolveAdvent for Day1 {
                 similarity_score += *count * num1;
             }
         }
-        println!("Similarity score is {:?}", similarity_score);
-        Ok(())
+        Ok(similarity_score.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const EXAMPLE: &str = "\
+3   4
+4   3
+2   5
+1   3
+3   9
+3   3
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day1::solve_part1, EXAMPLE), Answer::Integer(11));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_example(Day1::solve_part2, EXAMPLE), Answer::Integer(31));
+    }
+
+    #[test]
+    fn bad_location_ids_are_located() {
+        let error = preprocess_lists("3   4\n4   3x\n2").unwrap_err();
+        let error = error.downcast_ref::<InputError>().unwrap();
+        assert_eq!((error.line, error.column, error.found.as_str()), (2, 5, "3x"));
+        let error = preprocess_lists("3   4\n2").unwrap_err();
+        assert_eq!(error.downcast_ref::<InputError>().unwrap().line, 2);
+    }
+}
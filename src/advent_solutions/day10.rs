@@ -1,85 +1,67 @@
 use std::collections::HashSet;
 
-use super::{read_input_file, SolveAdvent};
+use rand::RngExt;
+
+use super::{Answer, SolveAdvent};
+use crate::generate::{nearest_seeds, InputRng};
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::input_error::InputError;
+use crate::visualize::{Color, Scene};
 
 pub struct Day10;
 
+const DAY: u8 = 10;
+
 
 ///Represents a path through the Trail Map.
 /// The unique requirement that the height always increase by 1 allows
 /// cycles to be avoided without a set to store this `TrailBlazer`'s history, 
 /// as is common in these kinds of problems.
 #[derive(Debug, Clone)]
-struct TrailBlazer {
-    row: i64,
-    col: i64,
+pub struct TrailBlazer {
+    pub position: Point,
     ///Represents the last_height traversed on this trail path.
     last_height: Option<u32>,
 }
 
 ///The two key statistics of interest for a trail.
-struct TrailResult {
+pub struct TrailResult {
     ///Represents the number of unique paths from height 0 to height 9
-    rank: usize,
+    pub rank: usize,
     ///Represents the number of different height 9's that can 
     /// be reached starting at a given trailhead.
-    score: usize
+    pub score: usize
+}
+
+pub fn parse_trail_map(file_contents: &str) -> Result<Grid<u32>, InputError> {
+    //! Parse the input file into a map of trail heights.
+    Grid::parse(DAY, file_contents, |char| char.to_digit(10), "a height from 0 to 9")
 }
 
 impl TrailBlazer {
-    fn is_inbounds(&self, trail_map: &[Vec<u32>]) -> bool {
+    fn is_inbounds(&self, trail_map: &Grid<u32>) -> bool {
         //! Returns true if the `TrailBlazer` is inbounds according to its
         //! current position
-        self.row >= 0 && self.col >= 0 && self.row < trail_map.len() as i64 && self.col < trail_map[0].len() as i64
-    }
-    fn up_one(&self) -> Self {
-        TrailBlazer {
-            row: self.row -1,
-            col: self.col,
-            last_height: self.last_height,
-        }
-    }
-    fn down_one(&self) -> Self {
-        TrailBlazer {
-            row: self.row +1,
-            col: self.col,
-            last_height: self.last_height,
-        }
+        trail_map.contains(self.position)
     }
-    fn right_one(&self) -> Self {
+    fn step(&self, direction: Direction) -> Self {
         TrailBlazer {
-            row: self.row,
-            col: self.col + 1,
+            position: self.position.step(direction),
             last_height: self.last_height,
         }
     }
-    fn left_one(&self) -> Self {
-        TrailBlazer {
-            row: self.row,
-            col: self.col - 1,
-            last_height: self.last_height,
-        }
-    }
-    fn construct_all_trailheads(all_trails: &[Vec<u32>]) -> Vec<TrailBlazer> {
+    pub fn construct_all_trailheads(all_trails: &Grid<u32>) -> Vec<TrailBlazer> {
         //! Build all of the starting trailheads, which are always at a height of 0.
-        let mut trail_heads = Vec::new();
-        for (row_number, row) in all_trails.iter().enumerate() {
-            for (col_number, col) in row.iter().enumerate() {
-                if *col == 0 {
-                    trail_heads.push(TrailBlazer {
-                        row: row_number as i64,
-                        col: col_number as i64,
-                        last_height: None, 
-                    })
-                }
-            }
-        }
-        trail_heads
+        all_trails.find_all(&0).map(|position| TrailBlazer {
+            position,
+            last_height: None, 
+        }).collect()
     }
 }
 
 
-fn get_trailhead_statistics(trail_head: TrailBlazer, trail_map: &[Vec<u32>]) -> TrailResult {
+pub fn get_trailhead_statistics(trail_head: TrailBlazer, trail_map: &Grid<u32>) -> TrailResult {
     //! Starting from the passed in `trail_head`, which is at height 0, analyze the trail to generate 
     //! a `TrailResult` using depth first search of the trail map. A successful trail goes from height 0 -> height 9,
     //! with each trail step 1 height greater than the previous step.
@@ -87,7 +69,7 @@ fn get_trailhead_statistics(trail_head: TrailBlazer, trail_map: &[Vec<u32>]) ->
     let mut unique_9s_reached = HashSet::new();
     let mut unique_trail_count = 0;
     while let Some(mut trail_blazer) = trail_stack.pop() {
-        let current_trail_height = trail_map[trail_blazer.row as usize][trail_blazer.col as usize];
+        let current_trail_height = trail_map[trail_blazer.position];
         if let Some(last_height) = trail_blazer.last_height {
             if last_height + 1 != current_trail_height {
                 //Kill the path as its invalid (current height is not 1 greater than last height)
@@ -95,19 +77,14 @@ fn get_trailhead_statistics(trail_head: TrailBlazer, trail_map: &[Vec<u32>]) ->
             }
         }
         if current_trail_height == 9 {
-            unique_9s_reached.insert((trail_blazer.row, trail_blazer.col));
+            unique_9s_reached.insert(trail_blazer.position);
             unique_trail_count += 1;
             continue;
         }
         trail_blazer.last_height  = Some(current_trail_height);
 
-        let next_trail_heads = [
-            trail_blazer.down_one(),
-            trail_blazer.left_one(),
-            trail_blazer.right_one(),
-            trail_blazer.up_one()
-        ];
-        trail_stack.extend(next_trail_heads.into_iter().filter(|trail_blazer| trail_blazer.is_inbounds(trail_map)));
+        let next_trail_heads = Direction::ALL.into_iter().map(|direction| trail_blazer.step(direction));
+        trail_stack.extend(next_trail_heads.filter(|trail_blazer| trail_blazer.is_inbounds(trail_map)));
 }
 TrailResult {
     rank: unique_trail_count,
@@ -115,29 +92,123 @@ TrailResult {
 }
 }
 
+fn walk_slopes(trail_map: &Grid<u32>, starts: impl IntoIterator<Item = Point>, height_change: i64) -> HashSet<Point> {
+    //! Every position reachable from `starts` by steps that each change the height by exactly `height_change`.
+    let mut reached = HashSet::new();
+    let mut walk_stack = starts.into_iter().collect::<Vec<_>>();
+    while let Some(position) = walk_stack.pop() {
+        if !reached.insert(position) {
+            continue;
+        }
+        let next_height = trail_map[position] as i64 + height_change;
+        walk_stack.extend(trail_map.neighbors4(position).filter(|next_position| trail_map[*next_position] as i64 == next_height));
+    }
+    reached
+}
+
+pub fn visualize(input: &str) -> anyhow::Result<Scene> {
+    //! The trail map with every position on a trail from a height 0 to a height 9 highlighted,
+    //! along with the trailheads and summits those trails connect.
+    let trail_map = parse_trail_map(input)?;
+    let uphill = walk_slopes(&trail_map, trail_map.find_all(&0), 1);
+    let summits = uphill.iter().copied().filter(|position| trail_map[*position] == 9).collect::<Vec<_>>();
+    //Walking back down from the summits drops the dead ends that never reach a height 9
+    let downhill = walk_slopes(&trail_map, summits.iter().copied(), -1);
+    let trails = uphill.intersection(&downhill).copied().collect::<HashSet<_>>();
+    let trailheads = trails.iter().copied().filter(|position| trail_map[*position] == 0).collect::<Vec<_>>();
+    let heights = trail_map.map(|height| char::from_digit(*height, 10).unwrap_or('?'));
+    Ok(Scene::new(heights)
+        .overlay("trails", Color::GREEN, trails)
+        .overlay("trailheads", Color::BLUE, trailheads)
+        .overlay("summits", Color::RED, summits))
+}
+
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! A `size` by `size` map of hills, one for every 40 positions, each peaking at a height of 9 and sloping down
+    //! one height per step. About one in 10 positions is at a random height instead.
+    let peaks = (0..(size * size / 40).max(1))
+        .map(|_| Point::new(rng.random_range(0..size as i64), rng.random_range(0..size as i64)))
+        .collect::<Vec<_>>();
+    let trail_map = nearest_seeds(size, size, &peaks).map(|(_, distance)| {
+        if rng.random_bool(0.1) {
+            rng.random_range(0..=9)
+        } else {
+            9 - (*distance).min(9) as u32
+        }
+    });
+    trail_map.to_string()
+}
+
 impl SolveAdvent for Day10 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
+    fn parse(input: &str) -> anyhow::Result<()> {
+        parse_trail_map(input)?;
+        Ok(())
+    }
+
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
         //! Sum all of the trailhead scores.
-        let file_contents = read_input_file(path_to_file)?;
-        let trail_map = file_contents.lines().map(|line| line.chars().flat_map(|char| char.to_digit(10)).collect::<Vec<_>>()).collect::<Vec<_>>();
+        let trail_map = parse_trail_map(input)?;
         let trail_heads = TrailBlazer::construct_all_trailheads(&trail_map);
         let mut total_trailhead_score = 0;
         for trail_head in trail_heads {
             total_trailhead_score += get_trailhead_statistics(trail_head, &trail_map).score;
         }
-        println!("Total trailhead score is {}", total_trailhead_score);
-        Ok(())
+        Ok(total_trailhead_score.into())
     }
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
         //! Exact same logic as part1 but add the ranks instead of the score.
-        let file_contents = read_input_file(path_to_file)?;
-        let trail_map = file_contents.lines().map(|line| line.chars().flat_map(|char| char.to_digit(10)).collect::<Vec<_>>()).collect::<Vec<_>>();
+        let trail_map = parse_trail_map(input)?;
         let trail_heads = TrailBlazer::construct_all_trailheads(&trail_map);
         let mut total_trailhead_rank = 0;
         for trail_head in trail_heads {
             total_trailhead_rank += get_trailhead_statistics(trail_head, &trail_map).rank;
         }
-        println!("Total trailhead rank is {}", total_trailhead_rank);
-        Ok(())
+        Ok(total_trailhead_rank.into())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const SMALL_EXAMPLE: &str = "\
+0123
+1234
+8765
+9876
+";
+
+    const LARGER_EXAMPLE: &str = "\
+89010123
+78121874
+87430965
+96549874
+45678903
+32019012
+01329801
+10456732
+";
+
+    #[test]
+    fn part1_small_example() {
+        assert_eq!(solve_example(Day10::solve_part1, SMALL_EXAMPLE), Answer::Integer(1));
+    }
+
+    #[test]
+    fn part1_larger_example() {
+        assert_eq!(solve_example(Day10::solve_part1, LARGER_EXAMPLE), Answer::Integer(36));
+    }
+
+    #[test]
+    fn part2_larger_example() {
+        assert_eq!(solve_example(Day10::solve_part2, LARGER_EXAMPLE), Answer::Integer(81));
+    }
+
+    #[test]
+    fn visualization_highlights_the_trails() {
+        let scene = visualize(LARGER_EXAMPLE).unwrap();
+        let legend = scene.legend().into_iter().map(|(label, _, cell_count)| (label, cell_count)).collect::<Vec<_>>();
+        assert_eq!(legend, vec![("trails", 59), ("trailheads", 9), ("summits", 7)]);
+    }
+}
@@ -1,18 +1,26 @@
-use anyhow::Context;
+use std::collections::HashMap;
 
-use super::{read_input_file, SolveAdvent};
+use num_bigint::BigUint;
+use num_traits::Zero;
+use rand::RngExt;
+
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::input_error::{parse_number, InputError};
 
 pub struct Day11;
 
-fn blink(num_in: i64) -> Vec<i64> {
+const DAY: u8 = 11;
+
+pub fn blink(num_in: i64) -> anyhow::Result<Vec<i64>> {
     //! Blink the stone by way of straightforward application 
-    //! of the rules.
+    //! of the rules. Fails if the new number is too large to engrave.
     if num_in == 0 {
         //Stone with engraved number 0 becomes 1
-        return vec![1];
+        return Ok(vec![1]);
     }
     let num_in_as_str = num_in.to_string();
-    if num_in_as_str.len() % 2 == 0 {
+    if num_in_as_str.len().is_multiple_of(2) {
         //If stone number has an even number of digits, split into two stones
         let num1 = &num_in_as_str[0..num_in_as_str.len() / 2];
         let mut num2 = &num_in_as_str[num_in_as_str.len() / 2 ..];
@@ -24,32 +32,297 @@ fn blink(num_in: i64) -> Vec<i64> {
                 num2 = "0";
             }
         }
-        return vec![num1.parse::<i64>().unwrap(), num2.parse::<i64>().unwrap()];
+        return Ok(vec![num1.parse::<i64>()?, num2.parse::<i64>()?]);
 
     }
     //Replace with one number multiplied by 2024
-    vec![2024 * num_in]
+    let multiplied = num_in.checked_mul(2024).ok_or_else(|| anyhow::anyhow!("Stone {} multiplied by 2024 is too large to engrave", num_in))?;
+    Ok(vec![multiplied])
 }
 
-impl SolveAdvent for Day11 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
-        //! 25 blinks turns out to be low enough that the exponential average growth
-        //! of the `stones` array has not yet become a problem.
-        let file_contents = read_input_file(path_to_file)?;
-        let stones: Result<Vec<_>, _> = file_contents.trim().split(' ').map(|num| num.trim().parse::<i64>()).collect();
-        let mut stones = stones.context("Failed to process input into numbers")?;
-        for _ in 0..25 {
-            let old_stones = std::mem::take(&mut stones);
-            for old_stone in old_stones {
-                stones.extend(blink(old_stone));
+///A row of stones, tracked as how many stones are engraved with each number rather than stone by stone.
+/// Stones never affect each other, so neither their order nor which stone is which ever matters, and only
+/// a few thousand different numbers ever come up however many times the stones blink. Blinking costs the same
+/// for a handful of stones as for a googol of them, bar the time adding up ever longer counts.
+#[derive(Debug, Clone)]
+pub struct StoneEvolution {
+    ///Every number seen so far, at the index it is counted at
+    values: Vec<i64>,
+    indices: HashMap<i64, usize>,
+    ///The indices of the numbers each number `blink`s into, worked out the first time it blinks
+    transitions: Vec<Option<Vec<usize>>>,
+    ///How many stones are engraved with each number. These outgrow any fixed size integer after a few hundred blinks.
+    counts: Vec<BigUint>,
+    ///The counts of the previous blink, kept around so that their memory can be reused
+    spare_counts: Vec<BigUint>,
+    blinks: usize,
+}
+
+impl StoneEvolution {
+    pub fn new(stones: &[i64]) -> Self {
+        let mut evolution = StoneEvolution {
+            values: Vec::new(),
+            indices: HashMap::new(),
+            transitions: Vec::new(),
+            counts: Vec::new(),
+            spare_counts: Vec::new(),
+            blinks: 0,
+        };
+        for stone in stones {
+            let index = evolution.index_of(*stone);
+            evolution.counts[index] += 1u32;
+        }
+        evolution
+    }
+
+    fn index_of(&mut self, value: i64) -> usize {
+        //! The index `value` is counted at, making room for it if it has not been seen before.
+        if let Some(index) = self.indices.get(&value) {
+            return *index;
+        }
+        self.values.push(value);
+        self.transitions.push(None);
+        self.counts.push(BigUint::ZERO);
+        self.indices.insert(value, self.values.len() - 1);
+        self.values.len() - 1
+    }
+
+    pub fn blink(&mut self) -> anyhow::Result<()> {
+        //! Blink every stone once. Each number follows the rules of `blink`, applied to the
+        //! number once no matter how many stones are engraved with it. If any number grows too large
+        //! to engrave, the stones are left as they were.
+        for index in 0..self.values.len() {
+            if self.transitions[index].is_none() && !self.counts[index].is_zero() {
+                let next_indices = blink(self.values[index])?.into_iter().map(|next_value| self.index_of(next_value)).collect();
+                self.transitions[index] = Some(next_indices);
+            }
+        }
+        let mut next_counts = std::mem::take(&mut self.spare_counts);
+        next_counts.iter_mut().for_each(Zero::set_zero);
+        next_counts.resize(self.values.len(), BigUint::ZERO);
+        for (count, transitions) in self.counts.iter().zip(self.transitions.iter()) {
+            if count.is_zero() {
+                continue;
+            }
+            for next_index in transitions.iter().flatten() {
+                next_counts[*next_index] += count;
             }
         }
-        println!("After 25 blinks, there are {} stones", stones.len());
+        self.spare_counts = std::mem::replace(&mut self.counts, next_counts);
+        self.counts.resize(self.values.len(), BigUint::ZERO);
+        self.blinks += 1;
         Ok(())
     }
 
-    fn solve_part2(_path_to_file: &str) -> anyhow::Result<()> {
-        //! Brute force solution on 75 blinks fails to complete.
+    pub fn blinks(&self) -> usize {
+        //! How many times the stones have blinked so far.
+        self.blinks
+    }
+
+    pub fn total_stones(&self) -> BigUint {
+        self.counts.iter().sum()
+    }
+
+    pub fn counts(&self) -> impl Iterator<Item = (i64, &BigUint)> {
+        //! How many stones are engraved with each number, skipping the numbers no stone has right now.
+        self.values.iter().copied().zip(self.counts.iter()).filter(|(_, count)| !count.is_zero())
+    }
+
+    pub fn stats(&self) -> BlinkStats {
+        BlinkStats {
+            blink: self.blinks,
+            distinct_values: self.counts().count(),
+            total_stones: self.total_stones(),
+            largest_value: self.counts().map(|(value, _)| value).max(),
+            values_seen: self.values.len(),
+        }
+    }
+
+    pub fn blink_with_stats(&mut self, blinks: usize) -> anyhow::Result<Vec<BlinkStats>> {
+        //! Blink `blinks` times, recording the stats of the stones before the first blink and after each one.
+        let mut stats = vec![self.stats()];
+        for _ in 0..blinks {
+            self.blink()?;
+            stats.push(self.stats());
+        }
+        Ok(stats)
+    }
+
+    pub fn transition_graph(&self) -> String {
+        //! Which numbers become which when blinking, as a graph in the DOT language. Only the numbers
+        //! the stones have blinked from so far have edges, and a number that splits in two identical halves has two.
+        let mut values = self.values.iter().copied().zip(self.transitions.iter()).collect::<Vec<_>>();
+        values.sort_by_key(|(value, _)| *value);
+        let mut graph = String::from("digraph stones {\n");
+        for (value, transitions) in values {
+            match transitions {
+                Some(next_indices) => {
+                    for next_index in next_indices {
+                        graph += &format!("    \"{}\" -> \"{}\";\n", value, self.values[*next_index]);
+                    }
+                }
+                None => graph += &format!("    \"{}\";\n", value),
+            }
+        }
+        graph + "}\n"
+    }
+}
+
+///Statistics of the stones after some number of blinks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlinkStats {
+    pub blink: usize,
+    ///How many different numbers the stones are engraved with
+    pub distinct_values: usize,
+    pub total_stones: BigUint,
+    ///`None` once there are no stones left, which only happens if there were none to begin with
+    pub largest_value: Option<i64>,
+    ///How many different numbers any stone has been engraved with up to this blink
+    pub values_seen: usize,
+}
+
+pub fn saturation_blink(stats: &[BlinkStats]) -> Option<usize> {
+    //! The blink by which every number the stones can ever be engraved with had been seen, judged by the first
+    //! blink that brought no new numbers. Every number is then known to blink into numbers already seen, so none
+    //! ever will. `None` if the `stats` (of consecutive blinks) kept finding new numbers to the end.
+    stats.windows(2).find(|window| window[0].values_seen == window[1].values_seen).map(|window| window[0].blink)
+}
+
+pub fn count_stones_after_blinks(stones: &[i64], blinks: usize) -> anyhow::Result<BigUint> {
+    //! How many stones the row of `stones` becomes after `blinks` blinks.
+    let mut evolution = StoneEvolution::new(stones);
+    for _ in 0..blinks {
+        evolution.blink()?;
+    }
+    Ok(evolution.total_stones())
+}
+
+pub fn parse_stones(file_contents: &str) -> anyhow::Result<Vec<i64>> {
+    //! Parse the space separated stone numbers, none of which can be negative.
+    let stones: Result<Vec<_>, _> = file_contents.split(' ').filter(|num| !num.is_empty()).map(|num| {
+        match parse_number::<i64>(DAY, file_contents, num)? {
+            stone if stone < 0 => Err(InputError::at(DAY, file_contents, num, "a stone number of 0 or more")),
+            stone => Ok(stone),
+        }
+    }).collect();
+    Ok(stones?)
+}
+
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! `size` stones engraved with numbers of up to 7 digits.
+    (0..size).map(|_| rng.random_range(0..10_000_000u32).to_string()).collect::<Vec<_>>().join(" ")
+}
+
+impl SolveAdvent for Day11 {
+    fn parse(input: &str) -> anyhow::Result<()> {
+        parse_stones(input)?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
+        let stones = parse_stones(input)?;
+        Ok(count_stones_after_blinks(&stones, 25)?.into())
+    }
+
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
+        //! Keeping every stone in a `Vec` fails to complete on 75 blinks, as the row grows exponentially,
+        //! but the number of different engraved numbers does not, so the stones are counted per number instead.
+        let stones = parse_stones(input)?;
+        Ok(count_stones_after_blinks(&stones, 75)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const EXAMPLE: &str = "125 17";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day11::solve_part1, EXAMPLE), Answer::Integer(55312));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_example(Day11::solve_part2, EXAMPLE), Answer::Integer(65601038650482));
+    }
+
+    #[test]
+    fn counting_matches_blinking() {
+        let mut evolution = StoneEvolution::new(&[125, 17]);
+        for _ in 0..6 {
+            evolution.blink().unwrap();
+        }
+        assert_eq!(evolution.total_stones(), BigUint::from(22u32));
+        let mut counts = evolution.counts().map(|(value, count)| (value, count.to_string())).collect::<Vec<_>>();
+        counts.sort();
+        //The example row after 6 blinks is `2097446912 14168 4048 2 0 2 4 40 48 2024 40 48 80 96 2 8 6 7 6 0 3 2`
+        assert_eq!(counts[..4], [(0, "2".to_string()), (2, "4".to_string()), (3, "1".to_string()), (4, "1".to_string())]);
+        assert_eq!(evolution.blinks(), 6);
+    }
+
+    #[test]
+    fn stats_follow_the_stones_blink_by_blink() {
+        let mut evolution = StoneEvolution::new(&[125, 17]);
+        let stats = evolution.blink_with_stats(2).unwrap();
+        //125 17 becomes 253000 1 7 and then 253 0 2024 14168
+        assert_eq!(stats[1], BlinkStats { blink: 1, distinct_values: 3, total_stones: BigUint::from(3u32), largest_value: Some(253000), values_seen: 5 });
+        assert_eq!((stats[2].distinct_values, stats[2].largest_value, stats[2].values_seen), (4, Some(14168), 9));
+        assert_eq!(saturation_blink(&stats), None);
+        let stats = StoneEvolution::new(&[0]).blink_with_stats(40).unwrap();
+        //A 0 stone only ever becomes one of 54 numbers, all of which it has been by blink 16
+        assert_eq!(saturation_blink(&stats), Some(16));
+        assert!(stats[16..].iter().all(|blink_stats| blink_stats.values_seen == 54));
+        assert_eq!(StoneEvolution::new(&[]).stats().largest_value, None);
+    }
+
+    #[test]
+    fn transitions_as_a_graph() {
+        let mut evolution = StoneEvolution::new(&[0, 22]);
+        evolution.blink().unwrap();
+        evolution.blink().unwrap();
+        let expected = "\
+digraph stones {
+    \"0\" -> \"1\";
+    \"1\" -> \"2024\";
+    \"2\" -> \"4048\";
+    \"22\" -> \"2\";
+    \"22\" -> \"2\";
+    \"2024\";
+    \"4048\";
+}
+";
+        assert_eq!(evolution.transition_graph(), expected);
+    }
+
+    #[test]
+    fn counts_outgrow_fixed_size_integers() {
+        let after_500 = count_stones_after_blinks(&[125, 17], 500).unwrap();
+        assert!(after_500.bits() > 128);
+        assert_eq!(Answer::from(count_stones_after_blinks(&[125, 17], 75).unwrap()), Answer::Integer(65601038650482));
+        assert!(matches!(Answer::from(after_500), Answer::Text(_)));
+        assert_eq!(count_stones_after_blinks(&[], 500).unwrap(), BigUint::ZERO);
+    }
+
+    #[test]
+    fn blink_rules() {
+        assert_eq!(blink(0).unwrap(), vec![1]);
+        assert_eq!(blink(1000).unwrap(), vec![10, 0]);
+        assert_eq!(blink(99).unwrap(), vec![9, 9]);
+        assert_eq!(blink(999).unwrap(), vec![2021976]);
+    }
+
+    #[test]
+    fn numbers_too_large_to_engrave_are_errors() {
+        let error = blink(99999999999999999).unwrap_err();
+        assert_eq!(error.to_string(), "Stone 99999999999999999 multiplied by 2024 is too large to engrave");
+        let mut evolution = StoneEvolution::new(&[99999999999999999, 125]);
+        assert!(evolution.blink().is_err());
+        assert_eq!((evolution.blinks(), evolution.total_stones()), (0, BigUint::from(2u32)));
+        assert!(Day11::solve_part1("99999999999999999").is_err());
+        let error = parse_stones("125 -17").unwrap_err().downcast::<InputError>().unwrap();
+        assert_eq!((error.column, error.found.as_str()), (5, "-17"));
+    }
+}
@@ -1,29 +1,35 @@
-use std::collections::HashSet;
-use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 
-use super::{read_input_file, SolveAdvent};
+use rand::RngExt;
+
+use super::{Answer, SolveAdvent};
+use crate::generate::{nearest_seeds, InputRng};
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::input_error::InputError;
+use crate::visualize::{Color, Scene};
 
 pub struct Day12;
 
-type OrderedPair = (i64, i64);
+const DAY: u8 = 12;
 
 ///Represents the key information regarding 
 /// a single continous garden region in the plot.
 #[derive(Debug, Clone)]
-struct GardenRegion {
+pub struct GardenRegion {
     ///Represents all perimeter boundaries of the region
-    perimeter_fences: Vec<PerimeterFenceMarker>,
+    pub perimeter_fences: Vec<PerimeterFenceMarker>,
     ///The unique elements of the region
-    region_elements: HashSet<OrderedPair>
+    pub region_elements: HashSet<Point>
 }
 
 impl GardenRegion {
-    fn get_part1_fence_price(&self) -> usize {
+    pub fn get_part1_fence_price(&self) -> usize {
         //! Part1 fence price is just area * perimeter
         self.region_elements.len() * self.perimeter_fences.len()
     }
 
-    fn get_part2_fence_price(&self) -> usize {
+    pub fn get_part2_fence_price(&self) -> usize {
         //! How to map all edges Algorithm:
         //! 
         //! 1. Select a random fence
@@ -50,20 +56,10 @@ impl GardenRegion {
                 //we are exploring
                 unique_perimeter_fences.remove(&current_explorer);
                 //Continue exploring the current edge by moving probes laterally (orthogonal to the jump direction)
-                let next_explorers = match current_explorer.jump_direction {
-                    Direction::Down | Direction::Up => {
-                        [
-                            current_explorer.slide_left(),
-                            current_explorer.slide_right()
-                        ]
-                    },
-                    Direction::Left | Direction::Right => {
-                        [
-                            current_explorer.slide_up(), 
-                            current_explorer.slide_down()
-                        ]
-                    }
-                };
+                let next_explorers = [
+                    current_explorer.slide(current_explorer.jump_direction.rotate_clockwise()),
+                    current_explorer.slide(current_explorer.jump_direction.rotate_counterclockwise())
+                ];
                 explorer_stack.extend(next_explorers);
 
             }
@@ -74,34 +70,26 @@ impl GardenRegion {
     }
 }
 
-///All 4 possible cardinal directions a perimeter
-/// could `face`. Diagonals are not allowed here.
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-enum Direction {
-    Up,
-    Down, 
-    Right, 
-    Left
-}
-
 ///In order to comprehensively mark 
 /// region perimeters, we mark the inbound position 
 /// and the outbound position, as well as the cardinal direction this
 /// boundary faces.
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-struct PerimeterFenceMarker {
+pub struct PerimeterFenceMarker {
     ///The ordered pair of the inbounds (part of the region) position
     /// that represents the boundary
-    inbounds: OrderedPair,
+    pub inbounds: Point,
     ///The direction from the `inbounds` point that the perimeter points.
     /// If `inbounds` is a corner, its possible for the same `inbounds` to have
     /// two or more fence markers pointing in different directions.
-    jump_direction: Direction
+    pub jump_direction: Direction
 }
 
 impl PerimeterFenceMarker {
-    fn try_new(inbounds: OrderedPair, outbounds: OrderedPair) -> anyhow::Result<Self> {
-        let jump_direction  =  Direction::from_ordered_pairs(inbounds, outbounds)?;
+    pub fn try_new(inbounds: Point, outbounds: Point) -> anyhow::Result<Self> {
+        //! The fence crossed stepping from `inbounds` to the adjacent `outbounds` position.
+        let jump_direction = Direction::from_delta(outbounds - inbounds)
+            .ok_or_else(|| anyhow::anyhow!("{} and {} are not orthogonally adjacent", inbounds, outbounds))?;
         Ok(PerimeterFenceMarker {
             inbounds,
             jump_direction
@@ -109,128 +97,57 @@ impl PerimeterFenceMarker {
     )
     }
 
-    fn slide_left(&self) -> Self {
-        //! Slide laterally left while preserving the jump direction.
-        PerimeterFenceMarker {
-            inbounds: (self.inbounds.0, self.inbounds.1 - 1),
-            jump_direction: self.jump_direction
-        }
-    }
-
-    fn slide_up(&self) -> Self {
-        //! Slide laterally up while preserving jump direction.
-        PerimeterFenceMarker {
-            inbounds: (self.inbounds.0 -1, self.inbounds.1),
-            jump_direction: self.jump_direction
-        }
-    }
-
-    fn slide_down(&self) -> Self {
-        //! Slide laterally down 1 while preserving jump direction.
-        PerimeterFenceMarker {
-            inbounds: (self.inbounds.0 + 1, self.inbounds.1),
-            jump_direction: self.jump_direction
-        }
-    }
-
-    fn slide_right(&self) -> Self {
-        //! Slide laterally right 1 while preserving jump direction.
+    fn slide(&self, direction: Direction) -> Self {
+        //! Slide laterally 1 in `direction` while preserving the jump direction.
         PerimeterFenceMarker {
-            inbounds: (self.inbounds.0, self.inbounds.1 + 1),
+            inbounds: self.inbounds.step(direction),
             jump_direction: self.jump_direction
         }
     }
 }
 
-impl Direction {
-    fn from_ordered_pairs(pair1: OrderedPair, pair2: OrderedPair) -> anyhow::Result<Self> {
-        //! Given two ordered pairs which represent a vector going from `pair1` -> `pair2`, 
-        //! mark the cardinal direction of the flow. This is important for assessing 
-        //! when the perimeter edge has changed.
-        let (row1, col1) = pair1;
-        let (row2, col2) = pair2;
-        if col1 == col2 {
-            match row1.cmp(&row2) {
-                Ordering::Less => return Ok(Direction::Down),
-                Ordering::Greater => return Ok(Direction::Up),
-                _ => {}
-            }
-        }
-        if row1 == row2 {
-            match col1.cmp(&col2) {
-                Ordering::Less => return Ok(Direction::Right),
-                Ordering::Greater =>return  Ok(Direction::Left),
-                _ => {}
-            }
-        }
-        anyhow::bail!("Could not determine the cardinal direction")
-    }
-}
-
-fn safe_map_read<T>(row: i64, col: i64, garden_map: &[Vec<T>]) -> anyhow::Result<&T> 
-{
-    //! Safely read an item from the `garden_map` without any of the boiler plate.
-    if row < 0 || col < 0 {
-        anyhow::bail!("Row or col was negative, but it must be a usize!");
-    }
-    if let Some(row) = garden_map.get(row as usize) {
-        if let Some(item) = row.get(col as usize) {
-            return Ok(item);
-        }
-    }
-    anyhow::bail!("Row {} col {} does not exist in passed in garden map", row, col);
-}
-
 ///A GardenProbe is a convenient data structure used
 /// to probe and explore the garden region.
 struct GardenProbe {
     ///The previous position this probe just visited, if it exists
-    last_position: Option<OrderedPair>,
+    last_position: Option<Point>,
     ///Current probe position.
-    current_position: OrderedPair
+    current_position: Point
 }
 
 impl GardenProbe {
     fn spawn_4(&self) -> impl IntoIterator<Item = Self> {
         //! Returns 4 new probes to continue probing in all 4 cardinal directions
         //! from the current probe's location: Up, down, left, right.
-        [
-                GardenProbe {
-                    last_position: Some(self.current_position),
-                    current_position: (self.current_position.0, self.current_position.1 + 1)
-                },
-                GardenProbe {
-                    last_position: Some(self.current_position),
-                    current_position: (self.current_position.0, self.current_position.1 - 1)
-                },
-                GardenProbe {
-                    last_position: Some(self.current_position),
-                    current_position: (self.current_position.0 + 1, self.current_position.1)
-                },
-                GardenProbe {
-                    last_position: Some(self.current_position),
-                    current_position: (self.current_position.0 - 1, self.current_position.1)
-                },
-            ]
+        let last_position = self.current_position;
+        last_position.neighbors4().map(move |current_position| GardenProbe {
+            last_position: Some(last_position),
+            current_position
+        })
     }
 }
 
 
+pub fn parse_garden_map(file_contents: &str) -> Result<Grid<char>, InputError> {
+    //! Parse the input file into the garden map of plant symbols.
+    Grid::parse_chars(DAY, file_contents)
+}
 
-fn process_garden_region(starting_row: i64, starting_col: i64, garden_map: &[Vec<char>]) -> anyhow::Result<GardenRegion> {
-    //! Analyze a garden region starting from the `starting_row`, `starting_col` position.
+pub fn process_garden_region(starting_position: Point, garden_map: &Grid<char>) -> anyhow::Result<GardenRegion> {
+    //! Analyze a garden region starting from the `starting_position`.
     //! Collects all of the interior region points, and all perimeter fences. 
-    let region_symbol = safe_map_read(starting_row, starting_col, garden_map)?;
+    let region_symbol = garden_map.get(starting_position)
+        .ok_or_else(|| anyhow::anyhow!("Position {} does not exist in passed in garden map", starting_position))?;
     let mut region_elements = HashSet::new(); //All positions in this region being explored
     let mut perimeter_crossings = Vec::new(); // Fences that mark the perimeter of the region.
     let mut traversal_stack = vec![GardenProbe {
-        current_position: (starting_row, starting_col),
+        current_position: starting_position,
         last_position: None
     }
     ];
     while let Some(current_probe)  = traversal_stack.pop() {
         //Traverse the region depth first
-        if let Ok(current_region_symbol) = safe_map_read(current_probe.current_position.0, current_probe.current_position.1, garden_map) {
+        if let Some(current_region_symbol) = garden_map.get(current_probe.current_position) {
             if current_region_symbol == region_symbol {
                 if region_elements.contains(&current_probe.current_position) {
                     //In a cycle, so kill this probe
@@ -256,49 +173,163 @@ fn process_garden_region(starting_row: i64, starting_col: i64, garden_map: &[Vec
     })
 }
 
-impl SolveAdvent for Day12 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
-        let file_contents = read_input_file(path_to_file)?;
-        let garden_map = file_contents.lines().map(|line| line.chars().collect::<Vec<_>>()).collect::<Vec<_>>();
-        //The visited set prevents counting duplicate regions that have already been reached by a previous iteration
-        let mut visited = HashSet::new();
-        let mut total_fence_price = 0 ;
-        for row_number in 0..garden_map.len() {
-            for col_number in 0..garden_map[0].len() {
-                let col_number = col_number as i64;
-                let row_number = row_number as i64;
-                if visited.contains(&(row_number, col_number)) {
-                    continue;
-                }
-                let plot_statistics = process_garden_region(row_number, col_number, &garden_map)?;
-                total_fence_price += plot_statistics.get_part1_fence_price();
-                visited.extend(plot_statistics.region_elements);
-            }
+pub fn find_all_regions(garden_map: &Grid<char>) -> anyhow::Result<Vec<GardenRegion>> {
+    //! Split the whole garden map into its regions.
+    //The visited set prevents counting duplicate regions that have already been reached by a previous iteration
+    let mut visited = HashSet::new();
+    let mut regions = Vec::new();
+    for position in garden_map.positions() {
+        if visited.contains(&position) {
+            continue;
         }
-        println!("Total fence cost is {}", total_fence_price);
+        let region = process_garden_region(position, garden_map)?;
+        visited.extend(region.region_elements.iter().copied());
+        regions.push(region);
+    }
+    Ok(regions)
+}
+
+pub fn visualize(input: &str) -> anyhow::Result<Scene> {
+    //! The garden map with every region highlighted, so that neighboring regions of different plants
+    //! (or separate regions of the same plant) can be told apart.
+    let garden_map = parse_garden_map(input)?;
+    let regions = find_all_regions(&garden_map)?;
+    //Greedily pick each region a palette color that none of its already colored neighbors have
+    let mut region_colors: HashMap<Point, usize> = HashMap::new();
+    let mut scene = Scene::new(garden_map);
+    for region in regions {
+        let neighbor_colors = region.perimeter_fences.iter()
+            .filter_map(|fence| region_colors.get(&fence.inbounds.step(fence.jump_direction)))
+            .copied()
+            .collect::<HashSet<_>>();
+        let color_index = (0..).find(|index| !neighbor_colors.contains(index)).unwrap_or_default();
+        region_colors.extend(region.region_elements.iter().map(|position| (*position, color_index)));
+        scene = scene.overlay("garden regions", Color::from_palette(color_index), region.region_elements);
+    }
+    Ok(scene)
+}
+
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! A `size` by `size` garden of patches of plants, about 30 plots to a patch, with the odd stray plant
+    //! in between. Neighboring patches of the same plant make up a single region.
+    let patch_count = (size * size / 30).max(1);
+    let patches = (0..patch_count)
+        .map(|_| Point::new(rng.random_range(0..size as i64), rng.random_range(0..size as i64)))
+        .collect::<Vec<_>>();
+    let plants = (0..patch_count).map(|_| rng.random_range(b'A'..=b'Z') as char).collect::<Vec<_>>();
+    let garden_map = nearest_seeds(size, size, &patches).map(|(patch, _)| {
+        if rng.random_bool(0.02) {
+            rng.random_range(b'A'..=b'Z') as char
+        } else {
+            plants[*patch]
+        }
+    });
+    garden_map.to_string()
+}
+
+impl SolveAdvent for Day12 {
+    fn parse(input: &str) -> anyhow::Result<()> {
+        parse_garden_map(input)?;
         Ok(())
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
+        let garden_map = parse_garden_map(input)?;
+        let regions = find_all_regions(&garden_map)?;
+        Ok(regions.iter().map(GardenRegion::get_part1_fence_price).sum::<usize>().into())
+    }
+
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
         //! Trickier than part1. All the hard work is done in the `get_part2_fence_price`. The rest
         //! of the code is identical to part1 solution.
-        let file_contents = read_input_file(path_to_file)?;
-        let garden_map = file_contents.lines().map(|line| line.chars().collect::<Vec<_>>()).collect::<Vec<_>>();
-        let mut visited = HashSet::new();
-        let mut total_fence_price = 0 ;
-        for row_number in 0..garden_map.len() {
-            for col_number in 0..garden_map[0].len() {
-                let col_number = col_number as i64;
-                let row_number = row_number as i64;
-                if visited.contains(&(row_number, col_number)) {
-                    continue;
+        let garden_map = parse_garden_map(input)?;
+        let regions = find_all_regions(&garden_map)?;
+        Ok(regions.iter().map(GardenRegion::get_part2_fence_price).sum::<usize>().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const SMALL_EXAMPLE: &str = "\
+AAAA
+BBCD
+BBCC
+EEEC
+";
+
+    const E_SHAPED_EXAMPLE: &str = "\
+EEEEE
+EXXXX
+EEEEE
+EXXXX
+EEEEE
+";
+
+    const INTERIOR_EXAMPLE: &str = "\
+AAAAAA
+AAABBA
+AAABBA
+ABBAAA
+ABBAAA
+AAAAAA
+";
+
+    const LARGER_EXAMPLE: &str = "\
+RRRRIICCFF
+RRRRIICCCF
+VVRRRCCFFF
+VVRCCCJFFF
+VVVVCJJCFE
+VVIVCCJJEE
+VVIIICJJEE
+MIIIIIJJEE
+MIIISIJEEE
+MMMISSJEEE
+";
+
+    #[test]
+    fn part1_small_example() {
+        assert_eq!(solve_example(Day12::solve_part1, SMALL_EXAMPLE), Answer::Integer(140));
+    }
+
+    #[test]
+    fn part1_larger_example() {
+        assert_eq!(solve_example(Day12::solve_part1, LARGER_EXAMPLE), Answer::Integer(1930));
+    }
+
+    #[test]
+    fn part2_small_example() {
+        assert_eq!(solve_example(Day12::solve_part2, SMALL_EXAMPLE), Answer::Integer(80));
+    }
+
+    #[test]
+    fn part2_e_shaped_example() {
+        assert_eq!(solve_example(Day12::solve_part2, E_SHAPED_EXAMPLE), Answer::Integer(236));
+    }
+
+    #[test]
+    fn part2_interior_example() {
+        assert_eq!(solve_example(Day12::solve_part2, INTERIOR_EXAMPLE), Answer::Integer(368));
+    }
+
+    #[test]
+    fn part2_larger_example() {
+        assert_eq!(solve_example(Day12::solve_part2, LARGER_EXAMPLE), Answer::Integer(1206));
+    }
+
+    #[test]
+    fn visualization_colors_neighboring_regions_differently() {
+        let scene = visualize(LARGER_EXAMPLE).unwrap();
+        assert_eq!(scene.overlays().len(), 11);
+        for (position, _) in scene.base().iter() {
+            for neighbor in scene.base().neighbors4(position) {
+                if scene.base()[neighbor] != scene.base()[position] {
+                    assert_ne!(scene.highlight(neighbor), scene.highlight(position));
                 }
-                let plot_statistics = process_garden_region(row_number, col_number, &garden_map)?;
-                total_fence_price += plot_statistics.get_part2_fence_price();
-                visited.extend(plot_statistics.region_elements);
             }
         }
-        println!("Total fence cost is {}", total_fence_price);
-        Ok(())
     }
-}
\ No newline at end of file
+}
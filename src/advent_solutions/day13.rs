@@ -1,29 +1,34 @@
 
-use anyhow::anyhow;
+use rand::RngExt;
 
-use super::{read_input_file, SolveAdvent};
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::input_error::InputError;
+use crate::parsing::{capture_numbers, exact_lines, paragraphs};
 
 pub struct Day13;
 
+const DAY: u8 = 13;
+
 type OrderedPair = (f64, f64);
 
 ///A single Claw Machine.
 /// Every click of `button_a` will move the position
 /// of the claw by x+button_a_dx, y+button_a_dy 
 #[derive(Debug, Clone)]
-struct ClawMachine {
+pub struct ClawMachine {
     ///The delta_x, delta_y moved by the claw whenever
     /// button_a is pressed
-    button_a: OrderedPair,
+    pub button_a: OrderedPair,
     ///The delta_x, delta_y moved by the claw whenever 
     /// button_b is pressed
-    button_b: OrderedPair,
+    pub button_b: OrderedPair,
     ///The ordered pair position of the target prize
-    prize_position: OrderedPair, 
+    pub prize_position: OrderedPair, 
 }
 
 impl ClawMachine {
-    fn try_win_prize_using_algebra(&self) -> Option<i64> {
+    pub fn try_win_prize_using_algebra(&self) -> Option<i64> {
         //!Use Algebra to solve the problem
         //! It turns out there the is exactly one solution to any given Claw Machine 
         //! problem, so there is actually nothing to optimize!
@@ -61,79 +66,73 @@ impl ClawMachine {
         
     }
 
-    fn parse_prize_line_into_ordered_pair(line: &str) -> anyhow::Result<OrderedPair> {
+    pub fn parse_prize_line_into_ordered_pair(line: &str) -> anyhow::Result<OrderedPair> {
         //! Parse lines that look like this `Prize: X=8400, Y=5400` into `8400.0, 5400.0`
-        let trimmed_num = line.replace("Prize:", "").replace("X=", "").replace("Y=", "");
-        let isolated_nums: Result<Vec<_>, _> = trimmed_num.split(',').filter_map(|item| {
-            if !item.is_empty() {
-                return Some(item.trim())
-            }
-            None
-        }).map(|item| item.parse::<f64>()).collect();
-        let [x_plus, y_plus]: [f64; 2] = isolated_nums?.try_into().map_err(|_| anyhow!("Could not coerce into length 2 array"))?;
-        Ok((x_plus, y_plus))
+        Ok(capture_numbers::<f64, 2>(DAY, line, line.trim(), "Prize: X={}, Y={}")?.into())
     }
-    fn parse_button_line_into_ordered_pair(line: &str) -> anyhow::Result<OrderedPair> {
+
+    pub fn parse_button_line_into_ordered_pair(line: &str) -> anyhow::Result<OrderedPair> {
         //! Parse lines that look like this: `Button A: X+94, Y+34` into `94.0, 34.0`
-        //! The chain of `replace` calls is inefficient but I do not need to use Regex to optimize
-        let trimmed_num = line.trim().replace("Button A:", "").replace("Button B:", "").replace("X+", "").replace("Y+", "");
-        let isolated_nums: Result<Vec<_>, _> = trimmed_num.split(',').filter_map(|item| {
-            if !item.is_empty() {
-                return Some(item.trim())
-            }
-            None
-        }).map(|item| item.parse::<f64>()).collect();
-        let [x_plus, y_plus]: [f64; 2] = isolated_nums?.try_into().map_err(|_| anyhow!("Could not coerce into length 2 array"))?;
-        Ok((x_plus, y_plus))
-    
+        let line = line.trim();
+        let pattern = if line.starts_with("Button B") { "Button B: X+{}, Y+{}" } else { "Button A: X+{}, Y+{}" };
+        Ok(capture_numbers::<f64, 2>(DAY, line, line, pattern)?.into())
     }
-    fn construct_claws(file_input: &str) -> anyhow::Result<Vec<ClawMachine>>{
-        //! Parse the file input into claw machines.
-        let mut button_a_content = None;
-        let mut button_b_content = None;
-        let mut prize = None;
-        let mut constructed_claw_machines = Vec::new();
-        for line in file_input.lines() {
-            if line.starts_with("Button A") {
-                button_a_content = Some(ClawMachine::parse_button_line_into_ordered_pair(line)?);
-            }
-            else if line.starts_with("Button B") {
-                button_b_content = Some(ClawMachine::parse_button_line_into_ordered_pair(line)?);
-            }else if line.starts_with("Prize:") {
-                prize = Some(ClawMachine::parse_prize_line_into_ordered_pair(line)?)
-            } else {
-                //We flush the Options whenever the we reach a blank line
-                constructed_claw_machines.push(ClawMachine {
-                    button_a: button_a_content.ok_or(anyhow!("Button A was None when it should be set!"))?,
-                    button_b: button_b_content.ok_or(anyhow!("Button B was None when it should be set!"))?,
-                    prize_position: prize.ok_or(anyhow!("Prize was None when it should be set!"))?,
-                });
-                button_a_content = None;
-                button_b_content = None;
-                prize = None;
-            }
-        }
-        //Flush one more time because the last machine has no trailing empty line
-        constructed_claw_machines.push(ClawMachine {
-            button_a: button_a_content.ok_or(anyhow!("Button A was None when it should be set!"))?,
-            button_b: button_b_content.ok_or(anyhow!("Button B was None when it should be set!"))?,
-            prize_position: prize.ok_or(anyhow!("Prize was None when it should be set!"))?,
-        });
-        Ok(constructed_claw_machines)
+
+    pub fn from_paragraph(paragraph: &str) -> Result<ClawMachine, InputError> {
+        //! Parse the three lines describing a single claw machine.
+        let [button_a, button_b, prize] = exact_lines(DAY, paragraph, "the `Button A`, `Button B` and `Prize` lines of a claw machine")?;
+        Ok(ClawMachine {
+            button_a: capture_numbers::<f64, 2>(DAY, paragraph, button_a, "Button A: X+{}, Y+{}")?.into(),
+            button_b: capture_numbers::<f64, 2>(DAY, paragraph, button_b, "Button B: X+{}, Y+{}")?.into(),
+            prize_position: capture_numbers::<f64, 2>(DAY, paragraph, prize, "Prize: X={}, Y={}")?.into(),
+        })
+    }
+
+    pub fn construct_claws(file_input: &str) -> anyhow::Result<Vec<ClawMachine>>{
+        //! Parse the file input into claw machines, which are separated by blank lines.
+        let claw_machines = paragraphs(file_input).map(|paragraph| paragraph.parse(ClawMachine::from_paragraph));
+        Ok(claw_machines.collect::<Result<Vec<_>, _>>()?)
     }
 }
 
 
 
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! `size` claw machines whose buttons move the claw 10 to 99 along each axis, never both in the same
+    //! direction. About half of the prizes can be won with at most 100 presses of each button.
+    let mut claw_machines = Vec::with_capacity(size);
+    while claw_machines.len() < size {
+        let [a_x, a_y, b_x, b_y] = [(); 4].map(|_| rng.random_range(10..100i64));
+        if a_x * b_y == a_y * b_x {
+            continue;
+        }
+        let (prize_x, prize_y) = if rng.random_bool(0.5) {
+            let (a_presses, b_presses) = (rng.random_range(0..=100), rng.random_range(0..=100));
+            (a_presses * a_x + b_presses * b_x, a_presses * a_y + b_presses * b_y)
+        } else {
+            (rng.random_range(1000..20000), rng.random_range(1000..20000))
+        };
+        claw_machines.push(format!(
+            "Button A: X+{}, Y+{}\nButton B: X+{}, Y+{}\nPrize: X={}, Y={}",
+            a_x, a_y, b_x, b_y, prize_x, prize_y
+        ));
+    }
+    claw_machines.join("\n\n")
+}
+
 impl SolveAdvent for Day13 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
+    fn parse(input: &str) -> anyhow::Result<()> {
+        ClawMachine::construct_claws(input)?;
+        Ok(())
+    }
+
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
         //! Despite the framing of the problem, this is not actually an optimization problem!!!
         //! There are two unknowns and two linear equations for each claw machine. Which means there is always
         //! either no solution or exactly 1 solution (number of A, B presses) that reached the target. Minimizing the tokens
         //! is a red herring, as there is nothing to minimize (there being only 1 solution).
         //! The only question is whether the solution (when it exists) are two whole numbers or not.
-        let file_contents = read_input_file(path_to_file)?;
-        let claw_machines = ClawMachine::construct_claws(&file_contents)?;
+        let claw_machines = ClawMachine::construct_claws(input)?;
         let mut total_tokens_used = 0;
         for claw_machine in claw_machines {
             if let Some(solution) = claw_machine.try_win_prize_using_algebra() {
@@ -141,15 +140,13 @@ impl SolveAdvent for Day13 {
             }
 
         }
-        println!("Total tokens used is {}", total_tokens_used);
-        Ok(())
+        Ok(total_tokens_used.into())
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
         //! Exactly the same solution as part1, but add the massive number to each
         //! prize position before computing. 
-        let file_contents = read_input_file(path_to_file)?;
-        let mut claw_machines = ClawMachine::construct_claws(&file_contents)?;
+        let mut claw_machines = ClawMachine::construct_claws(input)?;
         for claw_machine in claw_machines.iter_mut() {
             claw_machine.prize_position.0 += 10000000000000.0;
             claw_machine.prize_position.1 += 10000000000000.0;
@@ -161,7 +158,68 @@ impl SolveAdvent for Day13 {
             }
 
         }
-        println!("Total tokens used is {}", total_tokens_used);
-        Ok(())
+        Ok(total_tokens_used.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const EXAMPLE: &str = "\
+Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176
+
+Button A: X+17, Y+86
+Button B: X+84, Y+37
+Prize: X=7870, Y=6450
+
+Button A: X+69, Y+23
+Button B: X+27, Y+71
+Prize: X=18641, Y=10279";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day13::solve_part1, EXAMPLE), Answer::Integer(480));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_example(Day13::solve_part2, EXAMPLE), Answer::Integer(875318608908));
+    }
+
+    #[test]
+    fn single_lines_parse_into_ordered_pairs() {
+        assert_eq!(ClawMachine::parse_button_line_into_ordered_pair("Button A: X+94, Y+34").unwrap(), (94.0, 34.0));
+        assert_eq!(ClawMachine::parse_button_line_into_ordered_pair("Button B: X+22, Y+67").unwrap(), (22.0, 67.0));
+        assert_eq!(ClawMachine::parse_prize_line_into_ordered_pair("Prize: X=8400, Y=5400").unwrap(), (8400.0, 5400.0));
+        assert!(ClawMachine::parse_prize_line_into_ordered_pair("Prize: X=8400").is_err());
+    }
+
+    #[test]
+    fn example_3_claw_machine() {
+        //The claw machine quoted in `try_win_prize_using_algebra`: 38 A presses and 86 B presses
+        let claw_machine = ClawMachine {
+            button_a: (17.0, 86.0),
+            button_b: (84.0, 37.0),
+            prize_position: (7870.0, 6450.0)
+        };
+        assert_eq!(claw_machine.try_win_prize_using_algebra(), Some(200));
+    }
+
+    #[test]
+    fn example_2_claw_machine_cannot_win() {
+        let claw_machine = ClawMachine {
+            button_a: (26.0, 66.0),
+            button_b: (67.0, 21.0),
+            prize_position: (12748.0, 12176.0)
+        };
+        assert_eq!(claw_machine.try_win_prize_using_algebra(), None);
+    }
+}
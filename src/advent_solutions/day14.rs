@@ -1,22 +1,34 @@
 use std::collections::HashMap;
-use anyhow::{anyhow, Context};
 
-use super::{read_input_file, SolveAdvent};
+use rand::seq::SliceRandom;
+use rand::RngExt;
+
+use super::{Answer, SolveAdvent};
+use crate::animate::Recorder;
+use crate::generate::InputRng;
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::input_error::{parse_lines, InputError};
+use crate::parsing::capture_numbers;
+use crate::visualize::{Color, Scene};
 
 pub struct Day14;
 
-type OrderedPair = (i64, i64);
+const DAY: u8 = 14;
+
+///The number of rows and columns of the bathroom in the real puzzle
+pub const BOARD_DIMENSIONS: Point = Point::new(103, 101);
 
 ///Represents a Robot guarding the bathroom
 #[derive(Debug, Clone, Copy)]
-struct Robot {
-    position: OrderedPair,
-    velocity: OrderedPair
+pub struct Robot {
+    pub position: Point,
+    pub velocity: Point
 }
 
 ///The position on the map of a given robot
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
-enum Quadrant {
+pub enum Quadrant {
     UpperLeft,
     UpperRight,
     LowerLeft,
@@ -24,21 +36,21 @@ enum Quadrant {
 }
 
 impl Quadrant {
-    fn assign(robot: &Robot, board_dimensions: OrderedPair) -> Option<Self> {
+    pub fn assign(robot: &Robot, board_dimensions: Point) -> Option<Self> {
         //! Try to assign a robot to a quadrant. Any robot in the middle row or column
         //! is not in a quadrant at all according to the problem.
-        let middle_row = board_dimensions.0 /2;
-        let middle_column = board_dimensions.1 / 2;
-        if robot.position.0 < middle_row && robot.position.1 < middle_column {
+        let middle_row = board_dimensions.row /2;
+        let middle_column = board_dimensions.col / 2;
+        if robot.position.row < middle_row && robot.position.col < middle_column {
             return Some(Quadrant::UpperLeft);
         } 
-        else if robot.position.0 < middle_row && robot.position.1 > middle_column {
+        else if robot.position.row < middle_row && robot.position.col > middle_column {
             return Some(Quadrant::UpperRight);
         }
-        else if robot.position.0 > middle_row && robot.position.1 < middle_column {
+        else if robot.position.row > middle_row && robot.position.col < middle_column {
             return Some(Quadrant::LowerLeft);
         }
-        else if robot.position.0 > middle_row && robot.position.1 > middle_column {
+        else if robot.position.row > middle_row && robot.position.col > middle_column {
             return Some(Quadrant::LowerRight);
         }
         None
@@ -47,108 +59,169 @@ impl Quadrant {
 
 
 impl Robot {
-    fn try_new(line: &str) -> anyhow::Result<Robot> {
+    pub fn try_new(line: &str) -> Result<Robot, InputError> {
         //! Parse the input line into a Robot's initial velocity and position. 
-        let [positions, velocities]: [&str; 2] = line.trim().split(' ').collect::<Vec<_>>().try_into().map_err(|_| anyhow!("Could not coerce line into 2 parts"))?;
-        let positions: Result<Vec<_>, _> = positions.replace("p=", "").split(',').map(|component| component.parse::<i64>()).collect();
-        let positions = positions.context("Failed to parse positions into integers")?;
-        let velocities: Result<Vec<_>, _> = velocities.replace("v=", "").split(',').map(|component| component.parse::<i64>() ).collect();
-        let velocities = velocities.context("Failed to parse velocities into integers")?;
-        anyhow::ensure!(velocities.len() == 2, anyhow!("Velocities vec must be length 2!"));
-        anyhow::ensure!(positions.len() == 2, anyhow!("Positions vec must be length 2!"));
+        let [position_x, position_y, velocity_x, velocity_y] = capture_numbers(DAY, line, line, "p={},{} v={},{}")?;
         Ok(Robot {
-            position:  (positions[1], positions[0]), //Order is swapped from problem input because our convention is row, col not the other way around!
-            velocity: (velocities[1], velocities[0])
+            position:  Point::new(position_y, position_x), //Order is swapped from problem input because our convention is row, col not the other way around!
+            velocity: Point::new(velocity_y, velocity_x)
         })
 
     }
 
-    fn tick(&mut self, board_dimensions: OrderedPair) {
+    pub fn tick(&mut self, board_dimensions: Point) {
         //! Advance the robot by a second. Much like Pacman, a robot teleports to the other
         //! side of the board when it runs off the map.
-        self.position = ((self.position.0 + self.velocity.0) % board_dimensions.0, (self.position.1 + self.velocity.1) % board_dimensions.1);
-        if self.position.0 < 0 {
-            self.position.0 += board_dimensions.0
-        }
-        if self.position.1 < 0 {
-            self.position.1 += board_dimensions.1;
-        }
-
+        let moved = self.position + self.velocity;
+        self.position = Point::new(moved.row.rem_euclid(board_dimensions.row), moved.col.rem_euclid(board_dimensions.col));
     }   
 }
 
+pub fn parse_robots(file_contents: &str) -> anyhow::Result<Vec<Robot>> {
+    //! Parse every line of the input file into a `Robot`, of which there must be at least one.
+    if file_contents.is_empty() {
+        return Err(InputError::at_end(DAY, file_contents, "at least one robot").into());
+    }
+    Ok(parse_lines(file_contents, Robot::try_new)?)
+}
 
-impl SolveAdvent for Day14 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
-        let board_dimensions = (103, 101);
-        let file_contents = read_input_file(path_to_file)?;
-        let mut robots = file_contents.lines().map(Robot::try_new).collect::<Result<Vec<_>, _>>()?;
-        //Move the robots for 100 seconds
-        for _ in 0..100 {
-            for robot in robots.iter_mut() {
-                robot.tick(board_dimensions);
-            }
+pub fn safety_factor(mut robots: Vec<Robot>, board_dimensions: Point, seconds: usize) -> usize {
+    //! Move the robots for `seconds`, then multiply together the number of robots in each quadrant.
+    for _ in 0..seconds {
+        for robot in robots.iter_mut() {
+            robot.tick(board_dimensions);
         }
-        //Divide into quadrants and count how many are in each quadrant
-        let mut quadrant_map: HashMap<Quadrant, usize> = HashMap::new();
-        for robot in robots {
-            if let Some(quadrant) = Quadrant::assign(&robot, board_dimensions) {
-                *quadrant_map.entry(quadrant).or_default() += 1;
-            }
+    }
+    //Divide into quadrants and count how many are in each quadrant
+    let mut quadrant_map: HashMap<Quadrant, usize> = HashMap::new();
+    for robot in robots {
+        if let Some(quadrant) = Quadrant::assign(&robot, board_dimensions) {
+            *quadrant_map.entry(quadrant).or_default() += 1;
         }
-        let safety_factor = quadrant_map.values().product::<usize>();
-        println!("Safety factor on board of dimension {}x{} is {}", board_dimensions.0, board_dimensions.1, safety_factor);
+    }
+    quadrant_map.values().product::<usize>()
+}
+
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! `size` robots in the real puzzle's bathroom. Up to half of them come together as a christmas tree at a
+    //! random tick, the rest start anywhere and move at random.
+    let (rows, cols) = (BOARD_DIMENSIONS.row, BOARD_DIMENSIONS.col);
+    let tree_height = (((size / 2) as f64).sqrt() as i64).clamp(1, cols / 2);
+    let tree_top = Point::new(rng.random_range(0..=rows - tree_height), rng.random_range(tree_height - 1..=cols - tree_height));
+    let tree = (0..tree_height)
+        .flat_map(|row| (-row..=row).map(move |col| tree_top + Point::new(row, col)))
+        .collect::<Vec<_>>();
+    let tree_tick = rng.random_range(0..rows * cols);
+    let mut robots = Vec::with_capacity(size);
+    for robot in 0..size {
+        let velocity = Point::new(rng.random_range(-99..=99), rng.random_range(-99..=99));
+        let position = match tree.get(robot) {
+            //Start wherever the robot has to be to reach its place in the tree at `tree_tick`
+            Some(place_in_tree) => {
+                let start = *place_in_tree - velocity * tree_tick;
+                Point::new(start.row.rem_euclid(rows), start.col.rem_euclid(cols))
+            }
+            None => Point::new(rng.random_range(0..rows), rng.random_range(0..cols)),
+        };
+        robots.push(format!("p={},{} v={},{}", position.col, position.row, velocity.col, velocity.row));
+    }
+    robots.shuffle(rng);
+    robots.join("\n")
+}
+
+impl SolveAdvent for Day14 {
+    fn parse(input: &str) -> anyhow::Result<()> {
+        parse_robots(input)?;
         Ok(())
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
-        //! Trying but so far not succeeding to build a function `possible_christmas_tree` that identifies
-        //! when the robots have built out a christmas tree. 
-        let board_dimensions = (103, 101);
-        let file_contents = read_input_file(path_to_file)?;
-        let mut robots = file_contents.lines().map(Robot::try_new).collect::<Result<Vec<_>, _>>()?;
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
+        let robots = parse_robots(input)?;
+        Ok(safety_factor(robots, BOARD_DIMENSIONS, 100).into())
+    }
+
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
+        //! The christmas tree is drawn with robots standing side by side, which robots moving at random
+        //! seldom do, so it is the second at which the most robots have a robot right next to them.
+        let board_dimensions = BOARD_DIMENSIONS;
+        let mut robots = parse_robots(input)?;
+        //After `rows * cols` ticks every robot is back where it started, so there is no point looking any further
+        let repeats_after = board_dimensions.row * board_dimensions.col;
+        let mut tree = (robots_side_by_side(board_dimensions, &robots), 0, robots.clone());
         //Move the robots 1 tick at a time
-        for tick_number in 0.. {
-            if tick_number % 10_000 == 0 {
-                println!("{}", tick_number);
-            }
+        for seconds in 1..repeats_after {
             for robot in robots.iter_mut() {
                 robot.tick(board_dimensions);
             }
-            if possible_christmas_tree(board_dimensions, &robots, 0.8) {
-                println!("Possible christmas tree at {}!", tick_number);
-                draw_board(board_dimensions, &robots);
-                break;
+            let side_by_side = robots_side_by_side(board_dimensions, &robots);
+            if side_by_side > tree.0 {
+                tree = (side_by_side, seconds, robots.clone());
             }
         }
-        Ok(())
+        Ok(Answer::Grid {
+            caption: format!("Christmas tree after {} seconds!", tree.1),
+            rows: draw_board(board_dimensions, &tree.2)
+        })
     }
 }
 
-fn draw_board(board_dimensions: OrderedPair, robots: &[Robot]) {
+pub fn robots_side_by_side(board_dimensions: Point, robots: &[Robot]) -> usize {
+    //! The number of robots with another robot on the tile to their right.
+    let mut occupied = Grid::filled(board_dimensions.row as usize, board_dimensions.col as usize, false);
+    for robot in robots.iter() {
+        occupied[robot.position] = true;
+    }
+    robots.iter()
+        .filter(|robot| occupied.get(robot.position + Point::new(0, 1)) == Some(&true))
+        .count()
+}
+
+pub fn draw_board(board_dimensions: Point, robots: &[Robot]) -> Vec<String> {
     //! Draw the board, with a `*` for each robot position.
-    let (board_rows, board_cols) = board_dimensions;
-    let mut board_drawing = (0..board_rows as usize).map(|_| vec![' '; board_cols as usize]).collect::<Vec<_>>();
+    let mut board_drawing = (0..board_dimensions.row as usize).map(|_| vec![' '; board_dimensions.col as usize]).collect::<Vec<_>>();
     for robot in robots.iter() {
-        board_drawing[robot.position.0 as usize][robot.position.1 as usize] = '*';
+        board_drawing[robot.position.row as usize][robot.position.col as usize] = '*';
     }
-    let top_bottom_drawing = (0..board_dimensions.1 + 2).map(|_| '=').collect::<String>();
-    println!("{}", top_bottom_drawing);
+    let top_bottom_drawing = (0..board_dimensions.col + 2).map(|_| '=').collect::<String>();
+    let mut drawing = vec![top_bottom_drawing.clone()];
     for row in board_drawing {
-        println!("|{}|", row.into_iter().collect::<String>());
+        drawing.push(format!("|{}|", row.into_iter().collect::<String>()));
+    }
+    drawing.push(top_bottom_drawing);
+    drawing
+}
+
+
+pub fn animate_robots(mut robots: Vec<Robot>, board_dimensions: Point, recorder: &mut Recorder) -> anyhow::Result<()> {
+    //! Record the robots moving one second per tick. After `rows * cols` seconds every robot is back
+    //! where it started, so unless told otherwise that is where the animation stops.
+    let repeats_after = (board_dimensions.row * board_dimensions.col) as usize;
+    let last_tick = recorder.options().last_tick.unwrap_or(repeats_after - 1);
+    for tick in 0..=last_tick {
+        recorder.record(tick, || {
+            let floor = Grid::filled(board_dimensions.row as usize, board_dimensions.col as usize, '.');
+            Scene::new(floor).overlay("robots", Color::GREEN, robots.iter().map(|robot| robot.position))
+        })?;
+        for robot in robots.iter_mut() {
+            robot.tick(board_dimensions);
+        }
     }
-    println!("{}", top_bottom_drawing);
-   
+    Ok(())
 }
 
+pub fn animate(input: &str, recorder: &mut Recorder) -> anyhow::Result<()> {
+    //! Record the robots in the real puzzle's bathroom. Pick a tick range around the christmas tree,
+    //! as recording every tick until the robots repeat is a lot of frames.
+    animate_robots(parse_robots(input)?, BOARD_DIMENSIONS, recorder)
+}
 
-fn possible_christmas_tree(board_dimensions: OrderedPair, robots: &[Robot], acceptance_threshold: f64) -> bool {
+pub fn possible_christmas_tree(board_dimensions: Point, robots: &[Robot], acceptance_threshold: f64) -> bool {
     //! Algorithm to identify if a christmas tree could potentially be drawn.
     
     //Index the robots by their `row` position.
     let mut robots_by_row: HashMap<usize, Vec<&Robot>> = HashMap::new();
     for robot in robots.iter() {
-        robots_by_row.entry(robot.position.0 as usize).or_default().push(robot);
+        robots_by_row.entry(robot.position.row as usize).or_default().push(robot);
     }
 
     //Use the top robot (lowest row position) as an anchor to build a vertical symmetry line.
@@ -157,11 +230,11 @@ fn possible_christmas_tree(board_dimensions: OrderedPair, robots: &[Robot], acce
     if robots_at_top_of_map.len() != 1 {
         return false;
     }
-    let top_robot_column = robots_at_top_of_map[0].position.1;   
+    let top_robot_column = robots_at_top_of_map[0].position.col;   
         let mut acceptances = 0;
     //For all rows below the starting row, (greater row number), we
     //look for symmetry around the point of the christmas tree.
-    for remaining_row_number in *min_row_with_robot + 1..board_dimensions.0 as usize {
+    for remaining_row_number in *min_row_with_robot + 1..board_dimensions.row as usize {
         let robots_in_row = if let Some(robots_in_row) = robots_by_row.get(&remaining_row_number) {
             robots_in_row
         } else {
@@ -173,18 +246,97 @@ fn possible_christmas_tree(board_dimensions: OrderedPair, robots: &[Robot], acce
             continue;
 
         } else if robots_in_row.len() == 2 {
-            let distance_to_middle_col_robot1 = robots_in_row[0].position.1 - top_robot_column;
-            let distance_to_middle_col_robot2 = robots_in_row[1].position.1 - top_robot_column;
+            let distance_to_middle_col_robot1 = robots_in_row[0].position.col - top_robot_column;
+            let distance_to_middle_col_robot2 = robots_in_row[1].position.col - top_robot_column;
             if distance_to_middle_col_robot1 + distance_to_middle_col_robot2 != 0 {
                 // println!("Rejecting because two robots of same row are not symmetrical around middle column");
                 // println!("Robots are at positions {:?} and {:?} respectively, with middle column {}", robots_in_row[0].position, robots_in_row[1].position, top_robot_col);
                 continue;
             }
-        } else if robots_in_row.len() == 1  && robots_in_row[0].position.1 != top_robot_column{
+        } else if robots_in_row.len() == 1  && robots_in_row[0].position.col != top_robot_column{
             continue;
             
         }
         acceptances += 1;
     }
     (acceptances as f64 / robots.len() as f64) >= acceptance_threshold
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::animate::AnimationOptions;
+
+    const EXAMPLE: &str = "\
+p=0,4 v=3,-3
+p=6,3 v=-1,-3
+p=10,3 v=-1,2
+p=2,0 v=2,-1
+p=0,0 v=1,3
+p=3,0 v=-2,-2
+p=7,6 v=-1,-3
+p=3,0 v=-1,-2
+p=9,3 v=2,3
+p=7,3 v=-1,2
+p=2,4 v=2,-3
+p=9,5 v=-3,-3
+";
+
+    #[test]
+    fn part1_example() {
+        //The example takes place on a board 7 tall and 11 wide
+        let robots = parse_robots(EXAMPLE).unwrap();
+        assert_eq!(safety_factor(robots, Point::new(7, 11), 100), 12);
+    }
+
+    #[test]
+    fn malformed_robots_are_located() {
+        let error = parse_robots("p=0,4 v=3,-3\np=6,3 v=-1;-3").unwrap_err();
+        let error = error.downcast_ref::<InputError>().unwrap();
+        assert_eq!((error.line, error.column, error.found.as_str()), (2, 9, "-1;-3"));
+        let error = Robot::try_new("p=0,4").unwrap_err();
+        assert_eq!((error.column, error.expected.as_str()), (5, "` v=`"));
+    }
+
+    #[test]
+    fn animation_records_each_second() {
+        let robots = parse_robots(EXAMPLE).unwrap();
+        let options = AnimationOptions { first_tick: 100, last_tick: Some(101), cell_size: 1, ..AnimationOptions::default() };
+        let mut recorder = Recorder::in_memory(options);
+        animate_robots(robots, Point::new(7, 11), &mut recorder).unwrap();
+        let (tick, frame) = &recorder.frames()[0];
+        assert_eq!((*tick, frame.width, frame.height), (100, 11, 7));
+        //After 100 seconds the 12 robots are on 10 different tiles
+        let robot_pixels = frame.pixels.chunks_exact(3).filter(|pixel| *pixel == [87, 204, 153]).count();
+        assert_eq!(robot_pixels, 10);
+        assert_eq!(recorder.frames().len(), 2);
+    }
+
+    #[test]
+    fn robots_teleport_off_the_edge() {
+        let mut robot = Robot::try_new("p=2,4 v=2,-3").unwrap();
+        let positions = (0..5).map(|_| {
+            robot.tick(Point::new(7, 11));
+            robot.position
+        }).collect::<Vec<_>>();
+        assert_eq!(positions, [(1, 4), (5, 6), (2, 8), (6, 10), (3, 1)].map(Point::from));
+    }
+
+    #[test]
+    fn there_must_be_robots() {
+        let error = parse_robots("").unwrap_err().downcast::<InputError>().unwrap();
+        assert_eq!((error.line, error.expected.as_str()), (1, "at least one robot"));
+        assert!(Day14::solve_part2("").is_err());
+    }
+
+    #[test]
+    fn part2_finds_the_planted_tree() {
+        //125 robots plant a tree 7 rows tall, whose bottom row is 13 robots wide
+        let input = generate(125, &mut InputRng::seed_from_u64(14));
+        let Answer::Grid { rows, .. } = Day14::solve_part2(&input).unwrap() else { panic!("Day 14 part 2 draws the tree") };
+        assert!(rows.iter().any(|row| row.contains(&"*".repeat(13))));
+        assert_eq!(robots_side_by_side(Point::new(1, 4), &parse_robots("p=0,0 v=0,0\np=1,0 v=0,0\np=3,0 v=0,0").unwrap()), 1);
+    }
+}
@@ -1,55 +1,54 @@
 use std::collections::{HashMap, HashSet};
 
 use anyhow::anyhow;
+use rand::seq::IndexedRandom;
+use rand::RngExt;
 
-use super::{read_input_file, SolveAdvent};
+use super::{Answer, SolveAdvent};
+use crate::animate::Recorder;
+use crate::generate::InputRng;
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::input_error::InputError;
+use crate::visualize::{Color, Scene};
 
 pub struct Day15;
 
-type OrderedPair = (i64, i64);
-
-#[derive(Debug, Clone, Copy)]
-enum Direction {
-    Left,
-    Right, 
-    Down,
-    Up
-}
+const DAY: u8 = 15;
 
 ///Stores the robot seperate
 /// from the rest of the objects in the world
 /// Because each object can only be in one place at a time, we
 /// can index objects by their current position in the `other_objects` map
-struct WarehouseRuntime {
+pub struct WarehouseRuntime {
     ///The robot is kept seperate because it generates the movement
     /// of all other board objects.
-    robot: BoardObject,
-    //Position indexed map of all board objects NOT the robot
-    other_objects: HashMap<OrderedPair, BoardObject>,
+    pub robot: BoardObject,
+    ///Position indexed map of all board objects NOT the robot
+    pub other_objects: HashMap<Point, BoardObject>,
 }
 
-fn collect_boxes_2d(other_objects: & HashMap<OrderedPair, BoardObject>, current_position: OrderedPair, direction: Direction, boxes_to_move: &mut HashSet<OrderedPair>) -> Option<()> {
+fn collect_boxes_2d(other_objects: & HashMap<Point, BoardObject>, current_position: Point, direction: Direction, boxes_to_move: &mut HashSet<Point>) -> Option<()> {
     //! Collect all affected boxes in the double width scenario, where a robot can potentially push boxes not directly
     //! in the direction of movement due to boxes overlapping.
     //! All collected boxes have their coordinates inserted into the `boxes_to_move` set.
     //! This solution is not perfectly efficient, as it will insert duplicates into the set (which are of course ignored)
 
     //Climb the ladder (either up or down) as long as necessary to collect all boxes to move. 
-    let mut ladder = vec![direction.move_one(current_position)];
+    let mut ladder = vec![current_position.step(direction)];
     while let Some(object_position) = ladder.pop() {
-        let (current_row, current_col) = object_position;
         if let Some(board_object) = other_objects.get(&object_position) {
             if board_object.symbol == ']' {
                 //If a right bracket, then we need to include the matching left bracket, which is 1 to the left
-                let adjacent_box = (current_row, current_col - 1);
+                let adjacent_box = object_position.step(Direction::Left);
                 boxes_to_move.extend([adjacent_box, object_position]);
                 //Add to the ladder
-                ladder.extend([direction.move_one(adjacent_box), direction.move_one(object_position)]);
+                ladder.extend([adjacent_box.step(direction), object_position.step(direction)]);
             } else if board_object.symbol == '[' {
                 //If we have a left bracket, then we automatically add the right bracket
-                let adjacent_box = (current_row, current_col + 1);
+                let adjacent_box = object_position.step(Direction::Right);
                 boxes_to_move.extend([adjacent_box, object_position]);
-                ladder.extend([direction.move_one(adjacent_box), direction.move_one(object_position)]);
+                ladder.extend([adjacent_box.step(direction), object_position.step(direction)]);
             } else if board_object.symbol == '#' {
                 //If we hit a wall while iterating, then we return `None`, as the robot can't move anything
                 return None;
@@ -61,10 +60,10 @@ fn collect_boxes_2d(other_objects: & HashMap<OrderedPair, BoardObject>, current_
     }
 
 
-fn collect_boxes_1d(other_objects: & HashMap<OrderedPair, BoardObject>, mut current_position: OrderedPair, direction: Direction, boxes_to_move: &mut HashSet<OrderedPair>) -> Option<()> {
+fn collect_boxes_1d(other_objects: & HashMap<Point, BoardObject>, mut current_position: Point, direction: Direction, boxes_to_move: &mut HashSet<Point>) -> Option<()> {
     //In the 1d case, we just collect all boxes as we move directly in the `direction`
     loop {
-        current_position = direction.move_one(current_position);
+        current_position = current_position.step(direction);
         match other_objects.get(&current_position) {
             Some(board_object) => {
                 if let ObjectType::Box = board_object.object_type {
@@ -85,29 +84,25 @@ fn collect_boxes_1d(other_objects: & HashMap<OrderedPair, BoardObject>, mut curr
 }
 
 impl WarehouseRuntime {
-    #[allow(dead_code)]
-    fn draw_board(&self) {
-        //! Draw the current board for debugging purposes.
-        let max_row = self.other_objects.keys().max_by_key(|position| position.0).unwrap().0;
-        let max_col = self.other_objects.keys().max_by_key(|position| position.1).unwrap().1;
-        let max_row = max_row.max(self.robot.position.0) as usize;
-        let max_col = max_col.max(self.robot.position.1) as usize;
-        let mut board = (0..=max_row).map(|_| (0..=max_col).map(|_| '.').collect::<Vec<_>>()).collect::<Vec<_>>();
-        board[self.robot.position.0 as usize][self.robot.position.1 as usize] = self.robot.symbol;
+    pub fn board(&self) -> Grid<char> {
+        //! Draw the current board, with a `.` for every empty space.
+        let max_row = self.other_objects.keys().map(|position| position.row).max().unwrap();
+        let max_col = self.other_objects.keys().map(|position| position.col).max().unwrap();
+        let max_row = max_row.max(self.robot.position.row) as usize;
+        let max_col = max_col.max(self.robot.position.col) as usize;
+        let mut board = Grid::filled(max_row + 1, max_col + 1, '.');
+        board[self.robot.position] = self.robot.symbol;
         for other_object in self.other_objects.values() {
-            board[other_object.position.0 as usize][other_object.position.1 as usize] = other_object.symbol;
-        }
-
-        for board_row in board {
-            println!("{:?}", board_row.into_iter().collect::<String>());
+            board[other_object.position] = other_object.symbol;
         }
+        board
     }
 
  
-    fn try_move(&mut self, direction: Direction, double_width: bool) {
+    pub fn try_move(&mut self, direction: Direction, double_width: bool) -> HashSet<Point> {
         //! Try to move the robot, and any boxes that are movable
         //! If `double_width` is True, then for `Up`| `Down` motion, the `2d` collection version
-        //! is used.
+        //! is used. Returns where the boxes that were pushed ended up.
         
         //Step 1: Collect all boxes that can be safely moved. By convention, if nothing can be moved (including the robot),
         //then None is returned.
@@ -120,27 +115,30 @@ impl WarehouseRuntime {
         };
         if box_collection_outcome.is_none() {
             //None returned means we can't move anything so we are done!
-            return;
+            return HashSet::new();
         }
         //2. Move the robot and all objects in the `boxes_to_move` set 1 step in the `direction`
         //Reaching this point means we can at the very least move the robot.
-        self.robot.position = direction.move_one(self.robot.position);
+        self.robot.position = self.robot.position.step(direction);
         //3. In addition, we can move any boxes that were collected
         let mut boxes_after_move = Vec::new();
         for box_to_move in boxes_to_move {
             if let Some(mut removed_box) = self.other_objects.remove(&box_to_move) {
                 //Move the associated box
-                removed_box.position = direction.move_one(removed_box.position);
+                removed_box.position = removed_box.position.step(direction);
                 boxes_after_move.push(removed_box);
             }
         };
         //Put the new boxes back in the `other_objects` map. 
+        let mut pushed_boxes = HashSet::new();
         for moved_box in boxes_after_move {
+            pushed_boxes.insert(moved_box.position);
             self.other_objects.insert(moved_box.position, moved_box);
         };
+        pushed_boxes
     }
 
-    fn try_construct(board_objects: Vec<BoardObject>) -> anyhow::Result<Self> {
+    pub fn try_construct(board_objects: Vec<BoardObject>) -> anyhow::Result<Self> {
         //! Construct a `WarehouseRuntime`
         let mut robot = None;
         let mut other_objects = HashMap::new();
@@ -160,13 +158,14 @@ impl WarehouseRuntime {
     }
 }
 
-fn parse_input_file(file_contents: &str, double_width: bool) -> anyhow::Result<(WarehouseRuntime, Vec<Direction>)> {
+pub fn parse_input_file(file_contents: &str, double_width: bool) -> anyhow::Result<(WarehouseRuntime, Vec<Direction>)> {
     //! Parse the input file into a list of directions for the robot, and the positions
     //! of all objects in the map.
-    let demarcation_point = file_contents.find("\r\n\r\n").ok_or(anyhow!("Input file did not contain an empty line seperating map from directions"))?;
+    let demarcation_point = file_contents.find("\n\n").ok_or_else(|| InputError::at_end(DAY, file_contents, "an empty line seperating map from directions"))?;
     let map_input = &file_contents[0..demarcation_point];
     let directions_input = &file_contents[demarcation_point..];
-    let directions = Direction::from_input_file(directions_input.trim())?;
+    //The directions start after the map and the empty line
+    let directions = parse_directions(directions_input.trim()).map_err(|error| error.on_line(map_input.lines().count() + 2))?;
     let mut board_objects = BoardObject::from_input_file(map_input)?;
     if double_width {
         board_objects = BoardObject::build_double_width_objects(&mut board_objects);
@@ -175,39 +174,47 @@ fn parse_input_file(file_contents: &str, double_width: bool) -> anyhow::Result<(
     Ok((warehouse_runtime, directions))
 }
 
-impl Direction {
-    fn from_input_file(file_contents: &str) -> anyhow::Result<Vec<Direction>> {
-        //! Takes the second half of the input file, which is the list of directions, 
-        //! and parses them into `Direction`.
-        let mut directions = Vec::new();
-        for line in file_contents.lines() {
-            for direction in line.chars() {
-                match direction {
-                    '<' => directions.push(Direction::Left),
-                    '^' => directions.push(Direction::Up),
-                    '>' => directions.push(Direction::Right),
-                    'v' => directions.push(Direction::Down),
-                    other => anyhow::bail!("Encountered illegal direction {}", other)
-                }
-            }
+pub fn parse_directions(file_contents: &str) -> Result<Vec<Direction>, InputError> {
+    //! Takes the second half of the input file, which is the list of directions, 
+    //! and parses them into `Direction`.
+    let mut directions = Vec::new();
+    for line in file_contents.lines() {
+        for (offset, arrow) in line.char_indices() {
+            let direction = Direction::from_arrow(arrow)
+                .ok_or_else(|| InputError::at(DAY, file_contents, &line[offset..offset + arrow.len_utf8()], "one of `<`, `^`, `>` or `v`"))?;
+            directions.push(direction);
         }
-    Ok(directions)
     }
-    fn move_one(&self, current_position: OrderedPair) -> OrderedPair {
-        //! Get the new ordered_pair position by moving 1-step
-        //! in the `self` direction.
-        let (row, col) = current_position;
-        match self {
-            Direction::Down => (row + 1, col),
-            Direction::Left => (row, col -1),
-            Direction::Up => (row - 1, col),
-            Direction::Right => (row, col + 1)
+    Ok(directions)
+}
+
+pub fn animate_warehouse(input: &str, double_width: bool, recorder: &mut Recorder) -> anyhow::Result<()> {
+    //! Record the robot following its directions one move per tick, highlighting the boxes pushed by each move.
+    let (mut warehouse_runtime, directions) = parse_input_file(input, double_width)?;
+    let mut pushed_boxes = HashSet::new();
+    for (tick, direction) in std::iter::once(None).chain(directions.into_iter().map(Some)).enumerate() {
+        if recorder.is_finished(tick) {
+            break;
+        }
+        if let Some(direction) = direction {
+            pushed_boxes = warehouse_runtime.try_move(direction, double_width);
         }
+        recorder.record(tick, || {
+            Scene::new(warehouse_runtime.board())
+                .overlay("pushed boxes", Color::ORANGE, pushed_boxes.iter().copied())
+                .overlay("robot", Color::RED, [warehouse_runtime.robot.position])
+        })?;
     }
+    Ok(())
+}
+
+pub fn animate(input: &str, recorder: &mut Recorder) -> anyhow::Result<()> {
+    //! Record the part2 (double width) warehouse, where pushes can fan out across several boxes.
+    animate_warehouse(input, true, recorder)
 }
 
 #[derive(Debug, Clone, Copy)]
-enum ObjectType {
+pub enum ObjectType {
     ///The object is the robot. There should only
     /// be one in the map
     Robot, 
@@ -223,42 +230,40 @@ enum ObjectType {
 /// second enum `ObjectType`. But its preferable to not change the `ObjectType`
 /// enum directly, as that will break the part1 solution.
 #[derive(Debug, Clone, Copy)]
-struct BoardObject {
-    position: OrderedPair,
-    object_type: ObjectType, 
+pub struct BoardObject {
+    pub position: Point,
+    pub object_type: ObjectType, 
     ///The char representing the object.
     /// This is key in part2, where `[` represents
     /// a left box, and `]` represents a right box
-    symbol: char
+    pub symbol: char
 }
 
 impl BoardObject {
-    fn gps_coordinate_double_width(&self) -> Option<i64> {
+    pub fn gps_coordinate_double_width(&self) -> Option<i64> {
          //! GPS coordinate is only defined for a box, 
         //! and is 100xrow + col
         if self.symbol == '[' {
-            let (row, col) = self.position;
-            return Some(100*row + col);
+            return Some(100*self.position.row + self.position.col);
         }
         None
     }
-    fn gps_coordinate(&self) -> Option<i64> {
+    pub fn gps_coordinate(&self) -> Option<i64> {
         //! GPS coordinate is only defined for a box, 
         //! and is 100xrow + col
         if let ObjectType::Box = self.object_type {
-            let (row, col) = self.position;
-            return Some(100*row + col);
+            return Some(100*self.position.row + self.position.col);
         }
         None
     }
 
-    fn build_double_width_objects(objects: &mut Vec<Self>) -> Vec<Self> {
+    pub fn build_double_width_objects(objects: &mut Vec<Self>) -> Vec<Self> {
         //!Map all of the original objects into the new double width warehouse:
         //! In the double width variants, the `symbol` becomes key to represent the left `[`
         //! and right `]` side of the box
         let mut new_objects = Vec::new();
         while let Some(old_object) = objects.pop() {
-            let (old_row, old_col) = old_object.position;
+            let Point { row: old_row, col: old_col } = old_object.position;
             //Note that now col numbers are always doubled
             match old_object.object_type {
                 ObjectType::Box => {
@@ -266,12 +271,12 @@ impl BoardObject {
                     new_objects.extend(
                         [
                             BoardObject {
-                                position: (old_row, old_col * 2),
+                                position: Point::new(old_row, old_col * 2),
                                 object_type: ObjectType::Box,
                                 symbol: '['
                             },
                             BoardObject {
-                                position: (old_row, old_col * 2 + 1),
+                                position: Point::new(old_row, old_col * 2 + 1),
                                 object_type: ObjectType::Box,
                                 symbol: ']'
                             }
@@ -283,7 +288,7 @@ impl BoardObject {
                     //object for
                     new_objects.push(
                             BoardObject {
-                                position: (old_row, old_col * 2),
+                                position: Point::new(old_row, old_col * 2),
                                 object_type: ObjectType::Robot,
                                 symbol: '@'
                             }
@@ -295,12 +300,12 @@ impl BoardObject {
                     new_objects.extend(
                         [
                             BoardObject {
-                                position: (old_row, old_col * 2),
+                                position: Point::new(old_row, old_col * 2),
                                 object_type: ObjectType::Wall,
                                 symbol: '#'
                             },
                             BoardObject {
-                                position: (old_row, old_col * 2 + 1),
+                                position: Point::new(old_row, old_col * 2 + 1),
                                 object_type: ObjectType::Wall,
                                 symbol: '#'
                             }
@@ -312,39 +317,54 @@ impl BoardObject {
         new_objects
     }
 
-    fn from_input_file(file_contents: &str) -> anyhow::Result<Vec<Self>> {
+    pub fn from_input_file(file_contents: &str) -> Result<Vec<Self>, InputError> {
         //! Parse the first half of the input file into the map
-        let mut board_objects = Vec::new();
-        for (line_number, line) in file_contents.lines().enumerate() {
-            for (col_number, symbol) in line.chars().enumerate() {
-                let row = line_number as i64;
-                let col = col_number as i64;
-                let object_type = match symbol {
-                    '#' => ObjectType::Wall,
-                    'O' => ObjectType::Box,
-                    '@' => ObjectType::Robot,
-                    '.' => {
-                        continue;
-                    }
-                    other => anyhow::bail!("Encountered illegal map character {}", other)
-                };
-                board_objects.push(BoardObject {
-                    position: (row, col),
-                    object_type, 
-                    symbol
-                })
-            }
+        let map = Grid::parse(DAY, file_contents, |symbol| "#O@.".contains(symbol).then_some(symbol), "one of `#`, `O`, `@` or `.`")?;
+        let board_objects = map.iter().filter_map(|(position, &symbol)| {
+            let object_type = match symbol {
+                '#' => ObjectType::Wall,
+                'O' => ObjectType::Box,
+                '@' => ObjectType::Robot,
+                _ => return None
+            };
+            Some(BoardObject {
+                position,
+                object_type, 
+                symbol
+            })
+        }).collect();
+        Ok(board_objects)
+    }
+}
+
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! A `size` by `size` warehouse with walls all around, the robot in the middle and walls in about one in 12
+    //! of the other positions and boxes in one in 4. The robot then makes 8 moves per position, 1000 to a line.
+    let mut warehouse = Grid::filled(size, size, '#');
+    for row in 1..size - 1 {
+        for col in 1..size - 1 {
+            warehouse[Point::new(row as i64, col as i64)] = match rng.random_range(0..12) {
+                0 => '#',
+                1..=3 => 'O',
+                _ => '.',
+            };
         }
-        
-    Ok(board_objects)
     }
+    warehouse[Point::new(size as i64 / 2, size as i64 / 2)] = '@';
+    let moves = (0..8 * size * size).map(|_| Direction::ALL.choose(rng).unwrap().arrow()).collect::<Vec<_>>();
+    let move_lines = moves.chunks(1000).map(|line| line.iter().collect::<String>()).collect::<Vec<_>>();
+    format!("{}\n\n{}", warehouse, move_lines.join("\n"))
 }
 
 impl SolveAdvent for Day15 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
+    fn parse(input: &str) -> anyhow::Result<()> {
+        parse_input_file(input, false)?;
+        Ok(())
+    }
+
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
         //! Apply all of the directions, moving any eligible boxes
-        let file_contents = read_input_file(path_to_file)?;
-        let (mut warehouse_runtime, directions) = parse_input_file(&file_contents, false)?;
+        let (mut warehouse_runtime, directions) = parse_input_file(input, false)?;
         for direction in directions {
             warehouse_runtime.try_move(direction, false);
         }
@@ -354,13 +374,11 @@ impl SolveAdvent for Day15 {
                 total_gps_score += gps_coordinate;
             }
         }
-        println!("Total GPS Coordinate is {}", total_gps_score);
-        Ok(())
+        Ok(total_gps_score.into())
     }
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
         //! Apply all of the directions, moving any eligible boxes.
-        let file_contents = read_input_file(path_to_file)?;
-        let (mut warehouse_runtime, directions) = parse_input_file(&file_contents, true)?;
+        let (mut warehouse_runtime, directions) = parse_input_file(input, true)?;
         for direction in directions {
             warehouse_runtime.try_move(direction, true);
         }
@@ -370,7 +388,62 @@ impl SolveAdvent for Day15 {
                 total_gps_score += gps_coordinate;
             }
         }
-        println!("Total GPS Coordinates with double width boxes is {}", total_gps_score);
-        Ok(())
+        Ok(total_gps_score.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+    use crate::animate::AnimationOptions;
+
+    const SMALL_EXAMPLE: &str = "\
+########
+#..O.O.#
+##@.O..#
+#...O..#
+#.#.O..#
+#...O..#
+#......#
+########
+
+<^^>>>vv<v>>v<<
+";
+
+    const DOUBLE_WIDTH_EXAMPLE: &str = "\
+#######
+#...#.#
+#.....#
+#..OO@#
+#..O..#
+#.....#
+#######
+
+<vv<<^^<<^^
+";
+
+    #[test]
+    fn part1_small_example() {
+        assert_eq!(solve_example(Day15::solve_part1, SMALL_EXAMPLE), Answer::Integer(2028));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn animation_highlights_pushed_boxes() {
+        let options = AnimationOptions { cell_size: 1, ..AnimationOptions::default() };
+        let mut recorder = Recorder::in_memory(options);
+        animate_warehouse(DOUBLE_WIDTH_EXAMPLE, true, &mut recorder).unwrap();
+        //The starting position, then one frame per direction
+        assert_eq!(recorder.frames().len(), 12);
+        let pushed_pixels = |tick: usize| recorder.frames()[tick].1.pixels.chunks_exact(3).filter(|pixel| *pixel == [244, 140, 6]).count();
+        //The first `<` pushes both boxes to the robot's left, the first `^` pushes all 3 boxes at once,
+        //and the last `^` pushes a single box
+        assert_eq!((1..12).map(pushed_pixels).collect::<Vec<_>>(), vec![4, 0, 0, 0, 0, 6, 0, 0, 0, 0, 2]);
+    }
+
+    #[test]
+    fn part2_double_width_example() {
+        //Boxes finish at (1, 5), (2, 7) and (3, 6) once the map is widened
+        assert_eq!(solve_example(Day15::solve_part2, DOUBLE_WIDTH_EXAMPLE), Answer::Integer(618));
+    }
+}
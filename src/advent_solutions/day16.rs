@@ -1,260 +1,163 @@
-use std::{cmp::Ordering, collections::{HashMap, HashSet, VecDeque}};
+use std::collections::HashSet;
 
-use super::{read_input_file, SolveAdvent};
+use super::{Answer, SolveAdvent};
+use crate::generate::{last_corridor, maze, InputRng};
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::input_error::InputError;
+use crate::search::{dijkstra, SearchResult};
+use crate::visualize::{Color, Scene};
 
 pub struct Day16;
 
-type OrderedPair = (i64, i64);
+const DAY: u8 = 16;
 
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-enum Direction {
-    Up,
-    Left,
-    Right, 
-    Down
-}
+///A reindeer's place in the maze: where it stands and which way it faces.
+pub type MazeState = (Point, Direction);
 
-impl Direction {
-    fn rotate_clockwise(&self) -> Self {
-        match self {
-            Direction::Down =>Direction::Left,
-            Direction::Left => Direction::Up,
-            Direction::Up =>Direction::Right,
-            Direction::Right =>  Direction::Down
-        }
-    }
+fn maze_moves(maze: &Grid<char>, (position, direction): MazeState) -> impl Iterator<Item = (MazeState, usize)> {
+    //! The next states from `position` facing `direction`. Stepping forward costs 1 point (unless there is a wall in the way), 
+    //! and turning either way costs 1000 points.
+    let forward = position.step(direction);
+    let step = matches!(maze.get(forward), Some(symbol) if *symbol != '#').then_some(((forward, direction), 1));
+    step.into_iter().chain([
+        ((position, direction.rotate_clockwise()), 1_000),
+        ((position, direction.rotate_counterclockwise()), 1_000),
+    ])
+}
 
-    fn rotate_counterclockwise(&self) -> Self {
-        match self {
-            Direction::Down => Direction::Right,
-            Direction::Left =>  Direction::Down,
-            Direction::Up => Direction::Left,
-            Direction::Right => Direction::Up
-        }
-    }
+pub fn parse_maze(file_contents: &str) -> Result<Grid<char>, InputError> {
+    //! Parse the input file into the maze of symbols.
+    Grid::parse(DAY, file_contents, |symbol| "#.SE".contains(symbol).then_some(symbol), "one of `#`, `.`, `S` or `E`")
+}
 
-    fn move_one(& self, current_positon: OrderedPair) -> OrderedPair {
-        //! Move 1-step in the direction specified
-        let (row, col) = current_positon;
-        match self {
-            Direction::Down => (row + 1, col),
-            Direction::Left => (row, col - 1),
-            Direction::Up => (row - 1, col),
-            Direction::Right => (row, col + 1)
-        }
-    }
+pub fn search_maze(maze: &Grid<char>) -> anyhow::Result<SearchResult<MazeState>> {
+    //! Lowest score search of the maze from S (facing east) until E is reached facing any direction.
+    let start_position = maze.find(&'S').ok_or_else(|| anyhow::anyhow!("No S is present in the maze to start from"))?;
+    Ok(dijkstra(
+        (start_position, Direction::Right),
+        |state| maze_moves(maze, *state),
+        |(position, _)| maze[*position] == 'E',
+    ))
 }
 
-///An object exploring the maze
-#[derive(Debug, Clone)]
-struct MazeRunner<'a> {
-    ///Current maze position
-    position: OrderedPair,
-    ///Current traversal direction
-    direction: Direction,
-    ///This probe's traversal history
-    visited: HashSet<(OrderedPair, Direction)>, 
-    ///The maze being traversed
-    maze: &'a Vec<Vec<char>>,
-    ///Running score along the path. Each turn costs 1000 points,
-    /// each step straight costs 1 point
-    running_score: usize
+pub fn lowest_maze_score(maze: &Grid<char>) -> anyhow::Result<usize> {
+    //! The lowest score of any path from S to E.
+    search_maze(maze)?.goal_distance().ok_or_else(|| anyhow::anyhow!("There is no path from S to E"))
 }
 
-impl std::fmt::Display for MazeRunner<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "MazeRunner(position={:?}, direction={:?}, running_score: {})", self.position, self.direction, self.running_score)
-    }
+pub fn count_tiles_on_optimal_paths(maze: &Grid<char>) -> anyhow::Result<usize> {
+    //! Count the unique positions that lie on at least one of the lowest scoring paths from S to E.
+    let optimal_states = search_maze(maze)?.states_on_optimal_paths();
+    let positions_on_optimal_path = optimal_states.into_iter().map(|(position, _direction)| position).collect::<HashSet<_>>();
+    Ok(positions_on_optimal_path.len())
 }
 
-impl <'a>MazeRunner <'a> {
-    fn find_start_position(maze: &[Vec<char>]) -> anyhow::Result<OrderedPair> {
-        //! Find the start position (the first maze runner)
-        for (row_number, row) in maze.iter().enumerate() {
-            for (col_number, symbol) in row.iter().enumerate() {
-                if *symbol == 'S' {
-                    return Ok((row_number as i64, col_number as i64));
-                }
-            }
-        }
-        anyhow::bail!("No S is present in the maze to start from");
+pub fn visualize(input: &str) -> anyhow::Result<Scene> {
+    //! The maze with every tile on one of the lowest scoring paths from S to E highlighted.
+    let maze = parse_maze(input)?;
+    let optimal_states = search_maze(&maze)?.states_on_optimal_paths();
+    let positions_on_optimal_path = optimal_states.into_iter().map(|(position, _direction)| position).collect::<HashSet<_>>();
+    Ok(Scene::new(maze).overlay("optimal paths", Color::YELLOW, positions_on_optimal_path))
+}
 
-    }
-    fn try_new(maze: &'a Vec<Vec<char>>) -> anyhow::Result<MazeRunner<'a>> {
-        let start_position = MazeRunner::find_start_position(maze)?;
-        Ok(MazeRunner {
-            position: start_position,
-            direction: Direction::Right,
-            visited: HashSet::new(),
-            maze,
-            running_score: 0
-        })
-    }
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! A `size` by `size` maze from the bottom left corner to the top right, like the real puzzle. One in 10
+    //! of the walls between corridors is missing, so that there are several ways (and sometimes several best ways) through.
+    let mut reindeer_maze = maze(size, size, 0.1, rng);
+    reindeer_maze[Point::new(last_corridor(size), 1)] = 'S';
+    reindeer_maze[Point::new(1, last_corridor(size))] = 'E';
+    reindeer_maze.to_string()
+}
 
-    fn in_cycle(&self) -> bool {
-        self.visited.contains(&(self.position, self.direction))
+impl SolveAdvent for Day16 {
+    fn parse(input: &str) -> anyhow::Result<()> {
+        parse_maze(input)?;
+        Ok(())
     }
 
-    fn spawn_next(&self) -> [Self; 3] {
-        //! Spawn the next 3 possible probes from the current maze runner
-        [
-            //Option 1: Move 1 step in the same direction, score increments by 1
-            MazeRunner {
-                position: self.direction.move_one(self.position),
-                direction: self.direction,
-                maze: self.maze,
-                visited: self.visited.clone(), 
-                running_score: self.running_score + 1
-            }, 
-            //Option 2: Rotate Clockwise, which costs 1_000 points
-            MazeRunner {
-                position: self.position,
-                direction: self.direction.rotate_clockwise(),
-                maze: self.maze,
-                visited: self.visited.clone(),
-                running_score: self.running_score + 1_000
-            }, 
-            //Option 3: Rotate Counterclockwise, which costs 1_000 points
-            MazeRunner {
-                position: self.position,
-                direction: self.direction.rotate_counterclockwise(),
-                maze: self.maze,
-                visited: self.visited.clone(),
-                running_score: self.running_score + 1_000
-            }, 
-        ]
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
+        //! Dijkstra's search over (position, facing) states, where turning costs 1000 points
+        //! and stepping forward costs 1.
+        let maze = parse_maze(input)?;
+        Ok(lowest_maze_score(&maze)?.into())
     }
 
-
-    fn visit(&mut self) {
-        //! Add current position to visit history
-        self.visited.insert((self.position, self.direction));
-    }
-    fn destination_reached(&self) -> bool {
-        //! Has the `E` space been reached?
-        if !self.is_valid_space() {
-            return false;
-        }
-        let (row, col) = self.position;
-        if self.maze[row as usize][col as usize] == 'E' {
-            return true;
-        }
-        false
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
+        //! The same search as part1, but walk back through every predecessor on an optimal
+        //! path and count the unique positions.
+        let maze = parse_maze(input)?;
+        Ok(count_tiles_on_optimal_paths(&maze)?.into())
     }
-    fn is_valid_space(&self) -> bool {
-        //! Is the `current_position` a valid space (inbounds and not a wall (`#`))
-        let (row, col)  = self.position;
-        if row < 0 || col < 0 || row >= self.maze.len() as i64 || col >= self.maze[0].len() as i64 {
-            return false;
-        }
-        if self.maze[row as usize][col as usize] == '#' {
-            return false;
-        }
-        true
-    }
-}
-
-///Abstraction of an object that kills probes early as soon as it can 
-/// be determined that the probe in question is not optimal
-struct Optimizer {
-    best_score_tracker: HashMap<(OrderedPair, Direction), usize>,
 }
 
-impl Optimizer {
-    fn new() -> Self {
-        Optimizer {
-            best_score_tracker: HashMap::new()
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const EXAMPLE: &str = "\
+###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############
+";
+
+    const SECOND_EXAMPLE: &str = "\
+#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day16::solve_part1, EXAMPLE), Answer::Integer(7036));
     }
 
-    fn kill_probe(&mut self, current_maze_runner: &MazeRunner) -> bool {
-        //! Kill the probe if the `current_maze_runner` cannot possibly be on an optimal path.
-        if let Some(previous_best_score) =self.best_score_tracker.get_mut(&(current_maze_runner.position, current_maze_runner.direction)) {
-            if current_maze_runner.running_score > *previous_best_score {
-                //A previous probe got here first and had a better score, so the current probe should be killed
-                return true;
-            } else {
-                //Update to the new score
-                *previous_best_score = current_maze_runner.running_score;
-            }
+    #[test]
+    fn part1_second_example() {
+        assert_eq!(solve_example(Day16::solve_part1, SECOND_EXAMPLE), Answer::Integer(11048));
+    }
 
-        } else {
-            self.best_score_tracker.insert((current_maze_runner.position, current_maze_runner.direction), current_maze_runner.running_score); 
-        }
-        false
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_example(Day16::solve_part2, EXAMPLE), Answer::Integer(45));
     }
-}
 
-impl SolveAdvent for Day16 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
-        //! Breadth first search of the maze traveling all possible paths from S to E.
-        //! The lowest scored path is tracked. A black-box `optimizer` tries to kill probes
-        //! as soon as possible to clamp down the programs runtime.
-        //! 
-        //! The current optimizer can be improved. Current program runtime is round 50 seconds, which 
-        //! is obviously not desirable, and can be improved with a better optimizer
-        let file_contents =read_input_file(path_to_file)?;
-        let maze = file_contents.lines().map(|line| line.chars().collect::<Vec<_>>()).collect::<Vec<_>>();
-        let mut traversal_queue = VecDeque::new();
-        traversal_queue.push_back(MazeRunner::try_new(&maze)?);
-        let mut lowest_score = usize::MAX;
-        let mut optimizer = Optimizer::new();
-        while let Some(mut current_maze_runner) = traversal_queue.pop_front() {
-            if !current_maze_runner.is_valid_space() || current_maze_runner.in_cycle() {
-                continue;
-            }
-            if current_maze_runner.destination_reached() {
-                lowest_score = lowest_score.min(current_maze_runner.running_score);
-                continue;
-            }
-            if optimizer.kill_probe(&current_maze_runner) {
-                continue;
-            }
-            current_maze_runner.visit();
-            traversal_queue.extend(current_maze_runner.spawn_next());
-        }
-        println!("Minimum score traversing from S to E is {}", lowest_score);
-        Ok(())
+    #[test]
+    fn part2_second_example() {
+        assert_eq!(solve_example(Day16::solve_part2, SECOND_EXAMPLE), Answer::Integer(64));
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
-        //! Essentially the exact same algorithm as the solution to part1, but
-        //! each unique positions that are on one of the optimial paths is tracked.
-        //! Runtime is around 50 seconds, which can be improved.
-        let file_contents =read_input_file(path_to_file)?;
-        let maze = file_contents.lines().map(|line| line.chars().collect::<Vec<_>>()).collect::<Vec<_>>();
-        let mut traversal_queue = VecDeque::new();
-        traversal_queue.push_back(MazeRunner::try_new(&maze)?);
-        let mut lowest_seen_score = usize::MAX;
-        //Set to keep track of all unique positions that are part of an optimal path through the maze
-        let mut positions_on_optimal_path: HashSet<OrderedPair> = HashSet::new();
-        let mut optimizer = Optimizer::new();
-        while let Some(mut current_maze_runner) = traversal_queue.pop_front() {
-            if !current_maze_runner.is_valid_space() || current_maze_runner.in_cycle() {
-                continue;
-            }
-            current_maze_runner.visit();
-            if current_maze_runner.destination_reached() {
-                match current_maze_runner.running_score.cmp(&lowest_seen_score) {
-                    Ordering::Less => {
-                        lowest_seen_score = current_maze_runner.running_score;
-                    positions_on_optimal_path.clear();
-                    positions_on_optimal_path.extend(current_maze_runner.visited.iter().map(|(position, _direction)| position));
-                    },
-                    Ordering::Equal => {
-                        positions_on_optimal_path.extend(current_maze_runner.visited.iter().map(|(position, _direction)| position));
-                    },
-                    Ordering::Greater => {}
-                }
-                continue;
-            }
-            if optimizer.kill_probe(&current_maze_runner) {
-                continue;
-            }
-            traversal_queue.extend(current_maze_runner.spawn_next());
-        }
-        println!("Unique positions on an optimal path through the maze: {}", positions_on_optimal_path.len());
-        Ok(())
+    #[test]
+    fn visualization_highlights_the_optimal_paths() {
+        let scene = visualize(EXAMPLE).unwrap();
+        assert_eq!(scene.legend(), vec![("optimal paths", Color::YELLOW, 45)]);
+        assert_eq!(scene.highlight(Point::new(13, 1)), Some(Color::YELLOW));
     }
 }
-
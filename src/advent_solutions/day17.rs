@@ -1,40 +1,264 @@
 
-use anyhow::{anyhow, Context};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
 
-use super::{read_input_file, SolveAdvent};
+use anyhow::Context;
+use rand::seq::SliceRandom;
+use rand::RngExt;
+
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::input_error::{parse_number, InputError};
+use crate::parsing::{capture, capture_numbers, exact_lines, exact_paragraphs};
 
 pub struct Day17;
 
+const DAY: u8 = 17;
+
 ///Stores the computer runtime
 #[derive(Debug, Clone)]
-struct ChronospatialComputer {
+pub struct ChronospatialComputer {
     ///Register A value
-    ra: i64,
+    pub ra: i64,
     ///Register B value
-    rb: i64,
+    pub rb: i64,
     ///Register C Value
-    rc: i64, 
+    pub rc: i64, 
     ///Current Instruction Pointer
-    instruction_ptr: usize, 
+    pub instruction_ptr: usize, 
     ///Output buffer for the `out` command
-    out_buffer: Vec<u8>
+    pub out_buffer: Vec<u8>
+}
+
+pub fn parse_input_file(file_contents: &str) -> anyhow::Result<(ChronospatialComputer, Vec<u8>)> {
+    //! Parse the three registers and, after an empty line, the program.
+    let [registers, program] = exact_paragraphs(DAY, file_contents, "an empty line between the registers and the program")?;
+    let [ra, rb, rc] = registers.parse(|registers| {
+        let register_lines: [&str; 3] = exact_lines(DAY, registers, "the `Register A`, `Register B` and `Register C` lines")?;
+        let mut values = [0; 3];
+        for ((value, line), name) in values.iter_mut().zip(register_lines).zip(['A', 'B', 'C']) {
+            [*value] = capture_numbers(DAY, registers, line, &format!("Register {}: {{}}", name))?;
+        }
+        Ok(values)
+    })?;
+    let program_instructions = program.parse(parse_program)?;
+    Ok((ChronospatialComputer::new(ra, rb, rc), program_instructions))
+}
+
+pub fn parse_program(program: &str) -> Result<Vec<u8>, InputError> {
+    //! Parse the `Program: ` line into the opcodes and operands.
+    let [instructions] = capture(DAY, program, program, "Program: {}")?;
+    instructions.split(',').map(|instruction| parse_number::<u8>(DAY, program, instruction)).collect()
+}
+
+///What a combo operand stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComboOperand {
+    ///Operands 0 through 3 are themselves
+    Literal(u8),
+    RegisterA,
+    RegisterB,
+    RegisterC,
+}
+
+impl ComboOperand {
+    pub fn decode(operand: u8) -> anyhow::Result<Self> {
+        //! What the combo `operand` stands for, rejecting the reserved 7 just as `resolve_combo_operand` does.
+        match operand {
+            0..=3 => Ok(ComboOperand::Literal(operand)),
+            4 => Ok(ComboOperand::RegisterA),
+            5 => Ok(ComboOperand::RegisterB),
+            6 => Ok(ComboOperand::RegisterC),
+            other => anyhow::bail!("Encountered illegal operand {}", other),
+        }
+    }
+
+    pub fn encode(&self) -> u8 {
+        match self {
+            ComboOperand::Literal(value) => *value,
+            ComboOperand::RegisterA => 4,
+            ComboOperand::RegisterB => 5,
+            ComboOperand::RegisterC => 6,
+        }
+    }
+}
+
+impl Display for ComboOperand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComboOperand::Literal(value) => write!(f, "{}", value),
+            ComboOperand::RegisterA => write!(f, "A"),
+            ComboOperand::RegisterB => write!(f, "B"),
+            ComboOperand::RegisterC => write!(f, "C"),
+        }
+    }
 }
 
-fn parse_input_file(file_contents: &str) -> anyhow::Result<(ChronospatialComputer, Vec<u8>)> {
-    let register_program_divide = file_contents.find("\n\r").ok_or(anyhow!("Input file did not contain a program/computer init demarcation point"))?;
-    let register_configs = &file_contents[0..register_program_divide];
-    let [ra, rb, rc]: [&str; 3] = register_configs.trim().split("\r\n").collect::<Vec<_>>().try_into().map_err(|_| anyhow!("Failed to coerce register config to exactly 3 items"))?;
-    let ra = ra.replace("Register A:", "").trim().parse::<i64>()?;
-    let rb = rb.replace("Register B:", "").trim().parse::<i64>()?;
-    let rc = rc.replace("Register C:", "").trim().parse::<i64>()?;
-    let computer = ChronospatialComputer::new(ra, rb, rc);
-    let program = &file_contents[register_program_divide..];
-    let program_instructions = program.trim().replace("Program:", "").trim().split(',').map(|num| num.parse::<u8>()).collect::<Result<Vec<_>, _>>()?;
-    Ok((computer, program_instructions))
+///A single decoded instruction, with its operand read the way its opcode reads it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    ///Divide A by 2 to the power of the combo operand, into A
+    Adv(ComboOperand),
+    ///XOR B with the literal operand
+    Bxl(u8),
+    ///The combo operand modulo 8, into B
+    Bst(ComboOperand),
+    ///Jump to the literal operand unless A is 0
+    Jnz(u8),
+    ///XOR B with C. The operand is read but ignored.
+    Bxc(u8),
+    ///Output the combo operand modulo 8
+    Out(ComboOperand),
+    ///Divide A by 2 to the power of the combo operand, into B
+    Bdv(ComboOperand),
+    ///Divide A by 2 to the power of the combo operand, into C
+    Cdv(ComboOperand),
+}
+
+impl Instruction {
+    pub fn decode(opcode: u8, operand: u8) -> anyhow::Result<Self> {
+        let instruction = match opcode {
+            0 => Instruction::Adv(ComboOperand::decode(operand)?),
+            1 => Instruction::Bxl(operand),
+            2 => Instruction::Bst(ComboOperand::decode(operand)?),
+            3 => Instruction::Jnz(operand),
+            4 => Instruction::Bxc(operand),
+            5 => Instruction::Out(ComboOperand::decode(operand)?),
+            6 => Instruction::Bdv(ComboOperand::decode(operand)?),
+            7 => Instruction::Cdv(ComboOperand::decode(operand)?),
+            other => anyhow::bail!("Encountered illegal opcode {}", other),
+        };
+        Ok(instruction)
+    }
+
+    pub fn encode(&self) -> [u8; 2] {
+        //! The opcode and operand of the instruction.
+        match self {
+            Instruction::Adv(operand) => [0, operand.encode()],
+            Instruction::Bxl(operand) => [1, *operand],
+            Instruction::Bst(operand) => [2, operand.encode()],
+            Instruction::Jnz(operand) => [3, *operand],
+            Instruction::Bxc(operand) => [4, *operand],
+            Instruction::Out(operand) => [5, operand.encode()],
+            Instruction::Bdv(operand) => [6, operand.encode()],
+            Instruction::Cdv(operand) => [7, operand.encode()],
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Instruction::Adv(_) => "adv",
+            Instruction::Bxl(_) => "bxl",
+            Instruction::Bst(_) => "bst",
+            Instruction::Jnz(_) => "jnz",
+            Instruction::Bxc(_) => "bxc",
+            Instruction::Out(_) => "out",
+            Instruction::Bdv(_) => "bdv",
+            Instruction::Cdv(_) => "cdv",
+        }
+    }
+
+    pub fn pseudo_code(&self) -> String {
+        //! What the instruction does, as a statement. Dividing by a power of 2 is written as the right shift
+        //! it amounts to, as the registers never go negative.
+        match self {
+            Instruction::Adv(operand) => format!("A = A >> {}", operand),
+            Instruction::Bxl(operand) => format!("B = B ^ {}", operand),
+            Instruction::Bst(ComboOperand::Literal(value)) => format!("B = {}", value),
+            Instruction::Bst(operand) => format!("B = {} % 8", operand),
+            Instruction::Jnz(target) => format!("if A != 0 goto {}", target),
+            Instruction::Bxc(_) => "B = B ^ C".to_string(),
+            Instruction::Out(ComboOperand::Literal(value)) => format!("output {}", value),
+            Instruction::Out(operand) => format!("output {} % 8", operand),
+            Instruction::Bdv(operand) => format!("B = A >> {}", operand),
+            Instruction::Cdv(operand) => format!("C = A >> {}", operand),
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Adv(operand) | Instruction::Bst(operand) | Instruction::Out(operand) | Instruction::Bdv(operand) | Instruction::Cdv(operand) => {
+                write!(f, "{} {}", self.mnemonic(), operand)
+            }
+            Instruction::Bxl(operand) | Instruction::Jnz(operand) => write!(f, "{} {}", self.mnemonic(), operand),
+            //The ignored operand is only worth showing when it is not the usual 0
+            Instruction::Bxc(0) => write!(f, "{}", self.mnemonic()),
+            Instruction::Bxc(operand) => write!(f, "{} {}", self.mnemonic(), operand),
+        }
+    }
+}
+
+pub fn disassemble(program: &[u8]) -> anyhow::Result<Vec<Instruction>> {
+    //! Decode the `program` an instruction (an opcode and its operand) at a time from the start.
+    anyhow::ensure!(program.len().is_multiple_of(2), "The program ends in an opcode without an operand");
+    program.chunks(2)
+        .enumerate()
+        .map(|(index, instruction)| Instruction::decode(instruction[0], instruction[1]).with_context(|| format!("Could not decode the instruction at address {}", index * 2)))
+        .collect()
+}
+
+fn describe_jump_target(instructions: &[Instruction], target: u8) -> String {
+    //! Where jumping to `target` ends up. Jumping past the end halts, and jumping to an odd address
+    //! runs the operands as opcodes and the opcodes as operands.
+    let target = target as usize;
+    match instructions.get(target / 2) {
+        None => "past the end, which halts".to_string(),
+        Some(instruction) if target % 2 == 1 => format!("into the middle of `{}`", instruction),
+        Some(instruction) => format!("to `{}`", instruction),
+    }
+}
+
+pub fn listing(program: &[u8]) -> anyhow::Result<String> {
+    //! A line per instruction of the `program`, with its address, opcode and operand, the instruction
+    //! (combo operands shown as the literal or register they read) and what it does. Jumps also say where they land.
+    let instructions = disassemble(program)?;
+    let mut listing = format!("{:>4}  {:<5}  {:<11}  {}\n", "Addr", "Bytes", "Instruction", "Meaning");
+    for (index, instruction) in instructions.iter().enumerate() {
+        let [opcode, operand] = instruction.encode();
+        let mut meaning = instruction.pseudo_code();
+        if let Instruction::Jnz(target) = instruction {
+            meaning += &format!(", jumping {}", describe_jump_target(&instructions, *target));
+        }
+        listing += &format!("{:>4}  {:<5}  {:<11}  {}\n", index * 2, format!("{},{}", opcode, operand), instruction.to_string(), meaning);
+    }
+    Ok(listing)
+}
+
+pub fn pseudo_code(program: &[u8]) -> anyhow::Result<String> {
+    //! The `program` as pseudo code. Programs shaped like the puzzle inputs, whose only jump is a closing `jnz 0`,
+    //! are a `do`/`while` loop. Any other jumps become labels and `goto`s.
+    let instructions = disassemble(program)?;
+    let indent = |statements: &[Instruction]| statements.iter().map(|instruction| format!("    {}\n", instruction.pseudo_code())).collect::<String>();
+    let jumps = instructions.iter().filter(|instruction| matches!(instruction, Instruction::Jnz(_))).count();
+    if let [body @ .., Instruction::Jnz(0)] = instructions.as_slice() {
+        if jumps == 1 {
+            return Ok(format!("do {{\n{}}} while A != 0\n", indent(body)));
+        }
+    }
+    let targets = instructions.iter().filter_map(|instruction| match instruction {
+        Instruction::Jnz(target) if (*target as usize) < program.len() => Some(*target as usize),
+        _ => None,
+    }).collect::<HashSet<_>>();
+    let mut pseudo_code = String::new();
+    for (index, instruction) in instructions.iter().enumerate() {
+        if targets.contains(&(index * 2)) {
+            pseudo_code += &format!("{}:\n", index * 2);
+        }
+        if targets.contains(&(index * 2 + 1)) {
+            pseudo_code += &format!("    //Jumping to {} runs the operand below as an opcode\n", index * 2 + 1);
+        }
+        pseudo_code += &match instruction {
+            Instruction::Jnz(target) if (*target as usize) >= program.len() => "    if A != 0 halt\n".to_string(),
+            _ => indent(&[*instruction]),
+        };
+    }
+    Ok(pseudo_code)
 }
 
 impl ChronospatialComputer {
-    fn new(ra: i64, rb: i64, rc: i64) -> Self {
+    pub fn new(ra: i64, rb: i64, rc: i64) -> Self {
         ChronospatialComputer {
             ra,
             rb,
@@ -43,7 +267,7 @@ impl ChronospatialComputer {
             out_buffer: Vec::new()
         }
     }
-    fn resolve_combo_operand(&self, operand: u8) -> anyhow::Result<i64> {
+    pub fn resolve_combo_operand(&self, operand: u8) -> anyhow::Result<i64> {
         //! Resolve the combo `operand` to a value
         match operand {
             0..=3 => Ok(operand  as i64),
@@ -54,7 +278,7 @@ impl ChronospatialComputer {
             other => anyhow::bail!("Encountered illegal operand {}", other),
         }
     }
-     fn execute_instruction(&mut self, opcode: u8, operand: u8) -> anyhow::Result<()> {
+    pub fn execute_instruction(&mut self, opcode: u8, operand: u8) -> anyhow::Result<()> {
         //! Match and execute the opcode
         match opcode {
             0 => self.adv(operand).context(format!("Error executing adv command with opcode {} and operand {}", opcode, operand))?,
@@ -84,7 +308,7 @@ impl ChronospatialComputer {
         true
     }
 
-    fn try_copy_program(&mut self, program: &[u8]) -> anyhow::Result<bool> {
+    pub fn try_copy_program(&mut self, program: &[u8]) -> anyhow::Result<bool> {
         //! Execute the passed in `program` as normal, but exit early if it becomes
         //! apparent that the program copy attempt will fail.
         while let Some(instructions) = program.get(self.instruction_ptr..self.instruction_ptr + 2) {
@@ -99,7 +323,7 @@ impl ChronospatialComputer {
         Ok(self.out_buffer == program)
     }
 
-    fn run(&mut self, program: &[u8]) -> anyhow::Result<()> {
+    pub fn run(&mut self, program: &[u8]) -> anyhow::Result<()> {
         //! Run the program until the instruction pointer goes off the map
         while let Some(instructions) = program.get(self.instruction_ptr..self.instruction_ptr + 2) {
             let opcode = instructions[0];
@@ -109,10 +333,17 @@ impl ChronospatialComputer {
         Ok(())
     }
 
-    fn adv(&mut self, operand: u8) -> anyhow::Result<()> {
+    fn divide_register_a(&self, operand: u8) -> anyhow::Result<i64> {
+        //! Register A divided by 2 to the power of the combo `operand`, truncated. Done by shifting, as
+        //! register A can hold more bits than an `f64` would keep exactly.
         let combo_operand = self.resolve_combo_operand(operand)?;
-        let result = (self.ra as f64) / 2.0_f64.powi(combo_operand as i32);
-        self.ra = result.trunc() as i64;
+        u32::try_from(combo_operand).ok()
+            .and_then(|shift| self.ra.checked_shr(shift))
+            .ok_or_else(|| anyhow::anyhow!("Cannot divide register A by 2 to the power of {}", combo_operand))
+    }
+
+    fn adv(&mut self, operand: u8) -> anyhow::Result<()> {
+        self.ra = self.divide_register_a(operand)?;
         self.instruction_ptr += 2;
         Ok(())
     }
@@ -154,47 +385,370 @@ impl ChronospatialComputer {
     }
 
     fn bdv(&mut self, opcode: u8) -> anyhow::Result<()> {
-        let combo_operand = self.resolve_combo_operand(opcode)?;
-        let result = (self.ra as f64) / 2.0_f64.powi(combo_operand as i32);
-        self.rb = result.trunc() as i64;
+        self.rb = self.divide_register_a(opcode)?;
         self.instruction_ptr += 2;
         Ok(())
     }
 
     fn cdv(&mut self, opcode: u8) -> anyhow::Result<()> {
-        let combo_operand = self.resolve_combo_operand(opcode)?;
-        let result = (self.ra as f64) / 2.0_f64.powi(combo_operand as i32);
-        self.rc = result.trunc() as i64;
+        self.rc = self.divide_register_a(opcode)?;
         self.instruction_ptr += 2;
         Ok(())
     }
 }
 
+pub fn assemble(source: &str) -> Result<Vec<u8>, InputError> {
+    //! Compile assembly `source` into the program `parse_input_file` would have read. Each line holds an
+    //! instruction as the disassembler writes it, a mnemonic and its operand (`adv 3`, `out A`, `bxc`), and can
+    //! start with a `label:` for `jnz` to jump to instead of an address. Anything after a `;` is a comment.
+    //Labels can be jumped to before they are defined, so they are all found before any instruction is compiled
+    let mut labels = HashMap::new();
+    let mut statements = Vec::new();
+    for (line_index, line) in source.lines().enumerate() {
+        let mut code = line.split(';').next().unwrap_or_default().trim();
+        if let Some((label, rest)) = code.split_once(':') {
+            let label = label.trim();
+            let is_identifier = label.starts_with(|char: char| char.is_ascii_alphabetic() || char == '_')
+                && label.chars().all(|char| char.is_ascii_alphanumeric() || char == '_');
+            if !is_identifier {
+                return Err(InputError::at(DAY, line, label, "a label made of letters, digits and underscores").on_line(line_index + 1));
+            }
+            if labels.insert(label, statements.len() * 2).is_some() {
+                return Err(InputError::at(DAY, line, label, "a label that is not already defined").on_line(line_index + 1));
+            }
+            code = rest.trim();
+        }
+        if !code.is_empty() {
+            statements.push((line_index, line, code));
+        }
+    }
+    let mut program = Vec::new();
+    for (line_index, line, code) in statements {
+        let instruction = assemble_instruction(line, code, &labels).map_err(|error| error.on_line(line_index + 1))?;
+        program.extend(instruction.encode());
+    }
+    Ok(program)
+}
+
+fn assemble_instruction(line: &str, code: &str, labels: &HashMap<&str, usize>) -> Result<Instruction, InputError> {
+    //! Compile the `code` of a single `line` of assembly, with its comment and label already removed.
+    let mut words = code.split_whitespace();
+    let mnemonic = words.next().unwrap_or(code);
+    let operand = words.next();
+    if let Some(extra) = words.next() {
+        return Err(InputError::at(DAY, line, extra, "the end of the instruction"));
+    }
+    let literal = |operand: &str| match operand.parse::<u8>() {
+        Ok(value) if value < 8 => Ok(value),
+        _ => Err(InputError::at(DAY, line, operand, "a literal operand from 0 to 7")),
+    };
+    let combo = |operand: &str| match operand {
+        "A" => Ok(ComboOperand::RegisterA),
+        "B" => Ok(ComboOperand::RegisterB),
+        "C" => Ok(ComboOperand::RegisterC),
+        //4 to 6 are how the registers are encoded, and 7 is reserved
+        _ => match operand.parse::<u8>() {
+            Ok(value) if value < 4 => Ok(ComboOperand::Literal(value)),
+            _ => Err(InputError::at(DAY, line, operand, "a combo operand of 0 to 3, A, B or C")),
+        },
+    };
+    let jump_target = |operand: &str| match labels.get(operand) {
+        Some(address) if *address < 8 => Ok(*address as u8),
+        Some(_) => Err(InputError::at(DAY, line, operand, "a label within the first 8 addresses, as far as a 3 bit operand reaches")),
+        None if operand.starts_with(|char: char| char.is_ascii_digit()) => literal(operand),
+        None => Err(InputError::at(DAY, line, operand, "a defined label or an address from 0 to 7")),
+    };
+    let required = |expected: &str| operand.ok_or_else(|| InputError::at_end(DAY, line, expected));
+    let instruction = match mnemonic {
+        "adv" => Instruction::Adv(combo(required("a combo operand")?)?),
+        "bxl" => Instruction::Bxl(literal(required("a literal operand")?)?),
+        "bst" => Instruction::Bst(combo(required("a combo operand")?)?),
+        "jnz" => Instruction::Jnz(jump_target(required("a label or address to jump to")?)?),
+        //The operand is ignored, so it can be left out
+        "bxc" => Instruction::Bxc(operand.map(literal).transpose()?.unwrap_or(0)),
+        "out" => Instruction::Out(combo(required("a combo operand")?)?),
+        "bdv" => Instruction::Bdv(combo(required("a combo operand")?)?),
+        "cdv" => Instruction::Cdv(combo(required("a combo operand")?)?),
+        _ => return Err(InputError::at(DAY, line, mnemonic, "an instruction (adv, bxl, bst, jnz, bxc, out, bdv or cdv)")),
+    };
+    Ok(instruction)
+}
+
+pub fn check_octal_loop(program: &[u8]) -> anyhow::Result<()> {
+    //! Check that `program` has the shape every puzzle input has, which `find_quine` relies on: a single loop
+    //! back to the start (a closing `jnz 0`) that outputs one value and shifts register A down by one octal
+    //! digit (an `adv 3`) every time around. It then outputs one value per octal digit of register A.
+    let count_opcode = |opcode: u8| program.chunks(2).filter(|instruction| instruction[0] == opcode).count();
+    anyhow::ensure!(
+        program.len().is_multiple_of(2) && program.ends_with(&[3, 0]) && count_opcode(3) == 1,
+        "The program does not end in the only jump, a jnz 0 back to the start"
+    );
+    anyhow::ensure!(
+        count_opcode(0) == 1 && program.chunks(2).any(|instruction| instruction == [0, 3]),
+        "The program does not shift register A by 3 bits (adv 3) once per loop, and only then"
+    );
+    anyhow::ensure!(count_opcode(5) == 1, "The program does not output exactly one value per loop");
+    Ok(())
+}
+
+pub fn find_quine(computer: &ChronospatialComputer, program: &[u8]) -> anyhow::Result<Option<i64>> {
+    //! The smallest register A that makes the `computer` output a copy of the `program`, if any does.
+    find_register_a(computer, program, program)
+}
+
+pub fn find_register_a(computer: &ChronospatialComputer, program: &[u8], target: &[u8]) -> anyhow::Result<Option<i64>> {
+    //! The smallest register A that makes the `computer` running `program` output the `target`. As the program outputs
+    //! a value per octal digit of A, and each value only depends on that digit and the ones above it, A is pieced
+    //! together a digit at a time from the most significant: the top digit has to output the last value of
+    //! the target, the top two digits its last two values, and so on. Trying the digits in ascending order
+    //! means the first A to output the whole target is the smallest.
+    check_octal_loop(program)?;
+    anyhow::ensure!(target.len() <= 20, "Register A cannot have the {} octal digits needed to output {} values", target.len(), target.len());
+    extend_register_a(computer, program, target, 0)
+}
+
+fn extend_register_a(computer: &ChronospatialComputer, program: &[u8], target: &[u8], register_a: i64) -> anyhow::Result<Option<i64>> {
+    //! Append each octal digit to `register_a`, whose digits so far output the end of the `target`,
+    //! and carry on with those that output one more value of it.
+    for digit in 0..8 {
+        let candidate = register_a * 8 + digit;
+        if candidate == 0 {
+            //A register A of 0 never gets past the first loop
+            continue;
+        }
+        let mut candidate_computer = computer.clone();
+        candidate_computer.ra = candidate;
+        candidate_computer.run(program)?;
+        if !target.ends_with(&candidate_computer.out_buffer) {
+            continue;
+        }
+        if candidate_computer.out_buffer.len() == target.len() {
+            return Ok(Some(candidate));
+        }
+        if let Some(register_a) = extend_register_a(computer, program, target, candidate)? {
+            return Ok(Some(register_a));
+        }
+    }
+    Ok(None)
+}
+
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! A program shaped like the real ones, looping once per 3 bits of register A: it takes the lowest 3 bits,
+    //! scrambles them with some of the higher bits, outputs the result and shifts A down by 3 bits. Register A
+    //! starts with `size` octal digits, so the program outputs `size` values.
+    let register_a = rng.random_range(8i64.pow(size as u32 - 1)..8i64.pow(size as u32));
+    let [first_xor, second_xor, ignored] = [(); 3].map(|_| rng.random_range(0..8));
+    //bst A, bxl, cdv B, then bxl and bxc in either order
+    let mut instructions = vec![[2, 4], [1, first_xor], [7, 5]];
+    let mut scrambling = [[1, second_xor], [4, ignored]];
+    scrambling.shuffle(rng);
+    instructions.extend(scrambling);
+    //out B, with adv 3 anywhere after the cdv, and a jnz 0 back to the start
+    instructions.push([5, 5]);
+    instructions.insert(rng.random_range(3..=instructions.len()), [0, 3]);
+    instructions.push([3, 0]);
+    let program = instructions.iter().flatten().map(|value| value.to_string()).collect::<Vec<_>>();
+    format!("Register A: {}\nRegister B: 0\nRegister C: 0\n\nProgram: {}", register_a, program.join(","))
+}
+
 impl SolveAdvent for Day17 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
-        let file_contents = read_input_file(path_to_file)?;
-        let (mut computer, program_instructions) = parse_input_file(&file_contents)?;
-        computer.run(&program_instructions)?;
-        println!("Out contains the following: {:?}", computer.out_buffer.iter().map(|num| num.to_string()).collect::<Vec<_>>().join(","));
+    fn parse(input: &str) -> anyhow::Result<()> {
+        parse_input_file(input)?;
         Ok(())
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
-        //! Brute force solution will not resolve in a reasonable amount of time
-        let file_contents = read_input_file(path_to_file)?;
-        let (computer, program_instructions) = parse_input_file(&file_contents)?;
-        for register_a_init in 1..=i64::MAX {
-            if register_a_init % 1_000_000 == 0 {
-                println!("At value {}", register_a_init);
-            }
-            let mut computer = computer.clone();
-            computer.ra = register_a_init;
-            if computer.try_copy_program(&program_instructions)? {
-                println!("Register A init value {} generates out buffer copy of input program", register_a_init);
-                break;
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
+        let (mut computer, program_instructions) = parse_input_file(input)?;
+        computer.run(&program_instructions)?;
+        Ok(computer.out_buffer.iter().map(|num| num.to_string()).collect::<Vec<_>>().join(",").into())
+    }
+
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
+        //! Trying every register A one by one never finishes, so piece it together an octal digit at a time instead.
+        let (computer, program_instructions) = parse_input_file(input)?;
+        let register_a = find_quine(&computer, &program_instructions)?
+            .ok_or_else(|| anyhow::anyhow!("No register A init value generates an out buffer copy of the input program"))?;
+        //The search already ran every candidate, but make sure of the answer all the same
+        let mut quine_computer = computer.clone();
+        quine_computer.ra = register_a;
+        quine_computer.run(&program_instructions)?;
+        anyhow::ensure!(quine_computer.out_buffer == program_instructions, "Register A {} does not output a copy of the program", register_a);
+        Ok(register_a.into())
+    }
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+    use rand::SeedableRng;
+
+    const EXAMPLE: &str = "\
+Register A: 729
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0
+";
+
+    const QUINE_EXAMPLE: &str = "\
+Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day17::solve_part1, EXAMPLE), Answer::from("4,6,3,5,6,3,5,2,1,0"));
+    }
+
+    #[test]
+    fn part2_quine_example() {
+        assert_eq!(solve_example(Day17::solve_part2, QUINE_EXAMPLE), Answer::Integer(117440));
+    }
+
+    #[test]
+    fn small_programs() {
+        //If register C contains 9, the program 2,6 would set register B to 1
+        let mut computer = ChronospatialComputer::new(0, 0, 9);
+        computer.run(&[2, 6]).unwrap();
+        assert_eq!(computer.rb, 1);
+        //If register A contains 2024, the program 0,1,5,4,3,0 would output 4,2,5,6,7,7,7,7,3,1,0 and leave 0 in register A
+        let mut computer = ChronospatialComputer::new(2024, 0, 0);
+        computer.run(&[0, 1, 5, 4, 3, 0]).unwrap();
+        assert_eq!(computer.out_buffer, vec![4, 2, 5, 6, 7, 7, 7, 7, 3, 1, 0]);
+        assert_eq!(computer.ra, 0);
+        //If register B contains 2024 and register C contains 43690, the program 4,0 would set register B to 44354
+        let mut computer = ChronospatialComputer::new(0, 2024, 43690);
+        computer.run(&[4, 0]).unwrap();
+        assert_eq!(computer.rb, 44354);
+    }
+
+    #[test]
+    fn registers_wider_than_an_f64_divide_exactly() {
+        //20 octal digits of 7, shifted off one at a time
+        let mut computer = ChronospatialComputer::new(1152921504606846975, 0, 0);
+        computer.run(&[0, 3, 5, 4, 3, 0]).unwrap();
+        assert_eq!(computer.out_buffer, [vec![7; 19], vec![0]].concat());
+        let mut computer = ChronospatialComputer::new(1, 64, 0);
+        let error = computer.run(&[6, 5]).unwrap_err();
+        assert_eq!(format!("{:#}", error), "Error executing bdv command with opcode 6 and operand 5: Cannot divide register A by 2 to the power of 64");
+    }
+
+    #[test]
+    fn register_a_is_found_for_generated_programs() {
+        for seed in 0..20 {
+            let input = generate(16, &mut InputRng::seed_from_u64(seed));
+            let (mut computer, program) = parse_input_file(&input).unwrap();
+            let original_register_a = computer.ra;
+            let initial_computer = computer.clone();
+            computer.run(&program).unwrap();
+            let register_a = find_register_a(&initial_computer, &program, &computer.out_buffer).unwrap().unwrap();
+            //Several register As can output the same values, and the one found is the smallest
+            assert!(register_a <= original_register_a);
+            let mut found_computer = initial_computer.clone();
+            found_computer.ra = register_a;
+            found_computer.run(&program).unwrap();
+            assert_eq!(found_computer.out_buffer, computer.out_buffer);
+        }
+        //The example program outputs a 0 last whatever register A is
+        let computer = ChronospatialComputer::new(0, 0, 0);
+        assert_eq!(find_register_a(&computer, &[0, 3, 5, 4, 3, 0], &[1]).unwrap(), None);
+    }
+
+    #[test]
+    fn quines_need_an_octal_loop() {
+        let computer = ChronospatialComputer::new(0, 0, 0);
+        let error = find_quine(&computer, &[0, 3, 5, 4, 3, 2]).unwrap_err();
+        assert_eq!(error.to_string(), "The program does not end in the only jump, a jnz 0 back to the start");
+        let error = find_quine(&computer, &[0, 1, 5, 4, 3, 0]).unwrap_err();
+        assert_eq!(error.to_string(), "The program does not shift register A by 3 bits (adv 3) once per loop, and only then");
+        let error = find_quine(&computer, &[0, 3, 5, 4, 5, 4, 3, 0]).unwrap_err();
+        assert_eq!(error.to_string(), "The program does not output exactly one value per loop");
+    }
+
+    #[test]
+    fn instructions_decode_and_encode() {
+        for opcode in 0..8 {
+            for operand in 0..7 {
+                assert_eq!(Instruction::decode(opcode, operand).unwrap().encode(), [opcode, operand]);
             }
         }
-        Ok(())
+        assert_eq!(Instruction::decode(5, 6).unwrap(), Instruction::Out(ComboOperand::RegisterC));
+        assert_eq!(Instruction::decode(1, 7).unwrap().to_string(), "bxl 7");
+        assert_eq!(Instruction::decode(0, 7).unwrap_err().to_string(), "Encountered illegal operand 7");
+        assert_eq!(Instruction::decode(8, 0).unwrap_err().to_string(), "Encountered illegal opcode 8");
+    }
+
+    #[test]
+    fn disassembled_listing() {
+        let program = parse_program("Program: 0,3,5,4,3,0").unwrap();
+        let expected = "\
+Addr  Bytes  Instruction  Meaning
+   0  0,3    adv 3        A = A >> 3
+   2  5,4    out A        output A % 8
+   4  3,0    jnz 0        if A != 0 goto 0, jumping to `adv 3`
+";
+        assert_eq!(listing(&program).unwrap(), expected);
+        assert_eq!(pseudo_code(&program).unwrap(), "do {\n    A = A >> 3\n    output A % 8\n} while A != 0\n");
+        let error = disassemble(&[0, 1, 5, 7]).unwrap_err();
+        assert_eq!(format!("{:#}", error), "Could not decode the instruction at address 2: Encountered illegal operand 7");
+        assert!(disassemble(&[0, 1, 5]).is_err());
+    }
+
+    #[test]
+    fn jumps_other_than_the_loop_become_gotos() {
+        let program = [2, 1, 3, 6, 5, 1, 3, 2, 3, 20];
+        assert_eq!(pseudo_code(&program).unwrap(), "    B = 1
+2:
+    if A != 0 goto 6
+    output 1
+6:
+    if A != 0 goto 2
+    if A != 0 halt
+");
+        assert!(listing(&program).unwrap().ends_with("3,20   jnz 20       if A != 0 goto 20, jumping past the end, which halts\n"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn assembled_programs() {
+        let source = "\
+; The quine example, with a label
+start:  adv 3   ; shift out an octal digit
+        out A
+        jnz start
+";
+        assert_eq!(assemble(source).unwrap(), parse_program("Program: 0,3,5,4,3,0").unwrap());
+        assert_eq!(assemble("jnz end\nbxc\nend: bxc 4").unwrap(), vec![3, 4, 4, 0, 4, 4]);
+        //Disassembled instructions assemble back into the same program
+        for seed in 0..10 {
+            let (_, program) = parse_input_file(&generate(4, &mut InputRng::seed_from_u64(seed))).unwrap();
+            let source = disassemble(&program).unwrap().iter().map(|instruction| instruction.to_string() + "\n").collect::<String>();
+            assert_eq!(assemble(&source).unwrap(), program);
+        }
+    }
+
+    #[test]
+    fn invalid_assembly_is_pointed_at() {
+        let error = assemble("bst A\nout 7").unwrap_err();
+        assert_eq!((error.line, error.column, error.found.as_str()), (2, 5, "7"));
+        assert_eq!(error.expected, "a combo operand of 0 to 3, A, B or C");
+        assert_eq!(assemble("adv 4").unwrap_err().found, "4");
+        assert_eq!(assemble("bxl 8").unwrap_err().expected, "a literal operand from 0 to 7");
+        assert_eq!(assemble("jnz nowhere").unwrap_err().expected, "a defined label or an address from 0 to 7");
+        assert_eq!(assemble("a: bxc\na: bxc").unwrap_err().expected, "a label that is not already defined");
+        assert_eq!(assemble("mul A").unwrap_err().found, "mul");
+        assert_eq!(assemble("adv").unwrap_err().expected, "a combo operand");
+        assert_eq!(assemble("out A B").unwrap_err().found, "B");
+        let far = "bxc\n".repeat(4) + "far: bxc\njnz far";
+        assert_eq!(assemble(&far).unwrap_err().line, 6);
+    }
+
+    #[test]
+    fn combo_operand_7_is_rejected() {
+        let mut computer = ChronospatialComputer::new(1, 0, 0);
+        assert!(computer.run(&[5, 7]).is_err());
+    }
+}
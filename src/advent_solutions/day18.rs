@@ -1,81 +1,19 @@
-use std::collections::{HashMap, HashSet, VecDeque};
-
 use anyhow::anyhow;
+use rand::seq::SliceRandom;
 
-use super::{read_input_file, SolveAdvent};
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::input_error::{parse_number, InputError};
+use crate::search::{bfs, SearchResult};
+use crate::visualize::{Color, Scene};
 
 pub struct Day18;
 
-type OrderedPair = (i64, i64);
-
-///Represents a single path through the memory
-/// maze
-#[derive(Debug, Clone)]
-struct MemoryTraveler {
-    ///Current row position
-    row: i64,
-    ///Current column position
-    col: i64,
-    ///This travelers history (to prevent cycles)
-    visited: HashSet<OrderedPair>
-}
-
-impl MemoryTraveler {
-    fn visit(&mut self) {
-        //! Add current position to this traveler's
-        //! history of visited positions
-        self.visited.insert((self.row, self.col));
-    }
-
-    fn in_cycle(&self)-> bool {
-        //! Whether the probe is in a cycle
-        self.visited.contains(&(self.row, self.col))
-    }
+const DAY: u8 = 18;
 
-    fn new(row: i64, col: i64) -> Self {
-        MemoryTraveler {
-            row,
-            col, 
-            visited:HashSet::new()
-        }
-    }
-    fn is_inbounds(&self, grid: OrderedPair) -> bool {
-        let (max_row, max_col) = grid;
-        self.row >= 0 && self.col >= 0 && self.col <= max_col && self.row <= max_row
-    }
-    fn exit_reached(&self, grid: OrderedPair) -> bool {
-        //! Has this probe reached the bottom right corner of the memory space?
-        let (max_row, max_col) = grid;
-        self.row ==max_row && self.col ==max_col
-    }
-    fn spawn_next(&self) -> impl IntoIterator<Item = MemoryTraveler> {
-        //! Spawn 4 new probes, 1 up, 1 down, 1 left, 1 right.
-        [
-            MemoryTraveler {
-                row: self.row + 1,
-                col: self.col, 
-                visited: self.visited.clone()
-            },
-            MemoryTraveler {
-                row: self.row - 1,
-                col: self.col,
-                visited: self.visited.clone()
-            },
-            MemoryTraveler {
-                row: self.row, 
-                col: self.col + 1, 
-                visited: self.visited.clone()
-            },
-            MemoryTraveler {
-                row: self.row, 
-                col: self.col - 1, 
-                visited: self.visited.clone()
-            }
-        ]
-    }
-}
-
-fn build_corrupted_bytes(input_file: &str, size_limit: usize) -> anyhow::Result<Vec<OrderedPair>> {
+pub fn build_corrupted_bytes(input_file: &str, size_limit: usize) -> anyhow::Result<Vec<Point>> {
     //! Construct a set of the corruped byte positions.
     let mut corruped_bytes = Vec::new();
     for (line_number, line) in input_file.lines().enumerate() {
@@ -83,102 +21,242 @@ fn build_corrupted_bytes(input_file: &str, size_limit: usize) -> anyhow::Result<
             break;
         }
         //Input file is col, row but we want row, col
-        let [col, row]: [&str; 2] = line.split(',').collect::<Vec<_>>().try_into().map_err(|_| anyhow::anyhow!("Failed to split coordinates"))?;
-        let row = row.parse::<i64>()?;
-        let col = col.parse::<i64>()?;
-        corruped_bytes.push((row, col));
+        let locate = |error: InputError| error.on_line(line_number + 1);
+        let (col, row) = line.split_once(',').ok_or_else(|| locate(InputError::at_end(DAY, line, "a comma separated `X,Y` coordinate")))?;
+        let row = parse_number::<i64>(DAY, line, row).map_err(locate)?;
+        let col = parse_number::<i64>(DAY, line, col).map_err(locate)?;
+        corruped_bytes.push(Point::new(row, col));
     }
     Ok(corruped_bytes)
 }
 
 
-///Optimizer is a blackbox that helps efficiently
-/// kill probes that cannot be on an optimal path.
-struct Optimizer {
-    best_records: HashMap<OrderedPair, usize>,
+fn search_memory_space(memory_space: &Grid<bool>) -> Option<SearchResult<Point>> {
+    //! Breadth first search of the memory space from the top left corner until the bottom right corner is reached.
+    //! `None` if the top left corner is itself corrupted.
+    let exit = Point::new(memory_space.rows() as i64 - 1, memory_space.cols() as i64 - 1);
+    let is_open = |position: &Point| memory_space.get(*position) == Some(&false);
+    if !is_open(&Point::ORIGIN) {
+        return None;
+    }
+    let open_neighbors = |position: &Point| memory_space.neighbors4(*position).filter(is_open).collect::<Vec<_>>();
+    Some(bfs(Point::ORIGIN, open_neighbors, |position| *position == exit))
 }
 
-impl Optimizer {
-    fn new() -> Self {
-        Optimizer {
-            best_records: HashMap::new(),
-        }
+pub fn find_shortest_path(memory_space: &Grid<bool>) -> Option<usize> {
+    //! The fewest steps from the top left corner to the bottom right corner.
+    //! If no path exists, then `None` is returned.
+    search_memory_space(memory_space)?.goal_distance()
+}
+
+///The bottom right corner of the memory space in the real puzzle
+pub const MEMORY_SPACE_CORNER: Point = Point::new(70, 70);
+
+fn corrupt(memory_space: &mut Grid<bool>, corrupted_byte: Point) -> anyhow::Result<()> {
+    //! Mark a fallen byte as corrupted in the memory space.
+    let cell = memory_space.get_mut(corrupted_byte)
+        .ok_or_else(|| anyhow!("Byte {},{} falls outside the memory space", corrupted_byte.col, corrupted_byte.row))?;
+    *cell = true;
+    Ok(())
+}
+
+fn empty_memory_space(grid_size: Point) -> Grid<bool> {
+    //! A memory space with no corrupted bytes, whose bottom right corner is `grid_size`.
+    Grid::filled(grid_size.row as usize + 1, grid_size.col as usize + 1, false)
+}
+
+pub fn shortest_path_after_bytes(file_contents: &str, grid_size: Point, fallen_bytes: usize) -> anyhow::Result<usize> {
+    //! The number of steps to the exit once the first `fallen_bytes` bytes have corrupted the memory space.
+    let mut memory_space = empty_memory_space(grid_size);
+    for corrupted_byte in build_corrupted_bytes(file_contents, fallen_bytes)? {
+        corrupt(&mut memory_space, corrupted_byte)?;
     }
-    fn kill_probe(&mut self, current_explorer: &MemoryTraveler) -> bool {
-        //! Whether or not to kill this probe. 
-        let current_explorer_position = (current_explorer.row, current_explorer.col); 
-        match self.best_records.get_mut(&current_explorer_position) {
-            Some(best_record) => {
-                if current_explorer.visited.len() >= *best_record {
-                    //If another probe has done better or as good as this probe,
-                    //then we can kill this probe
-                    return true;
-                }
-                *best_record = current_explorer.visited.len();
-            },
-            None => {
-                self.best_records.insert(current_explorer_position, current_explorer.visited.len());
-            }
-        }
-        false
+    find_shortest_path(&memory_space).ok_or(anyhow!("No path found at all!"))
+}
+
+pub fn visualize_after_bytes(file_contents: &str, grid_size: Point, fallen_bytes: usize) -> anyhow::Result<Scene> {
+    //! The memory space once the first `fallen_bytes` bytes have fallen, with a shortest path to the exit highlighted
+    //! (if there still is one).
+    let mut memory_space = empty_memory_space(grid_size);
+    for corrupted_byte in build_corrupted_bytes(file_contents, fallen_bytes)? {
+        corrupt(&mut memory_space, corrupted_byte)?;
     }
+    let shortest_path = search_memory_space(&memory_space)
+        .and_then(|search| search.path_to(search.goals().first()?))
+        .unwrap_or_default();
+    let drawing = memory_space.map(|corrupted| if *corrupted { '#' } else { '.' });
+    Ok(Scene::new(drawing).overlay("shortest path", Color::GREEN, shortest_path))
 }
 
-fn find_shortest_path(corrupted_bytes: &HashSet<OrderedPair>, grid_size: OrderedPair) -> Option<usize> {
-    //! Depth first traversal of the memory space to find the optimal path (the path with the fewest steps)
-    //! The `Optimizer` keeps the runtime from being exponential
-    //! If no optimal path exists, then `None` is returned.
-    let mut explorer_queue = VecDeque::new();
-    explorer_queue.push_back(MemoryTraveler::new(0, 0));
-    let mut optimizer = Optimizer::new();
-    let mut min_path_taken: Option<usize> = None;
-    while let Some(mut current_explorer) = explorer_queue.pop_front() {
-        if current_explorer.in_cycle() || corrupted_bytes.contains(&(current_explorer.row, current_explorer.col)) || !current_explorer.is_inbounds(grid_size) {
-            continue;
-        }
-        if optimizer.kill_probe(&current_explorer) {
-            continue;
+pub fn visualize(input: &str) -> anyhow::Result<Scene> {
+    //! The shortest path through the memory space of the real puzzle after 1024 bytes have fallen.
+    visualize_after_bytes(input, MEMORY_SPACE_CORNER, 1024)
+}
+
+pub fn first_blocking_byte(file_contents: &str, grid_size: Point) -> anyhow::Result<String> {
+    //! For each byte we corrupt, check if this byte makes traversing the 
+    //! memory map from start to exit impossible.
+    let all_corrupted_bytes = build_corrupted_bytes(file_contents, file_contents.lines().count())?;
+    let mut memory_space = empty_memory_space(grid_size);
+    for corrupted_byte in all_corrupted_bytes  {
+        corrupt(&mut memory_space, corrupted_byte)?;
+        if find_shortest_path(&memory_space).is_none() {
+            //We are done when there is no optimal path at all!
+            //The answer is given as col,row to match the input file convention
+            return Ok(format!("{},{}", corrupted_byte.col, corrupted_byte.row));
         }
-        current_explorer.visit();
-        if current_explorer.exit_reached(grid_size) {
-            match min_path_taken {
-                Some(current_min_path_taken) => min_path_taken = Some(current_min_path_taken.min(current_explorer.visited.len())), 
-                None => min_path_taken = Some(current_explorer.visited.len())
-            }
+    }
+    anyhow::bail!("No corrupted byte prevents reaching the exit")
+}
+
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! `size` bytes falling into the memory space of the real puzzle, the first 1024 still leaving a way to the exit.
+    let falling_bytes = falling_bytes(MEMORY_SPACE_CORNER, 1024, size, rng);
+    falling_bytes.iter().map(|byte| format!("{},{}", byte.col, byte.row)).collect::<Vec<_>>().join("\n")
+}
+
+pub fn generate_for_grid(size: usize, grid_width: usize, rng: &mut InputRng) -> anyhow::Result<String> {
+    //! `size` bytes falling into a `grid_width` by `grid_width` memory space (71 by 71 in the real puzzle). Just as
+    //! the real puzzle's first 1024 bytes do, the first `fallen_bytes_for_grid` of them still leave a way to the exit.
+    anyhow::ensure!((2..=1000).contains(&grid_width), "Day 18 memory spaces are between 2 and 1000 wide, not {}", grid_width);
+    let cells = grid_width * grid_width;
+    //At least half of the memory space, so that the bytes are sure to cut off the exit eventually
+    anyhow::ensure!(
+        (cells / 2..=cells - 2).contains(&size),
+        "A {}x{} memory space fits between {} and {} bytes, not {}", grid_width, grid_width, cells / 2, cells - 2, size
+    );
+    let grid_size = Point::new(grid_width as i64 - 1, grid_width as i64 - 1);
+    let falling_bytes = falling_bytes(grid_size, fallen_bytes_for_grid(grid_width), size, rng);
+    Ok(falling_bytes.iter().map(|byte| format!("{},{}", byte.col, byte.row)).collect::<Vec<_>>().join("\n"))
+}
+
+pub fn fallen_bytes_for_grid(grid_width: usize) -> usize {
+    //! How many bytes fall before part 1 looks for the exit, the same fifth or so of the memory space as the real
+    //! puzzle's 1024 bytes in 71 by 71.
+    grid_width * grid_width * 1024 / (71 * 71)
+}
+
+pub fn falling_bytes(grid_size: Point, fallen_bytes: usize, count: usize, rng: &mut InputRng) -> Vec<Point> {
+    //! `count` bytes falling into the memory space whose bottom right corner is `grid_size`, each in a different
+    //! position other than the start or the exit. The exit can still be reached after `fallen_bytes` of them have
+    //! fallen, but not after all of them. The bytes are reshuffled until that is the case, so `count` should be a good
+    //! deal more than the roughly 40% of the memory space that random bytes take to cut off the exit.
+    let mut byte_positions = empty_memory_space(grid_size).positions()
+        .filter(|position| *position != Point::ORIGIN && *position != grid_size)
+        .collect::<Vec<_>>();
+    assert!(count <= byte_positions.len(), "Only {} bytes fit in the memory space", byte_positions.len());
+    loop {
+        byte_positions.shuffle(rng);
+        let falling_bytes = &byte_positions[..count];
+        if !exit_is_cut_off(grid_size, &falling_bytes[..fallen_bytes.min(count)]) && exit_is_cut_off(grid_size, falling_bytes) {
+            return falling_bytes.to_vec();
         }
-        explorer_queue.extend(current_explorer.spawn_next());
     }
-    min_path_taken
 }
 
+fn exit_is_cut_off(grid_size: Point, corrupted_bytes: &[Point]) -> bool {
+    let mut memory_space = empty_memory_space(grid_size);
+    for corrupted_byte in corrupted_bytes {
+        memory_space[*corrupted_byte] = true;
+    }
+    find_shortest_path(&memory_space).is_none()
+}
 
 impl SolveAdvent for Day18 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
-        //! Traverse all possible paths to find the shortest path
-        let file_contents = read_input_file(path_to_file)?;
-        let corrupted_bytes = build_corrupted_bytes(&file_contents, 1024)?.into_iter().collect::<HashSet<_>>();
-        let grid_size = (70, 70);
-        let shortest_path = find_shortest_path(&corrupted_bytes, grid_size).ok_or(anyhow!("No path found at all!"))?;
-        println!("Minimum path to exit is {} steps", shortest_path - 1);
+    fn parse(input: &str) -> anyhow::Result<()> {
+        build_corrupted_bytes(input, usize::MAX)?;
         Ok(())
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
-        //! For each byte we corrupt, check if this byte makes traversing the 
-        //! memory map from start to exit impossible.
-        let file_contents = read_input_file(path_to_file)?;
-        let grid_size = (70, 70);
-        let all_corrupted_bytes = build_corrupted_bytes(&file_contents, file_contents.lines().count())?;
-        let mut corrupted_bytes = HashSet::new();
-        for corrupted_byte in all_corrupted_bytes  {
-            corrupted_bytes.insert(corrupted_byte);
-            if find_shortest_path(&corrupted_bytes, grid_size).is_none() {
-                //We are done when there is no optimal path at all!
-                let (break_row, break_col) = corrupted_byte;
-                println!("Coordinates (as col,row) of first byte that prevents exit is: {break_col},{break_row}");
-                break;
-            }
-        }
-        Ok(())
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
+        //! Breadth first search for the shortest path
+        Ok(shortest_path_after_bytes(input, MEMORY_SPACE_CORNER, 1024)?.into())
+    }
+
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
+        Ok(first_blocking_byte(input, MEMORY_SPACE_CORNER)?.into())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::SeedableRng;
+
+    use super::*;
+
+    const EXAMPLE: &str = "\
+5,4
+4,2
+4,5
+3,0
+2,1
+6,3
+2,4
+1,5
+0,6
+3,3
+2,6
+5,1
+1,2
+5,5
+2,5
+6,5
+1,4
+0,4
+6,4
+1,1
+6,1
+1,0
+0,5
+1,6
+2,0
+";
+
+    ///The example memory space is 7x7 rather than 71x71
+    const EXAMPLE_CORNER: Point = Point::new(6, 6);
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(shortest_path_after_bytes(EXAMPLE, EXAMPLE_CORNER, 12).unwrap(), 22);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(first_blocking_byte(EXAMPLE, EXAMPLE_CORNER).unwrap(), "6,1");
+    }
+
+    #[test]
+    fn visualization_highlights_the_shortest_path() {
+        let scene = visualize_after_bytes(EXAMPLE, EXAMPLE_CORNER, 12).unwrap();
+        //22 steps visit 23 cells
+        assert_eq!(scene.legend(), vec![("shortest path", Color::GREEN, 23)]);
+        assert_eq!(scene.base()[Point::new(4, 5)], '#');
+        let blocked = visualize_after_bytes(EXAMPLE, EXAMPLE_CORNER, 21).unwrap();
+        assert_eq!(blocked.legend(), vec![("shortest path", Color::GREEN, 0)]);
+    }
+
+    #[test]
+    fn generated_bytes_block_the_exit_eventually() {
+        let mut rng = InputRng::seed_from_u64(18);
+        let bytes = falling_bytes(EXAMPLE_CORNER, 12, 30, &mut rng);
+        assert_eq!(bytes.iter().collect::<HashSet<_>>().len(), 30);
+        assert!(!bytes.contains(&Point::ORIGIN) && !bytes.contains(&EXAMPLE_CORNER));
+        let input = bytes.iter().map(|byte| format!("{},{}", byte.col, byte.row)).collect::<Vec<_>>().join("\n");
+        assert!(shortest_path_after_bytes(&input, EXAMPLE_CORNER, 12).is_ok());
+        assert!(first_blocking_byte(&input, EXAMPLE_CORNER).is_ok());
+    }
+
+    #[test]
+    fn generated_inputs_fit_the_grid() {
+        let mut rng = InputRng::seed_from_u64(18);
+        let input = generate_for_grid(30, 7, &mut rng).unwrap();
+        assert_eq!(input.lines().count(), 30);
+        assert_eq!(fallen_bytes_for_grid(7), 9);
+        assert!(shortest_path_after_bytes(&input, EXAMPLE_CORNER, 9).is_ok());
+        assert!(first_blocking_byte(&input, EXAMPLE_CORNER).is_ok());
+        let error = generate_for_grid(48, 7, &mut rng).unwrap_err();
+        assert_eq!(error.to_string(), "A 7x7 memory space fits between 24 and 47 bytes, not 48");
+    }
+}
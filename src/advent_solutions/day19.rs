@@ -1,119 +1,175 @@
-use std::collections::HashMap;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::RngExt;
 
-use anyhow::anyhow;
-
-use super::{read_input_file, SolveAdvent};
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::input_error::InputError;
+use crate::memo::Memo;
 
 pub struct Day19;
 
+const DAY: u8 = 19;
+
 ///A towel that may or may not be constructed
 /// from the `available_stripes`. 
 #[derive(Debug, Clone)]
-struct Towel<'a> {
+pub struct Towel<'a> {
     ///The pattern to try and make, such as `brwrr`
-    pattern: &'a str,
+    pub pattern: &'a str,
     ///The available towel patterns to use to make the `pattern`
-    available_stripes: &'a Vec<&'a str>,
+    pub available_stripes: &'a Vec<&'a str>,
 }
 
 
 impl <'a> Towel<'a> {
-    fn new(pattern: &'a str, available_stripes: &'a Vec<&str>) -> Towel<'a> {
+    pub fn new(pattern: &'a str, available_stripes: &'a Vec<&str>) -> Towel<'a> {
         Towel {
             pattern,
             available_stripes,
         }
     }
 
-    fn count_possibilities(&self, memo: &mut HashMap<&'a str, usize>) -> usize {
+    pub fn count_possibilities(&self, memo: &mut Memo<&'a str, usize>) -> usize {
         //! Exactly the same algorithm as the `is_possible` method below, except that instead
         //! of returning booleans we return integers.
-        if let Some(outcome) =  memo.get(self.pattern) {
-            return *outcome;
-        }
         if self.pattern.is_empty() {
             //Empty string means we have successfully constructed the pattern
             return 1;
         }
-        //Sum all possible ways to make the pattern
-        let mut total_possiblities = 0;
-        for possible_pattern in self.available_stripes {
-            //For every strip pattern that fits, we recursively call with a shorter pattern
-            if self.pattern.starts_with(possible_pattern) {
-                let new_towel = Towel::new(&self.pattern[possible_pattern.len()..], self.available_stripes);
-                total_possiblities +=  new_towel.count_possibilities( memo);
-            }
-        }
-        memo.insert(self.pattern, total_possiblities);
-        total_possiblities
+        memo.get_or_insert_with(self.pattern, |memo| {
+            //Sum all possible ways to make the pattern. For every strip pattern that fits, we recursively call with a shorter pattern
+            self.remaining_towels().map(|new_towel| new_towel.count_possibilities(memo)).sum()
+        })
     }
 
-    fn is_possible(&self, memo: &mut HashMap<&'a str, bool>) -> bool {
+    pub fn is_possible(&self, memo: &mut Memo<&'a str, bool>) -> bool {
         //! Recursively explore all possible pattern combinations to make the desired `pattern`.
         //! The `memo` is used to memoize results to keep the dynamic programming solution efficient.
-        if let Some(outcome) =  memo.get(self.pattern) {
-            return *outcome;
-        }
         if self.pattern.is_empty() {
             //Empty string means we have successfully constructed the pattern
             return true;
         }
-        for possible_pattern in self.available_stripes {
+        memo.get_or_insert_with(self.pattern, |memo| {
             //For every strip pattern that fits, we recursively call with a shorter pattern
-            if self.pattern.starts_with(possible_pattern) {
-                let new_towel = Towel::new(&self.pattern[possible_pattern.len()..], self.available_stripes);
-                if new_towel.is_possible( memo) {
-                    memo.insert(self.pattern, true);
-                    return true
-                }
-            }
-        }
-        memo.insert(self.pattern, false);
-        false
+            self.remaining_towels().any(|new_towel| new_towel.is_possible(memo))
+        })
+    }
+
+    fn remaining_towels(&self) -> impl Iterator<Item = Towel<'a>> + '_ {
+        //! The towels left to make once each stripe that fits the start of the `pattern` is used.
+        self.available_stripes.iter()
+            .filter(|possible_pattern| self.pattern.starts_with(*possible_pattern))
+            .map(|possible_pattern| Towel::new(&self.pattern[possible_pattern.len()..], self.available_stripes))
     }
   
 }
 
 
 
-fn parse_input(file_contents: &str) -> anyhow::Result<(Vec<&str>, Vec<&str>)> {
+pub fn parse_input(file_contents: &str) -> anyhow::Result<(Vec<&str>, Vec<&str>)> {
     //! Process the input file correctly into two vecs, one of the `available_stripes` and one of the `patterns`
-    let (available_stripes, patterns) = file_contents.split_once("\r\n").ok_or(anyhow!("Input file is malformed"))?;
+    let (available_stripes, patterns) = file_contents.split_once('\n').ok_or_else(|| InputError::at_end(DAY, file_contents, "the designs listed below the towel patterns"))?;
     let available_stripes = available_stripes.split(',').map(|pattern| pattern.trim()).collect::<Vec<_>>();
     let patterns = patterns.split_ascii_whitespace().collect::<Vec<_>>();
     Ok((available_stripes, patterns))
 }
 
+///The colors of the stripes on the towels.
+const STRIPE_COLORS: [char; 5] = ['w', 'u', 'b', 'r', 'g'];
+
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! 150 towel patterns of 1 to 8 stripes and `size` designs of 40 to 60 stripes. As in the real puzzle, there
+    //! is no single stripe towel of one of the colors. Most designs are made of towels, the rest are random
+    //! stripes that may well be impossible.
+    let missing_color = *STRIPE_COLORS.choose(rng).unwrap();
+    let mut towels = STRIPE_COLORS.iter().filter(|color| **color != missing_color).map(|color| color.to_string()).collect::<Vec<_>>();
+    while towels.len() < 150 {
+        let towel = random_stripes(rng.random_range(2..=8), rng);
+        if !towels.contains(&towel) {
+            towels.push(towel);
+        }
+    }
+    towels.shuffle(rng);
+    let mut designs = Vec::with_capacity(size);
+    for _ in 0..size {
+        let length = rng.random_range(40..=60);
+        if rng.random_bool(0.7) {
+            let mut design = String::new();
+            while design.len() < length {
+                design.push_str(towels.choose(rng).unwrap());
+            }
+            designs.push(design);
+        } else {
+            designs.push(random_stripes(length, rng));
+        }
+    }
+    format!("{}\n\n{}", towels.join(", "), designs.join("\n"))
+}
+
+fn random_stripes(length: usize, rng: &mut InputRng) -> String {
+    (0..length).map(|_| *STRIPE_COLORS.choose(rng).unwrap()).collect()
+}
+
 impl SolveAdvent for Day19 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
+    fn parse(input: &str) -> anyhow::Result<()> {
+        parse_input(input)?;
+        Ok(())
+    }
+
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
         //! Solution uses dynamic programming with memoization
-        let file_contents = read_input_file(path_to_file)?;
-        let (available_stripes, patterns) = parse_input(&file_contents)?;
+        let (available_stripes, patterns) = parse_input(input)?;
         let test_patterns = patterns.into_iter().map(|pattern| Towel::new(pattern, &available_stripes)).collect::<Vec<_>>();
+        //The memo is keyed by the part of a pattern left to make, so it can be shared by every pattern
+        let mut memo = Memo::new();
         let mut possible_patterns = 0;
         for test_pattern in test_patterns.iter() {
-            let mut memo = HashMap::new();
             if test_pattern.is_possible(&mut memo) {
                 possible_patterns += 1;
             }
         }
-        println!("There are {} possible designs in total", possible_patterns);
-        Ok(())
+        Ok(possible_patterns.into())
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
         //! Solution uses dynamic programming with memoization
-        let file_contents = read_input_file(path_to_file)?;
-        let (available_stripes, patterns) = parse_input(&file_contents)?;
+        let (available_stripes, patterns) = parse_input(input)?;
         let test_patterns = patterns.into_iter().map(|pattern| Towel::new(pattern, &available_stripes)).collect::<Vec<_>>();
+        let mut memo = Memo::new();
         let mut total_possible_patterns = 0;
         for test_pattern in test_patterns {
-            let mut memo = HashMap::new();
             let possibilities = test_pattern.count_possibilities(&mut memo);
             total_possible_patterns += possibilities;
         }
-        println!("There are {} different stripe combinations that make the desired pattern", total_possible_patterns);
+        Ok(total_possible_patterns.into())
+    }
+}
 
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const EXAMPLE: &str = "\
+r, wr, b, g, bwu, rb, gb, br
+
+brwrr
+bggr
+gbbr
+rrbgbr
+ubwu
+bwurrg
+brgr
+bbrgwb
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day19::solve_part1, EXAMPLE), Answer::Integer(6));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_example(Day19::solve_part2, EXAMPLE), Answer::Integer(16));
     }
-}
\ No newline at end of file
+}
@@ -1,21 +1,30 @@
 
 use std::cmp::Ordering;
 
-use anyhow::Context;
+use rand::RngExt;
 
-use super::{read_input_file, SolveAdvent};
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::input_error::{parse_lines, parse_number, InputError};
 pub struct Day2;
 
+const DAY: u8 = 2;
+
 #[derive(Debug, Clone)]
 pub struct ReactorReport {
-    report: Vec<i64>,
+    pub report: Vec<i64>,
+}
+
+pub fn parse_reports(file_contents: &str) -> anyhow::Result<Vec<ReactorReport>> {
+    //! Parse every line of the input file into a `ReactorReport`
+    Ok(parse_lines(file_contents, ReactorReport::try_new)?)
 }
 
 
 impl ReactorReport {
-    fn try_new(row: &str) -> anyhow::Result<Self> {
-        let report: Result<Vec<i64>, _> = row.trim().split(' ').filter(|substr| !substr.is_empty()).map(|substr| substr.parse::<i64>()).collect();
-        let report = report.context("Failed to parse row into report")?;
+    pub fn try_new(row: &str) -> Result<Self, InputError> {
+        let report: Result<Vec<i64>, _> = row.split(' ').filter(|substr| !substr.is_empty()).map(|substr| parse_number(DAY, row, substr)).collect();
+        let report = report?;
         Ok(Self {
             report
         })
@@ -43,7 +52,7 @@ impl ReactorReport {
         true
     }
 
-    fn is_safe_with_replacement(&self) -> bool {
+    pub fn is_safe_with_replacement(&self) -> bool {
         //! Brute-force solution to Part2. Simply try all possible combinations,
         //! removing one index at a time.
         if self.is_safe() {
@@ -59,7 +68,7 @@ impl ReactorReport {
         false
     }
 
-    fn is_safe(&self) -> bool {
+    pub fn is_safe(&self) -> bool {
         //! Determine if a report is safe.
         //! The two rules are that:
         //! 1. The levels are either all increasing or all decreasing
@@ -74,24 +83,45 @@ impl ReactorReport {
 }
 }
 
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! `size` reports of 5 to 8 levels. Every report starts out safe, but about half then have a level
+    //! changed at random, which the Problem Dampener may or may not be able to tolerate.
+    let mut reports = Vec::with_capacity(size);
+    for _ in 0..size {
+        let direction = if rng.random_bool(0.5) { 1 } else { -1 };
+        let mut level: i64 = rng.random_range(25..75);
+        let mut report = Vec::new();
+        for _ in 0..rng.random_range(5..=8) {
+            report.push(level);
+            level += direction * rng.random_range(1..=3);
+        }
+        if rng.random_bool(0.5) {
+            let changed_level = rng.random_range(0..report.len());
+            report[changed_level] = rng.random_range(1..100);
+        }
+        reports.push(report.iter().map(|level| level.to_string()).collect::<Vec<_>>().join(" "));
+    }
+    reports.join("\n")
+}
+
 impl  SolveAdvent for Day2 {
-        fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
-            let file_contents = read_input_file(path_to_file)?;
-            let reports: Result<Vec<ReactorReport>, anyhow::Error> = file_contents.lines().map(ReactorReport::try_new).collect();
-            let reports = reports?;
+        fn parse(input: &str) -> anyhow::Result<()> {
+            parse_reports(input)?;
+            Ok(())
+        }
+
+        fn solve_part1(input: &str) -> anyhow::Result<Answer> {
+            let reports = parse_reports(input)?;
             let mut safe_reports = 0;
             for report in reports {
                 if report.is_safe() {
                     safe_reports += 1;
                 }
             }
-            println!("There are a total of {safe_reports} safe reports.");
-            Ok(())
+            Ok(safe_reports.into())
         }
-        fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
-            let file_contents = read_input_file(path_to_file)?;
-            let reports: Result<Vec<ReactorReport>, anyhow::Error> = file_contents.lines().map(ReactorReport::try_new).collect();
-            let mut reports = reports?;
+        fn solve_part2(input: &str) -> anyhow::Result<Answer> {
+            let mut reports = parse_reports(input)?;
             let mut safe_reports = 0;
             for report in reports.iter_mut() {
                 if report.is_safe_with_replacement() {
@@ -99,7 +129,31 @@ impl  SolveAdvent for Day2 {
                     continue;
                 }
             }
-            println!("There are a total of {safe_reports} safe reports using problem damper.");
-            Ok(())
+            Ok(safe_reports.into())
         }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const EXAMPLE: &str = "\
+7 6 4 2 1
+1 2 7 8 9
+9 7 6 2 1
+1 3 2 4 5
+8 6 4 4 1
+1 3 6 7 9
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day2::solve_part1, EXAMPLE), Answer::Integer(2));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_example(Day2::solve_part2, EXAMPLE), Answer::Integer(4));
+    }
+}
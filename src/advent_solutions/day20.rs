@@ -1,171 +1,230 @@
 use std::collections::{HashMap, HashSet};
 
-use super::{read_input_file, SolveAdvent};
+use rand::seq::IndexedRandom;
+
+use super::{Answer, SolveAdvent};
+use crate::generate::{maze, InputRng};
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::input_error::InputError;
+use crate::memo::Memo;
+use crate::search::bfs;
+use crate::visualize::{Color, Scene};
 
 pub struct Day20;
 
-type OrderedPair = (i64, i64);
+const DAY: u8 = 20;
 
-struct RaceTrack {
-    track: Vec<Vec<char>>
+pub struct RaceTrack {
+    pub track: Grid<char>
 }
 
-fn generate_all_possible_step_combinations(min_steps: i64, max_steps: i64) -> HashSet<OrderedPair> {
+pub fn generate_all_possible_step_combinations(min_steps: i64, max_steps: i64) -> HashSet<Point> {
     //!Generate all possible unique combinations of steps starting at position `0,0` that is between
     //! `min_steps` and `max_steps` in length, inclusive on both ends.
     let mut step_combinations = HashSet::new();
-    let mut memo = HashSet::new();
-    collect_all_step_combinations((0, 0), max_steps, &mut step_combinations, &mut memo);
-    step_combinations.retain(|(row_step, col_step)| row_step.abs() + col_step.abs() >= min_steps);
+    let mut memo = Memo::new();
+    collect_all_step_combinations(Point::ORIGIN, max_steps, &mut step_combinations, &mut memo);
+    step_combinations.retain(|step| step.manhattan_distance(Point::ORIGIN) >= min_steps);
     step_combinations
 }
 
-fn collect_all_step_combinations(current_position: OrderedPair, remaining_steps: i64, unique_step_combos: &mut HashSet<OrderedPair>, memo : &mut HashSet<(OrderedPair, i64)>) {
+fn collect_all_step_combinations(current_position: Point, remaining_steps: i64, unique_step_combos: &mut HashSet<Point>, memo : &mut Memo<(Point, i64), ()>) {
     //! Recursively walk all possible step combinations
     //! The `memo` prevents the runtime from being exponential
-    if remaining_steps < 0 || memo.contains(&(current_position, remaining_steps)) {
+    if remaining_steps < 0 {
         return;
     }
-    unique_step_combos.insert(current_position);
-    memo.insert((current_position, remaining_steps));
-    let (current_row, current_col) = current_position;
-    collect_all_step_combinations((current_row + 1, current_col), remaining_steps - 1, unique_step_combos, memo);
-    collect_all_step_combinations((current_row - 1, current_col), remaining_steps - 1, unique_step_combos, memo);
-    collect_all_step_combinations((current_row, current_col + 1), remaining_steps - 1, unique_step_combos, memo);
-    collect_all_step_combinations((current_row, current_col - 1), remaining_steps - 1, unique_step_combos, memo);
+    memo.get_or_insert_with((current_position, remaining_steps), |memo| {
+        unique_step_combos.insert(current_position);
+        for next_position in current_position.neighbors4() {
+            collect_all_step_combinations(next_position, remaining_steps - 1, unique_step_combos, memo);
+        }
+    });
 }
 
 impl RaceTrack {
-    fn new(track: Vec<Vec<char>>) -> Self {
+    pub fn new(track: Grid<char>) -> Self {
         RaceTrack {
             track
         }
     }
-    fn reached_end(&self, position: OrderedPair) -> bool {
+
+    pub fn parse(file_contents: &str) -> Result<Self, InputError> {
+        //! Parse the input file into the racetrack of symbols.
+        let track = Grid::parse(DAY, file_contents, |symbol| "#.SE".contains(symbol).then_some(symbol), "one of `#`, `.`, `S` or `E`")?;
+        Ok(RaceTrack::new(track))
+    }
+    fn reached_end(&self, position: Point) -> bool {
         //! Whether or not the current `position` is at the End of the racetrack (position `E`)
-        if !self.is_valid_position(position) {
-            return false;
-        }
-        let (row, col) = position;
-        let current_symbol = self.track[row as usize][col as usize];
-        current_symbol == 'E'
+        self.track.get(position) == Some(&'E')
     }
 
-    fn is_valid_position(&self,  position: OrderedPair) -> bool {
+    fn is_valid_position(&self,  position: Point) -> bool {
         //! Whether or not the current `position` is a valid spot on the board.
         //! Walls count as invalid positions.
-        let (row, col) = position;
-        if row < 0 || col < 0 {
-           return false
-        }
-        if let Some(row) = self.track.get(row as usize) {
-            if let Some(symbol) = row.get(col as usize) {
-                return symbol != &'#'
-            }
-        }
-        false
+        matches!(self.track.get(position), Some(symbol) if *symbol != '#')
     }
-    fn find_start_position(&self) -> anyhow::Result<OrderedPair> {
+    pub fn find_start_position(&self) -> anyhow::Result<Point> {
         //! Find the start position for the race.
-        for (row_number, row) in self.track.iter().enumerate() {
-            for (col_number, symbol) in row.iter().enumerate() {
-                if symbol == &'S' {
-                    return Ok((row_number as i64, col_number as i64));
-                }
-            } 
-        }
-        anyhow::bail!("Now Start position (S) found in racetrack!");
+        self.track.find(&'S').ok_or_else(|| anyhow::anyhow!("Now Start position (S) found in racetrack!"))
     }
 
-    fn index_racetrack(&self) -> anyhow::Result<HashMap<OrderedPair, usize>> {
+    pub fn index_racetrack(&self) -> anyhow::Result<HashMap<Point, usize>> {
         //! Records a map of each position on the board to the number of picoseconds
         //! that have occurred. No cheating is allowed, so the path is deterministic
         let racetrack_start = self.find_start_position()?;
-        //Traversal queue stores a tuple of `(track position, picoseconds)`
-        let mut traversal_queue = vec![(racetrack_start, 0)];
-        let mut track_index = HashMap::new();
-        while let Some((current_position, picoseconds)) = traversal_queue.pop() {
-            if !self.is_valid_position(current_position) || track_index.contains_key(&current_position) {
-                continue;
-            }
-            track_index.insert(current_position, picoseconds);
-            if self.reached_end(current_position) {
-                //As soon as we reach the end of the track iteration can end.
-                break;
-            }
-            let (current_row, current_col) = current_position;
-            traversal_queue.extend([
-                ((current_row, current_col + 1), picoseconds + 1),
-                ((current_row, current_col - 1), picoseconds + 1),
-                ((current_row + 1, current_col), picoseconds + 1),
-                ((current_row - 1, current_col), picoseconds + 1)
-            ]);
-        }
-        Ok(track_index)
+        let open_neighbors = |position: &Point| {
+            self.track.neighbors4(*position).filter(|next_position| self.is_valid_position(*next_position)).collect::<Vec<_>>()
+        };
+        //As soon as we reach the end of the track the search can end.
+        let track_search = bfs(racetrack_start, open_neighbors, |position| self.reached_end(*position));
+        Ok(track_search.into_distances())
     }
 }
 
-fn index_cheat_shortcuts(indexed_racetrack: &HashMap<OrderedPair, usize>, possible_cheat_jumps: &HashSet<OrderedPair>) -> HashMap<i64,usize> {
+pub fn cheats<'a>(indexed_racetrack: &'a HashMap<Point, usize>, possible_cheat_jumps: &'a HashSet<Point>) -> impl Iterator<Item = (Point, Point, i64)> + 'a {
+    //! Every unique cheat that saves at least 1 picosecond when compared to the none-cheating variant,
+    //! as the position it starts at, the position it ends at and the picoseconds it saves.
+    indexed_racetrack.iter().flat_map(move |(track_position, picoseconds)| {
+        //For any given cheat start position, the seconds saved are the distance between 
+        //the seconds it took the non-cheater to get from the old position (before cheating)
+        // to the new position (after cheating) minus the seconds the cheater moved during the cheat.
+        possible_cheat_jumps.iter().filter_map(move |cheat_jump| {
+            let cheat_end_position = *track_position + *cheat_jump;
+            let cheat_jump_distance = cheat_jump.manhattan_distance(Point::ORIGIN);
+            let end_picoseconds = indexed_racetrack.get(&cheat_end_position)?;
+            let cheat_picoseconds_saved = *end_picoseconds  as i64  - *picoseconds as i64 - cheat_jump_distance;
+            (cheat_picoseconds_saved > 0).then_some((*track_position, cheat_end_position, cheat_picoseconds_saved))
+        })
+    })
+}
+
+pub fn index_cheat_shortcuts(indexed_racetrack: &HashMap<Point, usize>, possible_cheat_jumps: &HashSet<Point>) -> HashMap<i64,usize> {
     //! Generate a count of all possible unique cheats that save at least 1 picosecond, when compared to the 
     //! none-cheating variant. 
     let mut cheat_shortcuts: HashMap<i64, usize> = HashMap::new();
     //Explore all possible cheats
-    for (track_position, picoseconds) in indexed_racetrack.iter() {
-        //For any given cheat start position, the seconds saved are the distance between 
-        //the seconds it took the non-cheater to get from the old position (before cheating)
-        // to the new position (after cheating) minus the seconds the cheater moved during the cheat.
-        let (row, col) = *track_position;
-        for (row_step, col_step) in possible_cheat_jumps.iter() {
-            let cheat_end_position = (row + row_step, col + col_step);
-            let cheat_jump_distance = row_step.abs() + col_step.abs();
-            if let Some(end_picoseconds) = indexed_racetrack.get(&cheat_end_position) {
-                let cheat_picoseconds_saved = *end_picoseconds  as i64  - *picoseconds as i64 - cheat_jump_distance;
-                if cheat_picoseconds_saved > 0 {
-                    *cheat_shortcuts.entry(cheat_picoseconds_saved).or_default() += 1;
-                }
-        }
-    }
+    for (_, _, cheat_picoseconds_saved) in cheats(indexed_racetrack, possible_cheat_jumps) {
+        *cheat_shortcuts.entry(cheat_picoseconds_saved).or_default() += 1;
     }
     cheat_shortcuts
 }
 
 
+pub fn count_cheats_above_threshold(input_file: &str, max_cheat_steps: i64, picoseconds_threshold: i64) -> anyhow::Result<usize> {
+    //! Count the cheats of between 2 and `max_cheat_steps` steps that save at least `picoseconds_threshold` picoseconds.
+    //First, gather statistics on the racetrack without cheating
+    let indexed_racetrack = RaceTrack::parse(input_file)?.index_racetrack()?;
+    let possible_cheat_jumps=  generate_all_possible_step_combinations(2, max_cheat_steps);
+    //Find all possible cheats that save at least 1 picosecond
+    let cheat_shortcuts = index_cheat_shortcuts(&indexed_racetrack, &possible_cheat_jumps);
+    let total_cheats_above_threshold = cheat_shortcuts.into_iter().filter_map(|(picoseconds_saved, count)| {
+        if picoseconds_saved >= picoseconds_threshold {
+            return Some(count)
+        }
+        None
+    }).sum::<usize>();
+    Ok(total_cheats_above_threshold)
+}
+
+pub fn visualize_cheats(input_file: &str, max_cheat_steps: i64, picoseconds_threshold: i64) -> anyhow::Result<Scene> {
+    //! The racetrack with the fair route highlighted, along with where every cheat of between 2 and `max_cheat_steps` steps
+    //! that saves at least `picoseconds_threshold` picoseconds starts and ends.
+    let racetrack = RaceTrack::parse(input_file)?;
+    let indexed_racetrack = racetrack.index_racetrack()?;
+    let possible_cheat_jumps = generate_all_possible_step_combinations(2, max_cheat_steps);
+    let (cheat_starts, cheat_ends): (HashSet<_>, HashSet<_>) = cheats(&indexed_racetrack, &possible_cheat_jumps)
+        .filter(|(_, _, cheat_picoseconds_saved)| *cheat_picoseconds_saved >= picoseconds_threshold)
+        .map(|(cheat_start, cheat_end, _)| (cheat_start, cheat_end))
+        .unzip();
+    Ok(Scene::new(racetrack.track)
+        .overlay("race track", Color::BLUE, indexed_racetrack.into_keys())
+        .overlay("cheat starts", Color::GREEN, cheat_starts)
+        .overlay("cheat ends", Color::RED, cheat_ends))
+}
+
+pub fn visualize(input: &str) -> anyhow::Result<Scene> {
+    //! The part2 cheats (up to 20 steps) that save at least 100 picoseconds.
+    visualize_cheats(input, 20, 100)
+}
+
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! A `size` by `size` racetrack winding through a maze, from a random start to the furthest point from it.
+    //! Like the real puzzle there is a single track, every other position is a wall.
+    let maze = maze(size, size, 0.0, rng);
+    let corridors = maze.find_all(&'.').collect::<Vec<_>>();
+    let start = *corridors.choose(rng).unwrap();
+    let open_neighbors = |position: &Point| maze.neighbors4(*position).filter(|next| maze[*next] == '.').collect::<Vec<_>>();
+    let maze_search = bfs(start, open_neighbors, |_| false);
+    let (end, _) = maze_search.distances().iter().max_by_key(|(position, distance)| (**distance, **position)).unwrap();
+    let mut track = Grid::filled(size, size, '#');
+    for position in maze_search.path_to(end).unwrap() {
+        track[position] = '.';
+    }
+    track[start] = 'S';
+    track[*end] = 'E';
+    track.to_string()
+}
+
 impl SolveAdvent for Day20 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
-        let input_file = read_input_file(path_to_file)?;
-        let racetrack = input_file.lines().map(|line| line.chars().collect::<Vec<_>>()).collect::<Vec<_>>();
-        //First, gather statistics on the racetrack without cheating
-        let indexed_racetrack = RaceTrack::new(racetrack).index_racetrack()?;
-        //Allowed cheats must take exactly 2 steps
-        let possible_cheat_jumps=  generate_all_possible_step_combinations(2, 2);
-        //Find all possible cheats that save at least 1 picosecond
-        let cheat_shortcuts = index_cheat_shortcuts(&indexed_racetrack, &possible_cheat_jumps);
-        let total_cheats_above_threshold = cheat_shortcuts.into_iter().filter_map(|(picoseconds_saved, count)| {
-            if picoseconds_saved >= 100 {
-                return Some(count)
-            }
-            None
-        }).sum::<usize>();
-        println!("There are {} cheats that save at least 100 picoseconds", total_cheats_above_threshold);
+    fn parse(input: &str) -> anyhow::Result<()> {
+        RaceTrack::parse(input)?;
         Ok(())
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
+        //! Allowed cheats must take exactly 2 steps
+        Ok(count_cheats_above_threshold(input, 2, 100)?.into())
+    }
+
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
         //! Exact same solution as `part1`, but allowing cheats of between
         //! 2 and 20 picoseconds
-        let input_file = read_input_file(path_to_file)?;
-        let racetrack = input_file.lines().map(|line| line.chars().collect::<Vec<_>>()).collect::<Vec<_>>();
-        let indexed_racetrack = RaceTrack::new(racetrack).index_racetrack()?;
-        //Allowed cheats must take between 2 and 20 steps (inclusive on both ends)
-        let possible_cheat_jumps=  generate_all_possible_step_combinations(2, 20);
-        let cheat_shortcuts = index_cheat_shortcuts(&indexed_racetrack, &possible_cheat_jumps);
-        let total_cheats_above_threshold = cheat_shortcuts.into_iter().filter_map(|(picoseconds_saved, count)| {
-            if picoseconds_saved >= 100 {
-                return Some(count)
-            }
-            None
-        }).sum::<usize>();
-        println!("There are {} cheats that save at least 100 picoseconds", total_cheats_above_threshold);
-        Ok(())
+        Ok(count_cheats_above_threshold(input, 20, 100)?.into())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############
+";
+
+    #[test]
+    fn part1_example() {
+        //There is one cheat that saves 64 picoseconds, and 5 that save at least 20
+        assert_eq!(count_cheats_above_threshold(EXAMPLE, 2, 64).unwrap(), 1);
+        assert_eq!(count_cheats_above_threshold(EXAMPLE, 2, 20).unwrap(), 5);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(count_cheats_above_threshold(EXAMPLE, 20, 50).unwrap(), 285);
+    }
+
+    #[test]
+    fn visualization_marks_both_ends_of_the_cheats() {
+        //The one cheat saving 64 picoseconds cuts through the wall right of the end, ending on E itself
+        let scene = visualize_cheats(EXAMPLE, 2, 64).unwrap();
+        assert_eq!(scene.legend(), vec![("race track", Color::BLUE, 85), ("cheat starts", Color::GREEN, 1), ("cheat ends", Color::RED, 1)]);
+        assert_eq!(scene.highlight(Point::new(7, 7)), Some(Color::GREEN));
+        assert_eq!(scene.highlight(Point::new(7, 5)), Some(Color::RED));
+    }
+}
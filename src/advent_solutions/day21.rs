@@ -1,101 +1,53 @@
-use std::{collections::{HashMap, HashSet, VecDeque}, fmt::Display};
+use std::{collections::HashMap, fmt::Display};
 
-use super::{read_input_file, SolveAdvent};
+use rand::RngExt;
+
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::geometry::{Direction, Point};
+use crate::input_error::{parse_lines, InputError};
+use crate::memo::Memo;
+use crate::search::bfs;
 
 pub struct Day21;
 
-const NUMERIC_KEYPAD: [[char; 3]; 4] = [
+const DAY: u8 = 21;
+
+pub const NUMERIC_KEYPAD: [[char; 3]; 4] = [
     ['7', '8', '9'], 
     ['4', '5', '6'], 
     ['1', '2', '3'], 
     [' ', '0', 'A']
     ];
-const DIRECTIONAL_KEYPAD: [[char; 3]; 2] = [
+pub const DIRECTIONAL_KEYPAD: [[char; 3]; 2] = [
     [' ', '^', 'A'], 
-    ['<', 'V', '>']
+    ['<', 'v', '>']
     ];
 
-///An Explorer collects possible paths to traverse
-/// between two points in either the `DIRECTIONAL_KEYPAD`
-/// or the `NUMERIC_KEYPAD`, ALWAYS IN TERMS of the DIRECTIONAL KEYPAD
-#[derive(Debug, Clone)]
-struct KeyPadExplorer {
-    row: i64,
-    col: i64,
-    ///The move_history in terms of directional key inputs
-    move_history: Vec<char>,
-    visited: HashSet<(i64, i64)>
-}
-
-impl KeyPadExplorer {
-    fn new_empty(row: i64, col: i64) -> Self {
-        KeyPadExplorer {
-            row,
-            col,
-            move_history: Vec::new(), 
-            visited: HashSet::new()
-        }
-    }
-    fn visit(&mut self) {
-        self.visited.insert((self.row, self.col));
-    }
-    fn in_cycle(&self) -> bool {
-        self.visited.contains(&(self.row, self.col))
-    }
-    fn new(row: i64, col: i64,  move_history: Vec<char>, visited: HashSet<(i64, i64)>) -> Self {
-        KeyPadExplorer {
-            row,
-            col,
-            move_history, 
-            visited
-        }
-    }
-
-    ///To enforce the rule that empty spaces can never be traversed, 
-    /// returns `None` if either the current position if off the keypad
-    /// or in the forbidden empty space position.
-    fn safe_read(&self, keypad: &[[char; 3]]) -> Option<char> {
-        if let Some(row) = keypad.get(self.row as usize) {
-            if let Some(symbol) = row.get(self.col as usize) {
-                if *symbol != ' ' {
-                    return Some(*symbol);
-                }
-            }
-        }
-        None
-    }
-
-    fn spawn_next(&self) -> impl IntoIterator<Item=Self> {
-        let mut next_items = [
-            Self::new(self.row + 1, self.col,  self.move_history.clone(), self.visited.clone()),
-            Self::new(self.row - 1, self.col,  self.move_history.clone(), self.visited.clone()),
-            Self::new(self.row, self.col + 1,  self.move_history.clone(), self.visited.clone()),
-            Self::new(self.row, self.col - 1, self.move_history.clone(), self.visited.clone())
-        ];
-        for (next_item, new_move) in  next_items.iter_mut().zip(['V', '^', '>', '<']) {
-            next_item.move_history.push(new_move);
-        }
-        next_items
-    }
+fn keypad_symbol(keypad: &[[char; 3]], position: Point) -> Option<char> {
+    //! To enforce the rule that empty spaces can never be traversed, 
+    //! returns `None` if either the position is off the keypad
+    //! or in the forbidden empty space position.
+    let row = keypad.get(usize::try_from(position.row).ok()?)?;
+    row.get(usize::try_from(position.col).ok()?).copied().filter(|symbol| *symbol != ' ')
 }
 
-///From the keypad position specified by `starting_row`, `starting_col`, collect all possible optimal paths between the start position
-/// and all other positions in the given `keypad_in`.
-fn translate_to_directional_inputs_from_start(starting_row: usize, starting_col: usize, keypad_in: &[[char; 3]]) -> HashMap<(char, char), Vec<String>> { 
+///From the keypad position `start`, collect all possible optimal paths between the start position
+/// and all other positions in the given `keypad_in`, in terms of the directional keypad.
+fn translate_to_directional_inputs_from_start(start: Point, keypad_in: &[[char; 3]]) -> HashMap<(char, char), Vec<String>> { 
     let mut decoded_moves: HashMap<(char, char), Vec<String>> = HashMap::new();
-    let mut traversal_queue = VecDeque::new();
-    let starting_explorer = KeyPadExplorer::new_empty(starting_row as i64, starting_col as i64);
-    let starting_symbol = starting_explorer.safe_read(keypad_in).unwrap();
-    traversal_queue.push_back(starting_explorer);
-    while let Some(mut current_explorer) = traversal_queue.pop_front() {
-        if current_explorer.in_cycle() {
-            continue;
-        }
-        current_explorer.visit();
-        if let Some(current_symbol) = current_explorer.safe_read(keypad_in) {
-            decoded_moves.entry((starting_symbol, current_symbol)).or_default().push(current_explorer.move_history.clone().iter().collect::<String>());
-            traversal_queue.extend(current_explorer.spawn_next());
-
+    let starting_symbol = keypad_symbol(keypad_in, start).unwrap();
+    let keypad_neighbors = |position: &Point| position.neighbors4().filter(|next| keypad_symbol(keypad_in, *next).is_some()).collect::<Vec<_>>();
+    let keypad_search = bfs(start, keypad_neighbors, |_| false);
+    for end in keypad_search.distances().keys() {
+        let end_symbol = keypad_symbol(keypad_in, *end).unwrap();
+        for path in keypad_search.all_paths_to(end) {
+            //Each step of the path is one press of the matching arrow on the directional keypad
+            let move_history = path.windows(2)
+                .filter_map(|step| Direction::from_delta(step[1] - step[0]))
+                .map(|direction| direction.arrow())
+                .collect::<String>();
+            decoded_moves.entry((starting_symbol, end_symbol)).or_default().push(move_history);
         }
     }
     decoded_moves
@@ -103,22 +55,18 @@ fn translate_to_directional_inputs_from_start(starting_row: usize, starting_col:
 
 ///Find all possible optimal paths between any two valid positions
 /// on the passed in `keypad_in`, always in terms of the directional keypad inputs.
-fn translate_to_directional_inputs(keypad_in: &[[char; 3]]) -> HashMap<(char, char), Vec<String>> {
+pub fn translate_to_directional_inputs(keypad_in: &[[char; 3]]) -> HashMap<(char, char), Vec<String>> {
     let mut index = HashMap::new();
     for (row_number, row) in keypad_in.iter().enumerate() {
         for (col_number, symbol) in row.iter().enumerate() {
             if *symbol == ' ' {
                 continue;
             }
-           index.extend(translate_to_directional_inputs_from_start(row_number, col_number, keypad_in));
+           index.extend(translate_to_directional_inputs_from_start(Point::new(row_number as i64, col_number as i64), keypad_in));
         }
     }
 
     for (_, move_history) in index.iter_mut() {
-        //As an optimization, we only consider possible paths that are of the length of the shortest
-        //possible path. 
-        let shortest_path_length = move_history.iter().min_by_key(|path| path.len()).unwrap().len();
-        move_history.retain(|path| path.len() == shortest_path_length);
         for move_option in move_history.iter_mut() {
             //Append the A to each command because we need to press the button once we have reached
             //the desired button
@@ -131,7 +79,7 @@ fn translate_to_directional_inputs(keypad_in: &[[char; 3]]) -> HashMap<(char, ch
 ///Represents a keypad input that must be translated into
 /// a directional keypad command (1 layer deeper in the robot control chain)
 #[derive(Debug)]
-enum KeyPadInput {
+pub enum KeyPadInput {
     ///We are translating from a numerical keypad
     NumericalInput(String), 
     ///We are translating from a directional keypad
@@ -159,7 +107,7 @@ impl KeyPadInput {
     }
 
     ///Expose the list of keypad inputs embedded in the `KeyPadInput`
-    fn unbind(&self) -> &str {
+    pub fn unbind(&self) -> &str {
         match self {
             Self::NumericalInput(to_decode) => to_decode,
             Self::DirectionalInput(to_decode) => to_decode
@@ -167,22 +115,29 @@ impl KeyPadInput {
     }
 }
 
-struct Decoder {
+pub struct Decoder {
     directional_keypad_index: HashMap<(char, char), Vec<String>>,
     numerical_keypad_index: HashMap<(char, char), Vec<String>>
 }
 
 impl Decoder {
-    fn new(directional_keypad_index: HashMap<(char, char), Vec<String>>, numerical_keypad_index: HashMap<(char, char), Vec<String>>) -> Self {
+    pub fn new(directional_keypad_index: HashMap<(char, char), Vec<String>>, numerical_keypad_index: HashMap<(char, char), Vec<String>>) -> Self {
         Self {
             directional_keypad_index, 
             numerical_keypad_index
         }
     }
 
+    ///All of the optimal directional keypad inputs that move a robot from the `from` button to the `to`
+    /// button and press it.
+    fn moves_between(&self, from: char, to: char, numeric_keypad: bool) -> &[String] {
+        let decoder = if numeric_keypad { &self.numerical_keypad_index } else { &self.directional_keypad_index };
+        decoder.get(&(from, to)).unwrap_or_else(|| panic!("Decoder did not contain a mapping from {from} => {to}"))
+    }
+
     ///Decode the passed in input, returning a list of all possible optimal directional
     /// keypad inputs. 
-    fn decode(&self, to_decode: &KeyPadInput) -> Vec<KeyPadInput> {
+    pub fn decode(&self, to_decode: &KeyPadInput) -> Vec<KeyPadInput> {
         let (decoder, to_decode)= match to_decode {
             KeyPadInput::DirectionalInput(to_decode) => (&self.directional_keypad_index, to_decode), 
             KeyPadInput::NumericalInput(to_decode) => (&self.numerical_keypad_index, to_decode)
@@ -208,7 +163,7 @@ impl Decoder {
     }
 }
 
-fn numerical_code_component(code_to_input: &str) -> i64{
+pub fn numerical_code_component(code_to_input: &str) -> i64{
     //! Extract the numerical component of the code to input
     let mut valid_numbers = code_to_input.chars().filter_map(|char| {
         char.to_digit(10)
@@ -226,7 +181,7 @@ fn numerical_code_component(code_to_input: &str) -> i64{
     valid_numbers.into_iter().map(|num| num.to_string()).collect::<String>().parse::<i64>().unwrap_or(0)
 }
 
-fn decode_password(decoder: &Decoder, code_to_input: &str, decode_layers: usize) -> String {
+pub fn decode_password(decoder: &Decoder, code_to_input: &str, decode_layers: usize) -> String {
     //! Decode the passed in `code_to_input` (which is a numerical keypad task) into an optimal
     //! set of directional keypad inputs. The `decode_layers` variable specifies how many layers of directional keypads
     //! are in the way of the numerical keypad
@@ -250,25 +205,115 @@ fn decode_password(decoder: &Decoder, code_to_input: &str, decode_layers: usize)
 
 } 
 
-impl SolveAdvent for Day21 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
-        let file_contents = read_input_file(path_to_file)?;
-        let indexed_direction_keypad = translate_to_directional_inputs(&DIRECTIONAL_KEYPAD);
-        let indexed_numerical_keypad = translate_to_directional_inputs(&NUMERIC_KEYPAD);
-        let decoder = Decoder::new(indexed_direction_keypad, indexed_numerical_keypad);
-        let mut total_complexity = 0;
-        for line in file_contents.lines() {
-            println!("----------------------------------------");
-            let numerical_component = numerical_code_component(line);
-            let decoded_result = decode_password(&decoder, line, 3);
-            println!("Optimal result length {} with numerical component {} for line {}", decoded_result.len(), numerical_component, line);
-            total_complexity += numerical_component as usize * decoded_result.len(); 
+pub fn fewest_presses(decoder: &Decoder, to_press: &str, numeric_keypad: bool, decode_layers: usize, memo: &mut Memo<(char, char, bool, usize), usize>) -> usize {
+    //! The fewest button presses needed on the outermost directional keypad to type `to_press` on a keypad `decode_layers`
+    //! layers away. Unlike `decode_password`, only the length of the inputs is tracked, so each move between two buttons at
+    //! a given layer is solved once and memoized, however many layers deep the chain of robots goes.
+    if decode_layers == 0 {
+        return to_press.len();
+    }
+    //All robots start pointing at the `A` button
+    let mut current_button = 'A';
+    let mut total_presses = 0;
+    for next_button in to_press.chars() {
+        total_presses += memo.get_or_insert_with((current_button, next_button, numeric_keypad, decode_layers), |memo| {
+            decoder.moves_between(current_button, next_button, numeric_keypad).iter()
+                .map(|moves| fewest_presses(decoder, moves, false, decode_layers - 1, memo))
+                .min()
+                .unwrap_or(0)
+        });
+        current_button = next_button;
+    }
+    total_presses
+}
+
+pub fn parse_codes(file_contents: &str) -> Result<Vec<&str>, InputError> {
+    //! Parse the codes to type, one per line, which may only use buttons on the numeric keypad.
+    parse_lines(file_contents, |line| {
+        match line.char_indices().find(|(_, button)| *button == ' ' || !NUMERIC_KEYPAD.iter().flatten().any(|key| key == button)) {
+            Some((offset, button)) => Err(InputError::at(DAY, line, &line[offset..offset + button.len_utf8()], "a numeric keypad button (0-9 or A)")),
+            None => Ok(line)
         }
-        println!("Sum of all complexities: {}", total_complexity);
-        Ok(())
+    })
+}
+
+pub fn total_complexity(file_contents: &str, decode_layers: usize) -> anyhow::Result<usize> {
+    //! Sum the complexities of every code, which are the fewest presses to type the code through `decode_layers`
+    //! layers of keypads times the numeric part of the code.
+    let codes = parse_codes(file_contents)?;
+    let indexed_direction_keypad = translate_to_directional_inputs(&DIRECTIONAL_KEYPAD);
+    let indexed_numerical_keypad = translate_to_directional_inputs(&NUMERIC_KEYPAD);
+    let decoder = Decoder::new(indexed_direction_keypad, indexed_numerical_keypad);
+    let mut memo = Memo::new();
+    let mut total_complexity = 0;
+    for code in codes {
+        let numerical_component = numerical_code_component(code);
+        total_complexity += numerical_component as usize * fewest_presses(&decoder, code, true, decode_layers, &mut memo);
     }
+    Ok(total_complexity)
+}
+
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! `size` codes of three digits followed by `A`.
+    (0..size).map(|_| format!("{:03}A", rng.random_range(0..1000u32))).collect::<Vec<_>>().join("\n")
+}
 
-    fn solve_part2(_path_to_file: &str) -> anyhow::Result<()> {
+impl SolveAdvent for Day21 {
+    fn parse(input: &str) -> anyhow::Result<()> {
+        parse_codes(input)?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
+        //! 2 robots on directional keypads plus the robot at the numeric keypad
+        Ok(total_complexity(input, 3)?.into())
+    }
+
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
+        //! 25 robots on directional keypads plus the robot at the numeric keypad
+        Ok(total_complexity(input, 26)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const EXAMPLE: &str = "\
+029A
+980A
+179A
+456A
+379A
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day21::solve_part1, EXAMPLE), Answer::Integer(126384));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_example(Day21::solve_part2, EXAMPLE), Answer::Integer(154115708116294));
+    }
+
+    #[test]
+    fn fewest_presses_matches_the_decoded_password() {
+        let decoder = Decoder::new(translate_to_directional_inputs(&DIRECTIONAL_KEYPAD), translate_to_directional_inputs(&NUMERIC_KEYPAD));
+        let mut memo = Memo::new();
+        assert_eq!(decode_password(&decoder, "029A", 3).len(), 68);
+        assert_eq!(fewest_presses(&decoder, "029A", true, 3, &mut memo), 68);
+    }
+
+    #[test]
+    fn codes_off_the_keypad_are_located() {
+        let error = parse_codes("029A\n98<A").unwrap_err();
+        assert_eq!((error.line, error.column, error.found.as_str()), (2, 3, "<"));
+    }
+
+    #[test]
+    fn numerical_code_component_skips_leading_zeros() {
+        assert_eq!(numerical_code_component("029A"), 29);
+    }
+}
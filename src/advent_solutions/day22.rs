@@ -1,14 +1,21 @@
 use std::collections::{HashMap, HashSet};
 
-use super::{read_input_file, SolveAdvent};
+use rand::RngExt;
+
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::input_error::{parse_lines, parse_number};
+use crate::parallel;
 
 pub struct Day22;
 
+const DAY: u8 = 22;
+
 fn mix_and_prune(secret_number: i64, mix_value: i64) -> i64 {
     (mix_value ^ secret_number) % 16777216
 }
 
-fn find_nth_secret_number(first_number: i64, n: usize) -> i64 {
+pub fn find_nth_secret_number(first_number: i64, n: usize) -> i64 {
     //! Find the `n`th secret number as directed in the problem.
     let mut secret_number = first_number;
     for _ in 0..n {
@@ -19,9 +26,9 @@ fn find_nth_secret_number(first_number: i64, n: usize) -> i64 {
     secret_number
 }
 
-type PriceChangeMap = HashMap<[i64; 4], i64>;
+pub type PriceChangeMap = HashMap<[i64; 4], i64>;
 
-fn generate_price_changes_map(starting_number: i64, max_price_changes: usize) -> anyhow::Result<PriceChangeMap> {
+pub fn generate_price_changes_map(starting_number: i64, max_price_changes: usize) -> anyhow::Result<PriceChangeMap> {
     //! Generate a map of groupings of 4 price deltas mapped to the sales price (the price the banans would sell for 
     //! if sold after the given 4 price delta sequence is seen by the monkey.)
     let mut secret_numbers = vec![starting_number];
@@ -48,33 +55,70 @@ fn generate_price_changes_map(starting_number: i64, max_price_changes: usize) ->
     Ok(price_change_map)
 }
 
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! The initial secret numbers of `size` buyers, each less than 16777216.
+    (0..size).map(|_| rng.random_range(1..16777216i64).to_string()).collect::<Vec<_>>().join("\n")
+}
+
 impl SolveAdvent for Day22 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
+    fn parse(input: &str) -> anyhow::Result<()> {
+        parse_lines(input, |line| parse_number::<i64>(DAY, line, line))?;
+        Ok(())
+    }
+
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
         //! Simply apply the formula the correct number of times.
         let secret_number_iterations = 2000;
-        let file_contents = read_input_file(path_to_file)?;
-        let starting_numbers = file_contents.trim().lines().map(|num| num.parse::<i64>()).collect::<Result<Vec<_>, _>>()?;
-        let mut total_secret_numbers = 0;
-        for starting_number in starting_numbers {
-            total_secret_numbers += find_nth_secret_number(starting_number, secret_number_iterations);
-        }
-        println!("Total of all secret numbers after {} iterations is: {}", secret_number_iterations, total_secret_numbers);
-        Ok(())
+        let starting_numbers = parse_lines(input, |line| parse_number::<i64>(DAY, line, line))?;
+        //Every buyer is independent of the others, so in parallel mode they are worked out at the same time
+        let secret_numbers = parallel::map(&starting_numbers, |starting_number| find_nth_secret_number(*starting_number, secret_number_iterations));
+        let total_secret_numbers = secret_numbers.into_iter().sum::<i64>();
+        Ok(total_secret_numbers.into())
     }
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
         //! Index each of the 2001 secret numbers price deltas to the corresponding sales price.
         //! Then iterate over the unique 4-delta groupings, and compute the max possible number of bananas sold.
-        let file_contents = read_input_file(path_to_file)?;
         let secret_number_iterations = 2000;
-        let starting_numbers = file_contents.trim().lines().map(|num| num.parse::<i64>()).collect::<Result<Vec<_>, _>>()?;
-        let price_change_maps = starting_numbers.into_iter().map(|starting_number| generate_price_changes_map(starting_number, secret_number_iterations)).collect::<Result<Vec<_>, _>>()?;
+        let starting_numbers = parse_lines(input, |line| parse_number::<i64>(DAY, line, line))?;
+        let price_change_maps = parallel::map(&starting_numbers, |starting_number| generate_price_changes_map(*starting_number, secret_number_iterations))
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
         let all_price_changes = price_change_maps.iter().flat_map(|price_change_map| price_change_map.keys()).collect::<HashSet<_>>();
         let mut max_possible_bananas = 0;
         for price_change_sequence in all_price_changes {
             let total_bananas = price_change_maps.iter().map(|price_change_map| price_change_map.get(price_change_sequence).unwrap_or(&0)).sum::<i64>();
             max_possible_bananas = max_possible_bananas.max(total_bananas);
         }
-        println!("Max possible bananas for a single 4 price change is {}", max_possible_bananas);
-        Ok(())
+        Ok(max_possible_bananas.into())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const PART1_EXAMPLE: &str = "\
+1
+10
+100
+2024
+";
+
+    const PART2_EXAMPLE: &str = "\
+1
+2
+3
+2024
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day22::solve_part1, PART1_EXAMPLE), Answer::Integer(37327623));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_example(Day22::solve_part2, PART2_EXAMPLE), Answer::Integer(23));
+    }
+}
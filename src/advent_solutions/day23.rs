@@ -1,18 +1,26 @@
 use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Mutex;
 
-use anyhow::anyhow;
+use rand::seq::SliceRandom;
+use rand::RngExt;
 
-use super::{read_input_file, SolveAdvent};
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::input_error::InputError;
+use crate::parallel;
 
 pub struct Day23;
 
-type ConnectionTopology = HashMap<String, HashSet<String>>;
+const DAY: u8 = 23;
 
-fn construct_topology(input_file: &str) -> anyhow::Result<ConnectionTopology> {
+pub type ConnectionTopology = HashMap<String, HashSet<String>>;
+
+pub fn construct_topology(input_file: &str) -> anyhow::Result<ConnectionTopology> {
     //! Construct a bi-directional topology map of computer network connections.
     let mut connection_topology: HashMap<String, HashSet<String>> = HashMap::new();
-    for line in input_file.lines() {
-        let [computer1, computer2]: [&str; 2] = line.split('-').collect::<Vec<_>>().try_into().map_err(|_| anyhow!("Cannot coerce into length 2 array"))?;
+    for (line_index, line) in input_file.lines().enumerate() {
+        let (computer1, computer2) = line.split_once('-')
+            .ok_or_else(|| InputError::at_end(DAY, line, "two computer names joined by `-`").on_line(line_index + 1))?;
         connection_topology.entry(computer1.to_string()).or_default().insert(computer2.to_string());
         connection_topology.entry(computer2.to_string()).or_default().insert(computer1.to_string());
     }
@@ -38,10 +46,43 @@ fn traverse_topology_map_recursively(topology_map: &ConnectionTopology, current:
 }
 
 
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! A network of `size` computers with two letter names, each connected to 2 to 4 others at random. One LAN party
+    //! of a computer for every 40 (but 3 to 13 of them) are all connected to each other.
+    let mut computers = ('a'..='z').flat_map(|first| ('a'..='z').map(move |second| format!("{}{}", first, second))).collect::<Vec<_>>();
+    computers.shuffle(rng);
+    computers.truncate(size);
+    let lan_party_size = (size / 40).clamp(3, 13).min(size);
+    let mut connections = BTreeSet::new();
+    for computer1 in 0..lan_party_size {
+        for computer2 in computer1 + 1..lan_party_size {
+            connections.insert((computer1, computer2));
+        }
+    }
+    for computer1 in 0..size {
+        for _ in 0..rng.random_range(2..=4) {
+            let computer2 = rng.random_range(0..size);
+            if computer1 != computer2 {
+                connections.insert((computer1.min(computer2), computer1.max(computer2)));
+            }
+        }
+    }
+    let mut lines = connections.into_iter().map(|(computer1, computer2)| {
+        let (first, second) = if rng.random_bool(0.5) { (computer1, computer2) } else { (computer2, computer1) };
+        format!("{}-{}", computers[first], computers[second])
+    }).collect::<Vec<_>>();
+    lines.shuffle(rng);
+    lines.join("\n")
+}
+
 impl SolveAdvent for Day23 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
-        let file_contents = read_input_file(path_to_file)?;
-        let connection_topology = construct_topology(&file_contents)?;
+    fn parse(input: &str) -> anyhow::Result<()> {
+        construct_topology(input)?;
+        Ok(())
+    }
+
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
+        let connection_topology = construct_topology(input)?;
         //We use a b-tree set because we need to keep track of unique vectors, but vectors are not hash, so they
         //cannot go into a set. We could of course instead convert to a string and then hash
         let mut valid_computer_topologies = BTreeSet::new();
@@ -55,39 +96,40 @@ impl SolveAdvent for Day23 {
             }
             
         }
-        println!("There are {} unique length-3 computer groups that start with t", valid_computer_topologies.len());
-        Ok(())
+        Ok(valid_computer_topologies.len().into())
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
         //! Solution takes ~21 seconds to solve the full part2 problem input
         //! Not fast but it gets the job done
-        let file_contents = read_input_file(path_to_file)?;
-        let mut connection_topology = construct_topology(&file_contents)?;
+        let mut connection_topology = construct_topology(input)?;
         for (computer, connected_computers) in connection_topology.iter_mut() {
             //For part2 solution, we add each computer to its own connected set. 
             //This makes the `perfectly_connected` method on the `NetworkTraveler` work correctly
             connected_computers.insert(computer.to_string());
         }
 
-        let mut optimizer = HashSet::new();
-        let mut largest_connected_network = HashSet::new();
-        for starting_computer in connection_topology.keys() {
-            let largest_network = find_largest_connected_network( &mut optimizer, starting_computer, &connection_topology);
-            if largest_network.len() > largest_connected_network.len() {
-                largest_connected_network = largest_network;
-            }
-        }
-        //The password is the longest set of connected computers, sorted and concenated by a comma
-        let mut lan_network_password = largest_connected_network.into_iter().collect::<Vec<_>>();
-        lan_network_password.sort();
-        println!("LAN Password is {:?}", lan_network_password.join(","));
-        Ok(())
+        let mut starting_computers = connection_topology.keys().collect::<Vec<_>>();
+        starting_computers.sort();
+        //In parallel mode the threads share the optimizer, so that none of them travels what another already has
+        let optimizer = Mutex::new(HashSet::new());
+        let largest_networks = parallel::map(&starting_computers, |starting_computer| {
+            //The password is the longest set of connected computers, sorted and concenated by a comma
+            let mut lan_network_password = find_largest_connected_network(&optimizer, starting_computer, &connection_topology).into_iter().collect::<Vec<_>>();
+            lan_network_password.sort();
+            lan_network_password
+        });
+        //Equally large networks go to the first password alphabetically, so that the answer does not
+        //depend on which thread happened to find which network
+        let lan_network_password = largest_networks.into_iter()
+            .max_by(|password1, password2| password1.len().cmp(&password2.len()).then_with(|| password2.cmp(password1)))
+            .unwrap_or_default();
+        Ok(lan_network_password.join(",").into())
     }
 }
 
 
-fn find_largest_connected_network(optimizer: &mut HashSet<(String, String)>, starting_position: &str, topology: &ConnectionTopology) -> HashSet<String> {
+pub fn find_largest_connected_network(optimizer: &Mutex<HashSet<(String, String)>>, starting_position: &str, topology: &ConnectionTopology) -> HashSet<String> {
     //! Starting from the `starting_position`, depth first traverse the computer network to find the 
     //! largest set of connected devices. The Optimizer is used to prevent other invocations
     //! of this call from wasting work traveling previously explored nodes. 
@@ -97,11 +139,11 @@ fn find_largest_connected_network(optimizer: &mut HashSet<(String, String)>, sta
         let mut visited =  current_traveler.visited.clone().into_iter().collect::<Vec<_>>();
         visited.sort();
         let optimizer_insert = (visited.join(","),current_traveler.current.clone());
-        if current_traveler.in_cycle() || optimizer.contains(&optimizer_insert)  {
+        //Checking and inserting at once, so that two threads never both travel the same node
+        if current_traveler.in_cycle() || !optimizer.lock().unwrap().insert(optimizer_insert)  {
             continue;    
         }
         current_traveler.visit();
-        optimizer.insert(optimizer_insert);
         if !current_traveler.perfectly_connected() {
             continue;
         } else if current_traveler.visited.len() > largest_connected_network.len() {
@@ -161,4 +203,55 @@ impl <'a>NetworkTraveler <'a> {
         }
         true
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const EXAMPLE: &str = "\
+kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day23::solve_part1, EXAMPLE), Answer::Integer(7));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_example(Day23::solve_part2, EXAMPLE), Answer::from("co,de,ka,ta"));
+    }
+}
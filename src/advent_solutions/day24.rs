@@ -1,20 +1,27 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
-use anyhow::{anyhow, Context};
+use anyhow::Context;
+use rand::seq::SliceRandom;
+use rand::RngExt;
 
-use super::{read_input_file, SolveAdvent};
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::input_error::{parse_lines, InputError};
+use crate::parsing::{capture, exact_paragraphs};
 
 pub struct Day24;
 
+const DAY: u8 = 24;
+
 #[derive(Debug, Clone, Copy)]
-enum GateType{
+pub enum GateType{
     And,
     Or,
     Xor
 }
 
 impl GateType {
-    fn eval(&self, in_1: bool, in_2: bool) -> bool{
+    pub fn eval(&self, in_1: bool, in_2: bool) -> bool{
         //!Execute the operation specified by the gate type
         match self {
             GateType::And => in_1 && in_2,
@@ -28,37 +35,33 @@ impl GateType {
 /// It is not guranteed that we can evaluate the gate at
 /// any given time.
 #[derive(Debug, Clone)]
-struct GateJunction {
+pub struct GateJunction {
     ///The variable name of input_1, such as `x01`
-    in_1: String,
+    pub in_1: String,
     ///The variable name of input_2, such as `y02`
-    in_2: String,
+    pub in_2: String,
     ///The type of gate this junction is
-    gate_type: GateType,
+    pub gate_type: GateType,
     ///The variable name of the ouput, such as `z00`
-    out: String
+    pub out: String
 }
 
 ///The ValueStore stores a mapping of each variable whose
 /// associated value has been computed, or given in the problem input.
-type ValueStore = HashMap<String, bool>;
-
-fn init_value_store(input: &str) -> anyhow::Result<ValueStore> {
-    //! Construct the value store
-    let mut value_store = HashMap::new();
-    for line in input.trim().lines() {
-        let [input_name, input_value]: [&str; 2] = line.split(':').collect::<Vec<_>>().try_into().map_err(|_| anyhow!("Could not split init line correctly"))?;
-        let init_value = match input_value.trim() {
-            "0" => false,
-            "1" => true,
-            other => anyhow::bail!("Encountered invalid initialization value {} not a 0 or 1", other)
-        };
-        value_store.insert(input_name.to_string(), init_value);
-    }
-    Ok(value_store)
+pub type ValueStore = HashMap<String, bool>;
+
+fn parse_initial_value(line: &str) -> Result<(String, bool), InputError> {
+    //! Parse a line such as `x00: 1` into the wire name and its value.
+    let [input_name, input_value] = capture(DAY, line, line, "{}: {}")?;
+    let init_value = match input_value {
+        "0" => false,
+        "1" => true,
+        other => return Err(InputError::at(DAY, line, other, "an initial value of 0 or 1"))
+    };
+    Ok((input_name.to_string(), init_value))
 }
 
-fn generate_final_number(value_store: ValueStore) -> anyhow::Result<u64> {
+pub fn generate_final_number(value_store: ValueStore) -> anyhow::Result<u64> {
     //! Parse all of the numbers starting with `z` to produce a final 64-bit number
     let mut parsed_z_values = Vec::new();
     for (gate, value) in value_store.iter() {
@@ -74,24 +77,23 @@ fn generate_final_number(value_store: ValueStore) -> anyhow::Result<u64> {
 }
 
 impl GateJunction{
-    fn from_line(line: &str) -> anyhow::Result<Self> {
-        let [ins, out]: [&str; 2]  = line.split("->").collect::<Vec<_>>().try_into().map_err(|_| anyhow::anyhow!("Could not construct gate junction from input {line}"))?;
-        let [in1, gate_type, in2]: [&str; 3] = ins.trim().split(' ').collect::<Vec<_>>().try_into().map_err(|_| anyhow!("Failed to coerce gate input"))?;
+    pub fn from_line(line: &str) -> Result<Self, InputError> {
+        let [in1, gate_type, in2, out] = capture(DAY, line, line, "{} {} {} -> {}")?;
         let gate_type = match gate_type {
             "AND" => GateType::And,
             "XOR" => GateType::Xor,
             "OR" => GateType::Or,
-            other => anyhow::bail!("Encountered invalid gate type {}", other)
+            other => return Err(InputError::at(DAY, line, other, "a gate type of AND, OR or XOR"))
         };
         Ok(GateJunction {
-            in_1: in1.trim().to_string(),
-            in_2: in2.trim().to_string(),
+            in_1: in1.to_string(),
+            in_2: in2.to_string(),
             gate_type,
-            out: out.trim().to_string()
+            out: out.to_string()
         })
     }
 
-    fn try_eval(&self, value_store: &ValueStore) -> Option<(String, bool)> {
+    pub fn try_eval(&self, value_store: &ValueStore) -> Option<(String, bool)> {
         //! Attempt to evaluate the value of the gate junction. It is possible that at
         //! the current time we do not know both input values, in which case no progress can be made
         if let Some(value1) = value_store.get(&self.in_1) {
@@ -104,17 +106,70 @@ impl GateJunction{
     }
 }
 
-fn parse_input_file(file_contents: &str) -> anyhow::Result<(Vec<GateJunction>, ValueStore)> {
-    let split_position = file_contents.find("\r\n\r\n").ok_or(anyhow!("Input file did not contain an empty line as expected"))?;
-    let value_store = init_value_store(&file_contents[0..split_position])?;
-    let gate_junctions = (file_contents[split_position..]).trim().lines().map(GateJunction::from_line).collect::<Result<Vec<_>, _>>()?;
+pub fn parse_input_file(file_contents: &str) -> anyhow::Result<(Vec<GateJunction>, ValueStore)> {
+    //! Parse the initial wire values and, after an empty line, the gates.
+    let [initial_values, gates] = exact_paragraphs(DAY, file_contents, "an empty line between the initial values and the gates")?;
+    let value_store = initial_values.parse(|initial_values| parse_lines(initial_values, parse_initial_value))?.into_iter().collect();
+    let gate_junctions = gates.parse(|gates| parse_lines(gates, GateJunction::from_line))?;
     Ok((gate_junctions, value_store))
 }
 
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! A ripple carry adder of `size` bits, adding the `x` wires to the `y` wires into the `z` wires, with random
+    //! initial values. Unlike the real puzzle no gate outputs are swapped, so the circuit adds correctly.
+    let mut wires = HashSet::new();
+    let mut gates = Vec::new();
+    let mut carry: Option<String> = None;
+    for bit in 0..size {
+        let (x, y, z) = (format!("x{:02}", bit), format!("y{:02}", bit), format!("z{:02}", bit));
+        //The carry out of the highest bit is the highest bit of the sum
+        let carry_out = if bit == size - 1 { format!("z{:02}", size) } else { intermediate_wire(&mut wires, rng) };
+        match carry {
+            None => {
+                gates.push((x.clone(), "XOR", y.clone(), z));
+                gates.push((x, "AND", y, carry_out.clone()));
+            }
+            Some(carry_in) => {
+                let [sum, direct_carry, passed_carry] = [(); 3].map(|_| intermediate_wire(&mut wires, rng));
+                gates.push((x.clone(), "XOR", y.clone(), sum.clone()));
+                gates.push((sum.clone(), "XOR", carry_in.clone(), z));
+                gates.push((x, "AND", y, direct_carry.clone()));
+                gates.push((sum, "AND", carry_in, passed_carry.clone()));
+                gates.push((direct_carry, "OR", passed_carry, carry_out.clone()));
+            }
+        }
+        carry = Some(carry_out);
+    }
+    let initial_values = ['x', 'y'].iter()
+        .flat_map(|prefix| (0..size).map(move |bit| (prefix, bit)))
+        .map(|(prefix, bit)| format!("{}{:02}: {}", prefix, bit, rng.random_range(0..=1)))
+        .collect::<Vec<_>>();
+    let mut gates = gates.into_iter().map(|(in_1, gate_type, in_2, out)| {
+        let (in_1, in_2) = if rng.random_bool(0.5) { (in_1, in_2) } else { (in_2, in_1) };
+        format!("{} {} {} -> {}", in_1, gate_type, in_2, out)
+    }).collect::<Vec<_>>();
+    gates.shuffle(rng);
+    format!("{}\n\n{}", initial_values.join("\n"), gates.join("\n"))
+}
+
+fn intermediate_wire(wires: &mut HashSet<String>, rng: &mut InputRng) -> String {
+    //! A new wire name of three letters, none of them `x`, `y` or `z` so that it cannot be mistaken for an input or output.
+    loop {
+        let wire = (0..3).map(|_| rng.random_range(b'a'..=b'w') as char).collect::<String>();
+        if wires.insert(wire.clone()) {
+            return wire;
+        }
+    }
+}
+
 impl SolveAdvent for Day24{
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
-        let file_contents = read_input_file(path_to_file)?;
-        let (gate_junctions, mut value_store) = parse_input_file(&file_contents)?;
+    fn parse(input: &str) -> anyhow::Result<()> {
+        parse_input_file(input)?;
+        Ok(())
+    }
+
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
+        let (gate_junctions, mut value_store) = parse_input_file(input)?;
         let mut gate_junctions = gate_junctions.into_iter().collect::<VecDeque<_>>();
         //Iterate over gate junctions, evaluating each gate where possible. If a gate is successfully evaluated,
         //its output value is written in the `value_store` and the `gate_junction` is removed from the `gate_junctions` queue
@@ -131,11 +186,42 @@ impl SolveAdvent for Day24{
             }
         }
         let final_number = generate_final_number(value_store)?;
-        println!("Final number produced is: {}", final_number);
-        Ok(())
+        Ok(final_number.into())
     }
+}
 
-    fn solve_part2(_path_to_file: &str) -> anyhow::Result<()> {
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const SMALL_EXAMPLE: &str = "\
+x00: 1
+x01: 1
+x02: 1
+y00: 0
+y01: 1
+y02: 0
+
+x00 AND y00 -> z00
+x01 XOR y01 -> z01
+x02 OR y02 -> z02
+";
+
+    #[test]
+    fn part1_small_example() {
+        assert_eq!(solve_example(Day24::solve_part1, SMALL_EXAMPLE), Answer::Integer(4));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn generated_adders_add() {
+        let input = generate(20, &mut InputRng::seed_from_u64(24));
+        let (gate_junctions, value_store) = parse_input_file(&input).unwrap();
+        //5 gates per full adder, and 2 for the half adder of the lowest bit
+        assert_eq!(gate_junctions.len(), 5 * 19 + 2);
+        let number = |prefix: char| (0..20).map(|bit| (value_store[&format!("{}{:02}", prefix, bit)] as u64) << bit).sum::<u64>();
+        assert_eq!(Day24::solve_part1(&input).unwrap(), Answer::from(number('x') + number('y')));
+    }
+}
@@ -1,49 +1,64 @@
-use anyhow::anyhow;
+use rand::RngExt;
 
-use super::{read_input_file, SolveAdvent};
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::input_error::InputError;
+use crate::parsing::paragraphs;
 
 pub struct Day25;
 
+const DAY: u8 = 25;
+
 #[derive(Debug, Clone, Copy)]
-enum KeyOrLock {
+pub enum KeyOrLock {
     Lock,
     Key
 }
 
 #[derive(Debug, Clone, Copy)]
-struct Schematic {
-    schematic_type: KeyOrLock,
-    //The heights of each column in the key or lock
-    heights: [u64;5]
+pub struct Schematic {
+    pub schematic_type: KeyOrLock,
+    ///The heights of each column in the key or lock
+    pub heights: [u64;5]
 }
 
+///Locks and keys are always this many columns wide.
+const SCHEMATIC_WIDTH: usize = 5;
+
 impl Schematic {
-    fn from_schematic_diagram(schematic: &str) -> anyhow::Result<Self> {
-        let schematic = schematic.trim().lines().map(|line| line.chars().collect::<Vec<_>>()).collect::<Vec<_>>();
+    pub fn from_schematic_diagram(schematic: &str) -> Result<Self, InputError> {
+        let rows = schematic.lines().collect::<Vec<_>>();
+        let (Some(first_row), Some(last_row)) = (rows.first(), rows.last()) else {
+            return Err(InputError::at_end(DAY, schematic, "a lock or key schematic"));
+        };
+        for row in rows.iter() {
+            if let Some((offset, symbol)) = row.char_indices().find(|(_, symbol)| *symbol != '#' && *symbol != '.') {
+                return Err(InputError::at(DAY, schematic, &row[offset..offset + symbol.len_utf8()], "`#` or `.`"));
+            }
+            if row.len() != SCHEMATIC_WIDTH {
+                return Err(InputError::at(DAY, schematic, row, format!("a row {} characters wide", SCHEMATIC_WIDTH)));
+            }
+        }
         let obj_type = {
-            if schematic[0].iter().all(|symbol| symbol == &'.') {
+            if first_row.chars().all(|symbol| symbol == '.') {
                 //Keys have an empty first row
                 KeyOrLock::Key
-            } else if schematic.last().unwrap().iter().all(|symbol| symbol == &'.') {
+            } else if last_row.chars().all(|symbol| symbol == '.') {
                 //locks have an empty last row
                 KeyOrLock::Lock
             } else {
-                anyhow::bail!(format!("Schematic {:?} could not be classified as a lock or key", schematic));
+                return Err(InputError::at(DAY, schematic, first_row, "an empty top row (a key) or an empty bottom row (a lock)"));
             }
         };
-        let mut heights = Vec::with_capacity(5);
-        for col_number in 0..schematic[0].len() {
-            let mut height = 0;
-            for row in schematic.iter() {
-                if row[col_number] == '#' {
-                    height += 1;
-                }
-            }
+        let mut heights = [0; SCHEMATIC_WIDTH];
+        for (col_number, height) in heights.iter_mut().enumerate() {
+            let filled = rows.iter().filter(|row| row.as_bytes()[col_number] == b'#').count() as u64;
             //Check before subtracting one to prevent wrapping the u64 in case its 0, which should never happen
-            anyhow::ensure!(height>= 1, anyhow!("Encountered a column whose height is 0, which is illegal!"));
-            heights.push(height - 1); // the rules of the problem do not count one of the rows as a height so subtract 1
+            if filled == 0 {
+                return Err(InputError::at(DAY, schematic, &first_row[col_number..col_number + 1], "at least one `#` in every column"));
+            }
+            *height = filled - 1; // the rules of the problem do not count one of the rows as a height so subtract 1
         }
-        let heights: [u64; 5] = heights.try_into().map_err(|_| anyhow!("Collected heights were not of length 5"))?;
         Ok(Schematic {
             schematic_type: obj_type,
             heights
@@ -51,12 +66,13 @@ impl Schematic {
     }
 }
 
-fn parse_schematics(file_contents: &str) -> anyhow::Result<Vec<Schematic>> {
-    let parsed_schematics = file_contents.split("\r\n\r\n").map(Schematic::from_schematic_diagram).collect::<Result<Vec<_>, _>>()?;
-    Ok(parsed_schematics)
+pub fn parse_schematics(file_contents: &str) -> anyhow::Result<Vec<Schematic>> {
+    //! Schematics are separated by an empty line.
+    let parsed_schematics = paragraphs(file_contents).map(|schematic| schematic.parse(Schematic::from_schematic_diagram));
+    Ok(parsed_schematics.collect::<Result<Vec<_>, _>>()?)
 }
 
-fn is_valid_lock_key_pair(lock: &[u64; 5], key: &[u64; 5]) -> bool {
+pub fn is_valid_lock_key_pair(lock: &[u64; 5], key: &[u64; 5]) -> bool {
     //! Check if a lock/key pair is valid
     for (lock_val, key_val) in lock.iter().zip(key.iter()) {
         if *lock_val + *key_val > 5 {
@@ -66,10 +82,30 @@ fn is_valid_lock_key_pair(lock: &[u64; 5], key: &[u64; 5]) -> bool {
     true
 }
 
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! `size` schematics, each a lock or a key with pins of height 0 to 5.
+    let mut schematics = Vec::with_capacity(size);
+    for _ in 0..size {
+        let heights = [(); SCHEMATIC_WIDTH].map(|_| rng.random_range(0..=5));
+        let is_lock = rng.random_bool(0.5);
+        let rows = (0..7).map(|row| {
+            //Locks are filled down from the top row, keys up from the bottom row
+            let filled_to = if is_lock { row } else { 6 - row };
+            heights.iter().map(|height| if filled_to <= *height { '#' } else { '.' }).collect::<String>()
+        }).collect::<Vec<_>>();
+        schematics.push(rows.join("\n"));
+    }
+    schematics.join("\n\n")
+}
+
 impl SolveAdvent for Day25 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
-        let file_contents = read_input_file(path_to_file)?;
-        let parsed_schematics = parse_schematics(&file_contents)?;
+    fn parse(input: &str) -> anyhow::Result<()> {
+        parse_schematics(input)?;
+        Ok(())
+    }
+
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
+        let parsed_schematics = parse_schematics(input)?;
         //Seperate locks and keys. It would probably be more idiomatic to have two distinct
         //types so that the `is_valid_lock_key_pair` could be protected using types from bugs.
         let mut locks = Vec::new();
@@ -89,11 +125,73 @@ impl SolveAdvent for Day25 {
                 }
             }
         }
-        println!("There are a total of {} unique lock-key pairs that are valid", valid_lock_key_pairs);
-        Ok(())
+        Ok(valid_lock_key_pairs.into())
     }
 
-    fn solve_part2(_path_to_file: &str) -> anyhow::Result<()> {
-        Ok(())
+    fn solve_part2(_input: &str) -> anyhow::Result<Answer> {
+        //! Christmas day only has a single puzzle, the second star is awarded for finishing all other days.
+        anyhow::bail!("Day 25 does not have a part 2 puzzle")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const EXAMPLE: &str = "\
+#####
+.####
+.####
+.####
+.#.#.
+.#...
+.....
+
+#####
+##.##
+.#.##
+...##
+...#.
+...#.
+.....
+
+.....
+#....
+#....
+#...#
+#.#.#
+#.###
+#####
+
+.....
+.....
+#.#..
+###..
+###.#
+###.#
+#####
+
+.....
+.....
+.....
+#....
+#.#..
+#.#.#
+#####
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day25::solve_part1, EXAMPLE), Answer::Integer(3));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn bad_symbols_are_located_across_schematics() {
+        let input = EXAMPLE.trim().replacen("#.#.#\n#####", "#.#o#\n#####", 1);
+        let error = parse_schematics(&input).unwrap_err();
+        let error = error.downcast_ref::<InputError>().unwrap();
+        assert_eq!((error.line, error.column, error.found.as_str()), (38, 4, "o"));
+        assert!(error.to_string().ends_with("38 | #.#o#\n   |    ^"));
+    }
+}
@@ -1,7 +1,10 @@
 
 use anyhow::Context;
+use rand::seq::IndexedRandom;
+use rand::RngExt;
 
-use super::{read_input_file, SolveAdvent};
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
 
 pub struct Day3;
 
@@ -10,7 +13,7 @@ const DONT_WINDOW_SIZE: usize = "don't()".len();
 const DO_WINDOW_SIZE: usize = "do()".len();
 
 
-fn parse_mul_operation(input: &str) -> anyhow::Result<i64> {
+pub fn parse_mul_operation(input: &str) -> anyhow::Result<i64> {
     //! Parse the string that directly follows a `mul(` instruction.
     //! Will return an error as early as one is encountered.
     //! A valid input following the `mul(` string would be this: `5,5)`.
@@ -40,57 +43,106 @@ fn parse_mul_operation(input: &str) -> anyhow::Result<i64> {
     Ok(num1 * num2)
 }
 
+///The corruption that instructions are hidden among, including pieces of instructions.
+const CORRUPTION: [&str; 36] = [
+    "!", "@", "#", "$", "%", "^", "&", "*", "(", ")", "[", "]", "{", "}", "<", ">", ",", ";",
+    ":", "'", "?", "+", "-", "/", " ", "\n", "from()", "select()", "why()", "where()", "how()", "who()",
+    "mul", "mul(", "do", "don't",
+];
+
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! Corrupted memory with `size` instructions hidden in it. Most are valid `mul`, `do()` and `don't()`
+    //! instructions, the rest are near misses such as `mul(4*3)` or `mul ( 2 , 4 )`.
+    let mut memory = String::new();
+    for _ in 0..size {
+        for _ in 0..rng.random_range(0..8) {
+            memory.push_str(CORRUPTION.choose(rng).unwrap());
+        }
+        let (num1, num2) = (rng.random_range(1..1000), rng.random_range(1..1000));
+        let instruction = match rng.random_range(0..10) {
+            0 => "do()".to_string(),
+            1 => "don't()".to_string(),
+            2 => format!("mul({},{}]", num1, num2),
+            3 => format!("mul ( {} , {} )", num1, num2),
+            4 => format!("mul({}*{})", num1, num2),
+            _ => format!("mul({},{})", num1, num2),
+        };
+        memory.push_str(&instruction);
+    }
+    memory.trim().to_string()
+}
+
 impl SolveAdvent for Day3 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
         //! Evaluate all valid multiply operations
-        let file_contents = read_input_file(path_to_file)?;
         let mut lower_boundary = 0;
         let mut multiplied_sum = 0;
-        while let Some(window) = file_contents.get(lower_boundary..lower_boundary + MUL_WINDOW_SIZE) {
+        while let Some(window) = input.get(lower_boundary..lower_boundary + MUL_WINDOW_SIZE) {
             if window == "mul(" {
-                let remaining_str = file_contents.get(lower_boundary + MUL_WINDOW_SIZE..).ok_or(anyhow::anyhow!("File slice went out of bounds"))?;
+                let remaining_str = input.get(lower_boundary + MUL_WINDOW_SIZE..).ok_or(anyhow::anyhow!("File slice went out of bounds"))?;
                 if let Ok(multiplied_result) = parse_mul_operation(remaining_str) {
                     multiplied_sum += multiplied_result;
                 }
             }
             lower_boundary += 1
         }
-        println!("Sum of all mul operations is {}", multiplied_sum);
-        Ok(())
+        Ok(multiplied_sum.into())
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
         //! Evaluate all multiply operations as above, but ignore all instructions
         //! after encountering a `don't()` instruction until the next `do()` instruction.
-        let file_contents = read_input_file(path_to_file)?;
         let mut multiplied_sum = 0;
         let mut mul_op_enabled = true;
-        for lower_boundary in 0..file_contents.len() {
+        for lower_boundary in 0..input.len() {
             //While its tempting to use the same window size for all ops, this causes issues at the end of the string parsing
             //when there could be a valid mul op but a dont() op would not fit.
-            if let Some(command) = file_contents.get(lower_boundary..lower_boundary + DONT_WINDOW_SIZE) {
+            if let Some(command) = input.get(lower_boundary..lower_boundary + DONT_WINDOW_SIZE) {
                 if command == "don't()" {
                     mul_op_enabled = false;
                     continue;
                 }
             }
-            if let Some(command) = file_contents.get(lower_boundary..lower_boundary + DO_WINDOW_SIZE) {
+            if let Some(command) = input.get(lower_boundary..lower_boundary + DO_WINDOW_SIZE) {
                 if command == "do()" {
                     mul_op_enabled = true;
                     continue;
                 }
             }
-            if let Some(command) = file_contents.get(lower_boundary..lower_boundary + MUL_WINDOW_SIZE) {
+            if let Some(command) = input.get(lower_boundary..lower_boundary + MUL_WINDOW_SIZE) {
                 if command == "mul(" && mul_op_enabled {
-                        if let Ok(mul_result) = parse_mul_operation(&file_contents[lower_boundary + MUL_WINDOW_SIZE..]) {
+                        if let Ok(mul_result) = parse_mul_operation(&input[lower_boundary + MUL_WINDOW_SIZE..]) {
                             multiplied_sum += mul_result;
                         }
                     
                 }
             }
         }
-        println!("Sum of all enabled multiplications is {}", multiplied_sum);
+        Ok(multiplied_sum.into())
+    }
+}
 
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const PART1_EXAMPLE: &str = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
+    const PART2_EXAMPLE: &str = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day3::solve_part1, PART1_EXAMPLE), Answer::Integer(161));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_example(Day3::solve_part2, PART2_EXAMPLE), Answer::Integer(48));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn mul_operation_rejects_illegal_characters() {
+        assert_eq!(parse_mul_operation("5,5)").unwrap(), 25);
+        assert!(parse_mul_operation("32,64]").is_err());
+    }
+}
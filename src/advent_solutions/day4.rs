@@ -1,147 +1,168 @@
 
-use super::{read_input_file, SolveAdvent};
+use rand::seq::IndexedRandom;
+
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::input_error::InputError;
 
 pub struct Day4;
 
+const DAY: u8 = 4;
+
 ///Wrapper around the word map so that methods
 /// can be implemented.
-struct WordMap(Vec<Vec<char>>);
+pub struct WordMap(pub Grid<char>);
 
 impl WordMap {
-    fn safe_read(&self, row: i64, col: i64) -> Option<char> {
+    pub fn parse(file_contents: &str) -> Result<Self, InputError> {
+        //! Parse the input file into a grid of letters
+        Ok(WordMap(Grid::parse_chars(DAY, file_contents)?))
+    }
+
+    pub fn safe_read(&self, row: i64, col: i64) -> Option<char> {
         //! Facade to safely read from the `WordMap`. Takes row/col as i64
         //! so that we can go off the map without causing rust to panic (which a usize would do if it went negative in dev mode).
-        if row < 0 || col < 0 {
-            return None;
-        }
-        if let Some(row) = self.0.get(row as usize) {
-            if let Some(item) = row.get(col as usize) {
-                return Some(*item);
-            }
+        self.0.get(Point::new(row, col)).copied()
+    }
+
+    fn read_4(&self, row: i64, col: i64, step: Point) -> Option<String> {
+        //! Get the string of 4 letters starting at (and including) the base position, taking `step` each time.
+        let word = self.0.ray(Point::new(row, col), step).take(4).collect::<String>();
+        if word.chars().count() == 4 {
+            return Some(word);
         }
         None
     }
 
-    fn read_diagonal_4_nw(&self, row: i64, col: i64) -> Option<String> {
+    pub fn read_diagonal_4_nw(&self, row: i64, col: i64) -> Option<String> {
         //! Get the string 4 Northwest (including the current base position) if it exists.
-        let above: Option<Vec<_>> = [self.safe_read(row, col), self.safe_read(row -1, col - 1), self.safe_read(row - 2, col - 2), self.safe_read(row - 3, col - 3)].into_iter().collect();
-        let above = above?;
-        Some(above.into_iter().collect::<String>())
+        self.read_4(row, col, Point::new(-1, -1))
     }
 
-    fn read_diagonal_4_ne(&self, row: i64, col: i64) -> Option<String> {
+    pub fn read_diagonal_4_ne(&self, row: i64, col: i64) -> Option<String> {
         //! Get the string 4 Northeast if it exists.
-        let above: Option<Vec<_>> = [self.safe_read(row, col), self.safe_read(row - 1, col + 1), self.safe_read(row -2, col + 2), self.safe_read(row - 3, col + 3)].into_iter().collect();
-        let above = above?;
-        Some(above.into_iter().collect::<String>())
+        self.read_4(row, col, Point::new(-1, 1))
     }
 
-    fn read_diagonal_4_sw(&self, row: i64, col: i64) -> Option<String> {
+    pub fn read_diagonal_4_sw(&self, row: i64, col: i64) -> Option<String> {
         //! Get the string 4 Southwest if it exists.
-        let above: Option<Vec<_>> = [self.safe_read(row, col), self.safe_read(row + 1, col - 1), self.safe_read(row + 2, col - 2), self.safe_read(row + 3, col - 3)].into_iter().collect();
-        let above = above?;
-        Some(above.into_iter().collect::<String>())
+        self.read_4(row, col, Point::new(1, -1))
     }
 
-    fn read_diagonal_4_se(&self, row: i64, col: i64) -> Option<String> {
+    pub fn read_diagonal_4_se(&self, row: i64, col: i64) -> Option<String> {
         //! Get the string 4 Southeast if it exists.
-        let above: Option<Vec<_>> = [self.safe_read(row, col), self.safe_read(row + 1, col + 1), self.safe_read(row + 2, col + 2), self.safe_read(row + 3, col + 3)].into_iter().collect();
-        let above = above?;
-        Some(above.into_iter().collect::<String>())
+        self.read_4(row, col, Point::new(1, 1))
     }
 
-    fn read_4_left(&self, row: i64, col: i64) -> Option<String> {
+    pub fn read_4_left(&self, row: i64, col: i64) -> Option<String> {
         //! Get the string 4 left if it exists.
-        let above: Option<Vec<_>> = [self.safe_read(row, col), self.safe_read(row, col - 1), self.safe_read(row, col - 2), self.safe_read(row, col - 3)].into_iter().collect();
-        let above = above?;
-        Some(above.into_iter().collect::<String>())
+        self.read_4(row, col, Point::new(0, -1))
     }
 
-    fn read_4_right(&self, row: i64, col: i64) -> Option<String> {
+    pub fn read_4_right(&self, row: i64, col: i64) -> Option<String> {
         //! Get the string 4 right if it exists.
-        let above: Option<Vec<_>> = [self.safe_read(row, col), self.safe_read(row, col + 1), self.safe_read(row, col + 2), self.safe_read(row, col + 3)].into_iter().collect();
-        let above = above?;
-        Some(above.into_iter().collect::<String>())
+        self.read_4(row, col, Point::new(0, 1))
     }
 
-    fn read_4_below(&self, row: i64, col: i64) -> Option<String> {
+    pub fn read_4_below(&self, row: i64, col: i64) -> Option<String> {
         //! Get the string 4 below if it exists.
-        let above: Option<Vec<_>> = [self.safe_read(row, col), self.safe_read(row + 1, col ), self.safe_read(row + 2, col), self.safe_read(row + 3, col)].into_iter().collect();
-        let above = above?;
-        Some(above.into_iter().collect::<String>())
+        self.read_4(row, col, Point::new(1, 0))
     }
 
-
-    fn read_4_above(&self, row: i64, col: i64) -> Option<String> {
-        //! Get the string 4 above it it exists.
-        let above: Option<Vec<_>> = [self.safe_read(row, col), self.safe_read(row - 1, col), self.safe_read(row - 2, col), self.safe_read(row - 3, col)].into_iter().collect();
-        let above = above?;
-        Some(above.into_iter().collect::<String>())
+    pub fn read_4_above(&self, row: i64, col: i64) -> Option<String> {
+        //! Get the string 4 above it if it exists.
+        self.read_4(row, col, Point::new(-1, 0))
     }
 
 }
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! A `size` by `size` word search of the letters X, M, A and S.
+    Grid::filled(size, size, 'X').map(|_| *['X', 'M', 'A', 'S'].choose(rng).unwrap()).to_string()
+}
+
 impl SolveAdvent for Day4 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
-        let file_contents = read_input_file(path_to_file)?;
-        let word_search = WordMap(file_contents.lines().map(|line| line.trim().chars().collect::<Vec<_>>()).collect::<Vec<_>>());
+    fn parse(input: &str) -> anyhow::Result<()> {
+        WordMap::parse(input)?;
+        Ok(())
+    }
+
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
+        let word_search = WordMap::parse(input)?;
         let mut xmas_word_count = 0;
-        for row in 0..word_search.0.len() {
-            for col in 0..word_search.0.first().unwrap().len() {
-                let row = row as i64;
-                let col = col as i64;
-                //Read all 8 allowed cardinal directions from the base `row, col` position.
-                let results_iterator = [
-                    word_search.read_4_above(row, col), 
-                    word_search.read_4_below(row, col),
-                    word_search.read_4_left(row, col),
-                    word_search.read_4_right(row, col), 
-                    word_search.read_diagonal_4_ne(row, col), 
-                    word_search.read_diagonal_4_nw(row, col), 
-                    word_search.read_diagonal_4_se(row, col), 
-                    word_search.read_diagonal_4_sw(row, col)
-                ].into_iter().flatten();
-                //Count all instances of 'XMAS'. Note that we do not count backwards instances,
-                //as a future iteration of this loop will find this facing forward and we don't want to double count!
-                for word_found in results_iterator {
-                    if word_found == "XMAS" {
-                        xmas_word_count += 1;
-                    }
+        for Point { row, col } in word_search.0.positions() {
+            //Read all 8 allowed cardinal directions from the base `row, col` position.
+            let results_iterator = [
+                word_search.read_4_above(row, col), 
+                word_search.read_4_below(row, col),
+                word_search.read_4_left(row, col),
+                word_search.read_4_right(row, col), 
+                word_search.read_diagonal_4_ne(row, col), 
+                word_search.read_diagonal_4_nw(row, col), 
+                word_search.read_diagonal_4_se(row, col), 
+                word_search.read_diagonal_4_sw(row, col)
+            ].into_iter().flatten();
+            //Count all instances of 'XMAS'. Note that we do not count backwards instances,
+            //as a future iteration of this loop will find this facing forward and we don't want to double count!
+            for word_found in results_iterator {
+                if word_found == "XMAS" {
+                    xmas_word_count += 1;
                 }
             }
         }
-        println!("XMAS word count: {:?}", xmas_word_count);
-        Ok(())
+        Ok(xmas_word_count.into())
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
-        let file_contents = read_input_file(path_to_file)?;
-        let word_search = WordMap(file_contents.lines().map(|line| line.trim().chars().collect::<Vec<_>>()).collect::<Vec<_>>());
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
+        let word_search = WordMap::parse(input)?;
         let mut xmases_count = 0;
-        for row in 0..word_search.0.len() {
-            for col in 0..word_search.0.first().unwrap().len() {
-                if word_search.0[row][col] != 'A' {
-                    continue;
-                }
-                //At this point we know that we are centered on an 'A',
-                //we need to check the 4 diagonals to see if they form the required
-                // X-MAS x shape.
-                let row = row as i64;
-                let col = col as i64;
-                let diagonal_nw_to_se = [
-                    word_search.safe_read(row - 1, col - 1), 
-                    word_search.safe_read(row + 1, col + 1)
-                ].into_iter().flatten().collect::<String>();
-                let diagonal_ne_to_sw = [
-                    word_search.safe_read(row - 1, col + 1),
-                    word_search.safe_read(row + 1, col - 1)
-                ].into_iter().flatten().collect::<String>();
-                if (diagonal_ne_to_sw == "MS" || diagonal_ne_to_sw == "SM") && (diagonal_nw_to_se == "MS" || diagonal_nw_to_se == "SM") {
-                        xmases_count += 1;
-                    
-                }
+        for Point { row, col } in word_search.0.find_all(&'A') {
+            //At this point we know that we are centered on an 'A',
+            //we need to check the 4 diagonals to see if they form the required
+            // X-MAS x shape.
+            let diagonal_nw_to_se = [
+                word_search.safe_read(row - 1, col - 1), 
+                word_search.safe_read(row + 1, col + 1)
+            ].into_iter().flatten().collect::<String>();
+            let diagonal_ne_to_sw = [
+                word_search.safe_read(row - 1, col + 1),
+                word_search.safe_read(row + 1, col - 1)
+            ].into_iter().flatten().collect::<String>();
+            if (diagonal_ne_to_sw == "MS" || diagonal_ne_to_sw == "SM") && (diagonal_nw_to_se == "MS" || diagonal_nw_to_se == "SM") {
+                    xmases_count += 1;
+                
             }
         }
-        println!("The total number of X-MAS's is {}", xmases_count);
-        Ok(())
+        Ok(xmases_count.into())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const EXAMPLE: &str = "\
+MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day4::solve_part1, EXAMPLE), Answer::Integer(18));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_example(Day4::solve_part2, EXAMPLE), Answer::Integer(9));
+    }
+}
@@ -1,23 +1,28 @@
 
 use std::collections::HashMap;
 
-use anyhow::{anyhow, Context};
+use rand::seq::{index, SliceRandom};
+use rand::RngExt;
 
-use super::{read_input_file, SolveAdvent};
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::input_error::{parse_number, InputError};
 
 pub struct  Day5;
 
+const DAY: u8 = 5;
+
 #[derive(Debug, Clone)]
-struct SleighLauncher {
+pub struct SleighLauncher {
     ///The ordering rules of the problem.
-    update_rules: Vec<(i64, i64)>, 
+    pub update_rules: Vec<(i64, i64)>, 
     ///the parsed updates from the input file. Note that these updates have
     /// not been checked to see if they are valid.
-    potential_updates: Vec<Vec<i64>>
+    pub potential_updates: Vec<Vec<i64>>
 }
 
 impl SleighLauncher {
-    fn is_valid_update(update_rules: &[(i64, i64)], update: &[i64]) -> bool {
+    pub fn is_valid_update(update_rules: &[(i64, i64)], update: &[i64]) -> bool {
         //! Check if a row follows all of the ordering rules.
         
         //The inverted_map is a hashmap of value to index. In essence, when the problem discusses proper ordering all we
@@ -50,7 +55,7 @@ impl SleighLauncher {
         None
     }
 
-    fn fix_bad_update(update_rules: &[(i64, i64)], update: &mut [i64]) {
+    pub fn fix_bad_update(update_rules: &[(i64, i64)], update: &mut [i64]) {
         //! So long as the update is not valid, continue to swap any pair of numbers that 
         //! violate a rule. The outer while loop gurantees that if the function returns, the `update`
         //! is guranteed to be valid. It is not obvious to me whether or not this loop could potentially be infinite
@@ -70,24 +75,25 @@ impl SleighLauncher {
     }
 }
 
-fn parse_and_preprocess_input(file_contents: &str) -> anyhow::Result<SleighLauncher> {
+pub fn parse_and_preprocess_input(file_contents: &str) -> anyhow::Result<SleighLauncher> {
     //! Preprocess the input file contents into a `SleighLauncher`
     let mut finished_rules = false;
     let mut rules = Vec::new();
     let mut updates = Vec::new();
-    for line in file_contents.lines() {
+    for (line_index, line) in file_contents.lines().enumerate() {
         if line.is_empty() {
             finished_rules = true;
             continue;
         }
+        let locate = |error: InputError| error.on_line(line_index + 1);
         if !finished_rules {
-            let [num1, num2]: [&str; 2] = line.trim().split('|').collect::<Vec<_>>().try_into().map_err(|_err| anyhow!("Failed to coerce into array of length 2"))?;
-            let num1 = num1.parse::<i64>()?;
-            let num2 = num2.parse::<i64>()?;
+            let (num1, num2) = line.split_once('|').ok_or_else(|| locate(InputError::at_end(DAY, line, "a rule of the form `<page>|<page>`")))?;
+            let num1 = parse_number::<i64>(DAY, line, num1).map_err(locate)?;
+            let num2 = parse_number::<i64>(DAY, line, num2).map_err(locate)?;
             rules.push((num1, num2));
         } else {
-            let page_updates: Result<Vec<_>, _> = line.trim().split(',').map(|num| num.parse::<i64>()).collect();
-            let page_updates = page_updates.context("Failed to process row into list of page updates")?;
+            let page_updates: Result<Vec<_>, _> = line.split(',').map(|num| parse_number::<i64>(DAY, line, num)).collect();
+            let page_updates = page_updates.map_err(locate)?;
             updates.push(page_updates);
 
         }
@@ -98,23 +104,51 @@ fn parse_and_preprocess_input(file_contents: &str) -> anyhow::Result<SleighLaunc
     })
 }
 
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! Ordering rules for every pair of 49 two digit pages, followed by `size` updates of 5 to 23 of those
+    //! pages. About half the updates are already in the right order.
+    let mut pages = (10..100).collect::<Vec<i64>>();
+    pages.shuffle(rng);
+    pages.truncate(49);
+    let mut rules = Vec::new();
+    for (index, lower) in pages.iter().enumerate() {
+        for upper in pages[index + 1..].iter() {
+            rules.push(format!("{}|{}", lower, upper));
+        }
+    }
+    rules.shuffle(rng);
+    let mut updates = Vec::with_capacity(size);
+    for _ in 0..size {
+        let update_length = 2 * rng.random_range(2..=11) + 1;
+        let mut page_indexes = index::sample(rng, pages.len(), update_length).into_vec();
+        //The pages are listed in the order the rules require
+        if rng.random_bool(0.5) {
+            page_indexes.sort();
+        }
+        updates.push(page_indexes.iter().map(|page_index| pages[*page_index].to_string()).collect::<Vec<_>>().join(","));
+    }
+    format!("{}\n\n{}", rules.join("\n"), updates.join("\n"))
+}
+
 impl SolveAdvent for Day5 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
-        let file_contents = read_input_file(path_to_file)?;
-        let sleigh_launcher = parse_and_preprocess_input(&file_contents)?;
+    fn parse(input: &str) -> anyhow::Result<()> {
+        parse_and_preprocess_input(input)?;
+        Ok(())
+    }
+
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
+        let sleigh_launcher = parse_and_preprocess_input(input)?;
         let mut total_middle_numbers = 0;
         for potential_update in sleigh_launcher.potential_updates {
             if SleighLauncher::is_valid_update(&sleigh_launcher.update_rules, &potential_update) {
                 total_middle_numbers += *potential_update.get(potential_update.len() / 2).unwrap();
             } 
         }
-        println!("Sum of all middle page numbers is {}", total_middle_numbers);
-        Ok(())
+        Ok(total_middle_numbers.into())
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
-        let file_contents = read_input_file(path_to_file)?;
-        let mut sleigh_launcher = parse_and_preprocess_input(&file_contents)?;
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
+        let mut sleigh_launcher = parse_and_preprocess_input(input)?;
         let mut total_middle_numbers = 0;
         for potential_update in sleigh_launcher.potential_updates.iter_mut() {
             if !SleighLauncher::is_valid_update(&sleigh_launcher.update_rules, potential_update) {
@@ -122,7 +156,53 @@ impl SolveAdvent for Day5 {
                 total_middle_numbers += *potential_update.get(potential_update.len() / 2).unwrap();
             } 
         }
-        println!("Sum of middle page numbers after fix is {}", total_middle_numbers);
-        Ok(())
+        Ok(total_middle_numbers.into())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const EXAMPLE: &str = "\
+47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day5::solve_part1, EXAMPLE), Answer::Integer(143));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_example(Day5::solve_part2, EXAMPLE), Answer::Integer(123));
+    }
+}
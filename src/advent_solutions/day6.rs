@@ -1,161 +1,268 @@
 use std::collections::HashSet;
 
-use super::{read_input_file, SolveAdvent};
+use rand::RngExt;
+
+use super::{Answer, SolveAdvent};
+use crate::animate::Recorder;
+use crate::generate::InputRng;
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::input_error::InputError;
+use crate::parallel;
+use crate::visualize::{Color, Scene};
 
 
 pub struct  Day6;
 
+const DAY: u8 = 6;
+
 #[derive(Debug, Clone)]
-struct Guard {
-    ///Guards current row position
-    row: i64,
-    ///Guards current column position
-    col: i64,
+pub struct Guard {
+    ///Guards current position
+    pub position: Point,
     ///Guards current direction
-    direction: Direction,
+    pub direction: Direction,
+}
+
+pub fn parse_lab_map(file_contents: &str) -> Result<Grid<char>, InputError> {
+    //! Parse the input file into the lab map of symbols.
+    Grid::parse(DAY, file_contents, |symbol| "#.^>v<".contains(symbol).then_some(symbol), "one of `#`, `.`, `^`, `>`, `v` or `<`")
 }
 
 impl Guard {
-    fn patrol_lab(&mut self, lab_map: &[Vec<char>]) -> anyhow::Result<()> {
+    pub fn patrol_lab(&mut self, lab_map: &Grid<char>) -> anyhow::Result<()> {
         //! Move the guard by 1 step. 
         //! If possible the guard takes 1 step in the same direction they are facing.
         //! Otherwise, rotate 90 degrees until motion is possible. 
         //! Returns an error if the guard walks off the lab map (which is actually the goal)
         loop {
-            let (next_row, next_col) = match self.direction {
-                Direction::Right => ( self.row, self.col + 1),
-                Direction::Down => (self.row + 1, self.col),
-                Direction::Left => (self.row, self.col - 1),
-                Direction::Up => (self.row - 1, self.col)
-            };
-            if next_row < 0 || next_col < 0 || next_row  >= lab_map.len() as i64 || next_col  >= lab_map[0].len() as i64 {
+            let next_position = self.position.step(self.direction);
+            let Some(&next_space_character) = lab_map.get(next_position) else {
                 anyhow::bail!("Guard has walked off the map!");
-            }
-            let next_space_character = lab_map[next_row as usize][next_col as usize];
+            };
             if next_space_character == '.' || next_space_character == '^' {
                 //The next space is valid, so we step and return.
-                self.col = next_col;
-                self.row = next_row;
+                self.position = next_position;
                 return Ok(());
             } else {
                 //If we are facing an obstacle, we rotate 90 degrees and try again
-                self.direction.right_90_degrees();
+                self.direction = self.direction.rotate_clockwise();
             }
         }
     }
-    fn try_new(lab_map: &[Vec<char>]) -> anyhow::Result<Self> {
+    pub fn try_new(lab_map: &Grid<char>) -> anyhow::Result<Self> {
         //! Construct a starting guard from the input lab map.
-        for (row_number, row) in lab_map.iter().enumerate() {
-            for (col_number, col) in row.iter().enumerate() {
-                let row_number = row_number as i64;
-                let col_number = col_number as i64;
-                match col {
-                    '^' => {
-                        return Ok(Guard {
-                            row: row_number, 
-                            col: col_number, 
-                            direction: Direction::Up,
-                        })
-                    }, 
-                    '>' => {
-                        return Ok(Guard { 
-                            row: row_number, 
-                            col: col_number, 
-                            direction: Direction::Right})
-                    },
-                    'v' => {
-                        return Ok(Guard{
-                            row: row_number, 
-                            col: col_number, 
-                            direction: Direction::Down})
-                    },
-                    '<' => {
-                        return Ok(Guard { 
-                            row: row_number, 
-                            col: col_number, 
-                            direction: Direction::Left })
-                    }
-                    _ => {}
-                }
+        for (position, symbol) in lab_map.iter() {
+            if let Some(direction) = Direction::from_arrow(*symbol) {
+                return Ok(Guard { position, direction });
             }
         }
         anyhow::bail!("Lab map did not contain a starting guard position!");
     }
 }
 
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-enum Direction {
-    Up, 
-    Down, 
-    Left,
-    Right
+pub fn patrol_path(lab_map: &Grid<char>) -> anyhow::Result<HashSet<Point>> {
+    //! Every position the guard visits before walking off the map, the start included.
+    let mut guard = Guard::try_new(lab_map)?;
+    let mut visit_history = HashSet::new();
+    visit_history.insert(guard.position);
+    //When the guard patrol_lab call returns an error, the guard
+    // has walked off the map.
+    while guard.patrol_lab(lab_map).is_ok() {
+        //Keep track of unique visits
+        visit_history.insert(guard.position);
+    }
+    Ok(visit_history)
+}
+
+pub fn visualize(input: &str) -> anyhow::Result<Scene> {
+    //! The lab map with the guard's patrol path highlighted.
+    let lab_map = parse_lab_map(input)?;
+    let guard = Guard::try_new(&lab_map)?;
+    let patrol_path = patrol_path(&lab_map)?;
+    Ok(Scene::new(lab_map)
+        .overlay("guard path", Color::YELLOW, patrol_path)
+        .overlay("guard start", Color::GREEN, [guard.position]))
 }
 
-impl Direction {
-    fn right_90_degrees(&mut self) {
-        match self {
-            Direction::Up => *self = Direction::Right,
-            Direction::Right => *self = Direction::Down,
-            Direction::Down => *self = Direction::Left,
-            Direction::Left => *self = Direction::Up
+pub fn animate(input: &str, recorder: &mut Recorder) -> anyhow::Result<()> {
+    //! Record the guard's patrol one step per tick, leaving a trail of the positions visited so far.
+    let lab_map = parse_lab_map(input)?;
+    let mut guard = Guard::try_new(&lab_map)?;
+    //The guard is drawn wherever they currently are, rather than where they started
+    let mut empty_lab_map = lab_map.clone();
+    empty_lab_map[guard.position] = '.';
+    let mut visit_history = HashSet::new();
+    visit_history.insert(guard.position);
+    let mut guard_loop_detector = HashSet::new();
+    for tick in 0.. {
+        if recorder.is_finished(tick) {
+            break;
+        }
+        recorder.record(tick, || {
+            let mut frame = empty_lab_map.clone();
+            frame[guard.position] = guard.direction.arrow();
+            Scene::new(frame)
+                .overlay("guard path", Color::YELLOW, visit_history.iter().copied())
+                .overlay("guard", Color::RED, [guard.position])
+        })?;
+        //A guard back in a position and direction they have already been in is patrolling a loop forever
+        if !guard_loop_detector.insert((guard.position, guard.direction)) || guard.patrol_lab(&lab_map).is_err() {
+            break;
+        }
+        visit_history.insert(guard.position);
+    }
+    Ok(())
+}
+
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! A `size` by `size` lab with an obstacle in about one in 20 positions, and a guard facing up somewhere
+    //! in it. Labs where the guard would patrol forever are thrown away, the guard must walk off the map.
+    loop {
+        let mut lab_map = Grid::filled(size, size, '.').map(|_| if rng.random_bool(0.05) { '#' } else { '.' });
+        lab_map[Point::new(rng.random_range(0..size as i64), rng.random_range(0..size as i64))] = '^';
+        if guard_walks_off(&lab_map) {
+            return lab_map.to_string();
+        }
+    }
+}
+
+fn guard_walks_off(lab_map: &Grid<char>) -> bool {
+    //! Whether the guard eventually walks off the map, rather than getting stuck in a loop.
+    let Ok(mut guard) = Guard::try_new(lab_map) else {
+        return false;
+    };
+    //A guard surrounded by obstacles would turn on the spot forever
+    if guard.position.neighbors4().all(|neighbor| lab_map.get(neighbor) == Some(&'#')) {
+        return false;
+    }
+    let mut guard_loop_detector = HashSet::new();
+    while guard_loop_detector.insert((guard.position, guard.direction)) {
+        if guard.patrol_lab(lab_map).is_err() {
+            return true;
         }
     }
+    false
 }
 
 impl SolveAdvent for Day6 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
+    fn parse(input: &str) -> anyhow::Result<()> {
+        parse_lab_map(input)?;
+        Ok(())
+    }
+
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
         //! Let the guard patrol the lab until they walk off the map.
         //! Count the unique spaces traversed.
-        let file_contents = read_input_file(path_to_file)?;
-        let lab_map = file_contents.lines().map(|row| row.chars().collect::<Vec<_>>()).collect::<Vec<_>>();
-        let mut guard = Guard::try_new(&lab_map)?;
-        let mut visit_history = HashSet::new();
-        visit_history.insert((guard.row, guard.col));
-        //When the guard patrol_lab call returns an error, the guard
-        // has walked off the map.
-        while guard.patrol_lab(&lab_map).is_ok() {
-            //Keep track of unique visits
-            visit_history.insert((guard.row, guard.col));
-        }
-        println!("Guard visited {:?} unique positions in the lab", visit_history.len());
-        Ok(())
+        let lab_map = parse_lab_map(input)?;
+        Ok(patrol_path(&lab_map)?.len().into())
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
         //! A brute-force simulation to find all single obstacles that can
         //! be placed to induce the guard to get stuck in an infinite loop. Not sure
         //! if there is a better way to do this.
-        let file_contents = read_input_file(path_to_file)?;
-        let mut lab_map = file_contents.lines().map(|row| row.chars().collect::<Vec<_>>()).collect::<Vec<_>>();
+        //! Each candidate obstacle is simulated independently, so in parallel mode every thread
+        //! places them in a copy of the map of its own.
+        let lab_map = parse_lab_map(input)?;
         let guard = Guard::try_new(&lab_map)?;
-        let mut successful_guard_loops = 0;
-        for row_number in 0..lab_map.len() {
-            for col_number in 0..lab_map[0].len() {
-                if lab_map[row_number][col_number] == '#' || lab_map[row_number][col_number] == '^' {
-                    //We are not allowed to place a new obstacle if there already is an obstacle
-                    //or this is the guards starting position
-                    continue;
-                }
-                //Temporarily change row,col to contain an obstacle.
-                lab_map[row_number][col_number] = '#';
-                let mut guard = guard.clone();
-                //An infinite loop occurs if the same row, col and direction are ever repeated.
-                //Because the guards progression are deterministic, this gurantees a infinite loop has been reached
-                let mut guard_loop_detector = HashSet::new();
-                guard_loop_detector.insert((guard.row, guard.col, guard.direction));
-                while guard.patrol_lab(&lab_map).is_ok() {
-                    if guard_loop_detector.contains(&(guard.row, guard.col, guard.direction)) {
-                        successful_guard_loops += 1;
-                        break;
-                    }
-                    guard_loop_detector.insert((guard.row, guard.col, guard.direction));
+        let candidates = lab_map.positions().collect::<Vec<_>>();
+        let guard_loops = parallel::map_init(&candidates, || lab_map.clone(), |lab_map, position| {
+            let position = *position;
+            if lab_map[position] == '#' || lab_map[position] == '^' {
+                //We are not allowed to place a new obstacle if there already is an obstacle
+                //or this is the guards starting position
+                return false;
+            }
+            //Temporarily change row,col to contain an obstacle.
+            lab_map[position] = '#';
+            let mut guard = guard.clone();
+            //An infinite loop occurs if the same row, col and direction are ever repeated.
+            //Because the guards progression are deterministic, this gurantees a infinite loop has been reached
+            let mut guard_loop_detector = HashSet::new();
+            guard_loop_detector.insert((guard.position, guard.direction));
+            let mut guard_loops = false;
+            while guard.patrol_lab(lab_map).is_ok() {
+                if guard_loop_detector.contains(&(guard.position, guard.direction)) {
+                    guard_loops = true;
+                    break;
                 }
-                //After each simulation we put the current spot back to being empty. 
-                lab_map[row_number][col_number] = '.';
+                guard_loop_detector.insert((guard.position, guard.direction));
             }
-        }
-        println!("There were {} unique obstacles that generate infinite guard loops", successful_guard_loops);
-        Ok(())
+            //After each simulation we put the current spot back to being empty. 
+            lab_map[position] = '.';
+            guard_loops
+        });
+        let successful_guard_loops = guard_loops.into_iter().filter(|guard_loops| *guard_loops).count();
+        Ok(successful_guard_loops.into())
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+    use crate::animate::AnimationOptions;
+
+    const EXAMPLE: &str = "\
+....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day6::solve_part1, EXAMPLE), Answer::Integer(41));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_example(Day6::solve_part2, EXAMPLE), Answer::Integer(6));
+    }
+
+    #[test]
+    fn visualization_highlights_the_patrol() {
+        let scene = visualize(EXAMPLE).unwrap();
+        assert_eq!(scene.legend(), vec![("guard path", Color::YELLOW, 41), ("guard start", Color::GREEN, 1)]);
+        assert_eq!(scene.highlight(Point::new(6, 4)), Some(Color::GREEN));
+        assert_eq!(scene.highlight(Point::new(0, 4)), None);
+    }
+
+    #[test]
+    fn animation_follows_the_guard() {
+        let options = AnimationOptions { cell_size: 1, ..AnimationOptions::default() };
+        let mut recorder = Recorder::in_memory(options);
+        animate(EXAMPLE, &mut recorder).unwrap();
+        //The patrol takes 44 steps (some of them revisiting positions) before the guard walks off the map
+        assert_eq!(recorder.finish().unwrap(), 45);
+        let options = AnimationOptions { first_tick: 3, last_tick: Some(5), cell_size: 1, ..AnimationOptions::default() };
+        let mut recorder = Recorder::in_memory(options);
+        animate(EXAMPLE, &mut recorder).unwrap();
+        let (tick, frame) = &recorder.frames()[0];
+        //3 steps up from the start at (6, 4)
+        assert_eq!((*tick, &frame.pixels[(3 * 10 + 4) * 3..(3 * 10 + 5) * 3]), (3, &[230, 57, 70][..]));
+        assert_eq!(recorder.frames().len(), 3);
+    }
+
+    #[test]
+    fn animating_a_looping_guard_stops() {
+        //The guard turns at each of the 4 obstacles, walking the square between them forever
+        let looping_lab = "\
+.#..
+.^.#
+#...
+..#.";
+        let options = AnimationOptions { cell_size: 1, ..AnimationOptions::default() };
+        let mut recorder = Recorder::in_memory(options);
+        animate(looping_lab, &mut recorder).unwrap();
+        //Once around the square, ending back where the guard started
+        assert_eq!(recorder.finish().unwrap(), 5);
+    }
+}
@@ -1,28 +1,34 @@
-use anyhow::{anyhow, Context};
+use rand::seq::IndexedRandom;
+use rand::RngExt;
 
-use super::{read_input_file, SolveAdvent};
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::input_error::{parse_lines, parse_number, InputError};
+use crate::parallel;
 
 
 pub struct Day7;
 
+const DAY: u8 = 7;
+
 ///A single equation line. 
 /// It is not guranteed that the `target` value
 /// can be constructed from the `constituents`
 #[derive(Debug, Clone)]
-struct Equation {
-    target: i64,
-    constituents: Vec<i64>
+pub struct Equation {
+    pub target: i64,
+    pub constituents: Vec<i64>
 }
 
 ///An operation to combine two numbers.
-enum AvailableOperators {
+pub enum AvailableOperators {
     Plus, 
     Multiply, 
     Concatenate
 }
 
 impl AvailableOperators {
-    fn apply(&self, num1: i64, num2: i64) -> i64 {
+    pub fn apply(&self, num1: i64, num2: i64) -> i64 {
         //! Apply the specified operation to the two numbers.
         match self {
             AvailableOperators::Plus => num1 + num2,
@@ -38,10 +44,10 @@ impl AvailableOperators {
 
 
 impl Equation {
-    fn try_new(line: &str) -> anyhow::Result<Equation> {
-        let [target, constituents]: [&str;2] = line.trim().split(':').collect::<Vec<_>>().try_into().map_err(|_err| anyhow!("Failed to coerce into length 2 array"))?;
-        let target = target.parse::<i64>().context("Target is not a valid integer")?;
-        let constituents: Result<Vec<_>, _> = constituents.trim().split(' ').map(|constituent| constituent.parse::<i64>()).collect();
+    pub fn try_new(line: &str) -> Result<Equation, InputError> {
+        let (target, constituents) = line.split_once(':').ok_or_else(|| InputError::at_end(DAY, line, "a `:` after the test value"))?;
+        let target = parse_number::<i64>(DAY, line, target)?;
+        let constituents: Result<Vec<_>, _> = constituents.trim().split(' ').map(|constituent| parse_number::<i64>(DAY, line, constituent)).collect();
         let constituents = constituents?;
         Ok(Equation {
             target, 
@@ -50,7 +56,7 @@ impl Equation {
     }
 
 
-    fn is_possible(&self, available_operations: &[AvailableOperators]) -> bool {
+    pub fn is_possible(&self, available_operations: &[AvailableOperators]) -> bool {
         //! Determine if the `target` sum can be generated by some combination of the `constituents`
         //! using the `available_operations`.
         let mut scratchpad = Vec::new();
@@ -72,36 +78,86 @@ impl Equation {
     }
 }
 
-impl SolveAdvent for Day7 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
-        let file_contents = read_input_file(path_to_file)?;
-        let equations: Result<Vec<_>, _> = file_contents.lines().map(Equation::try_new).collect();
-        let equations = equations.context("Could not parse all lines into equations")?;
-        let mut test_value_sum = 0;
-        let available_operations = [AvailableOperators::Plus, AvailableOperators::Multiply];
-        for equation in equations.iter() {
-            if equation.is_possible(&available_operations) {
-                test_value_sum += equation.target;
-            }
+pub fn test_value_sum(equations: &[Equation], available_operations: &[AvailableOperators]) -> i64 {
+    //! The sum of the test values of the `equations` that are possible. Each equation is independent of the others,
+    //! so in parallel mode they are checked at the same time.
+    let possible_test_values = parallel::map(equations, |equation| {
+        if equation.is_possible(available_operations) { equation.target } else { 0 }
+    });
+    possible_test_values.into_iter().sum()
+}
+
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! `size` equations of 2 to 6 numbers of up to 3 digits, few enough that any way of combining them fits
+    //! in an `i64`. Most test values can be made by some choice of operators, the rest are a little off.
+    let operators = [AvailableOperators::Plus, AvailableOperators::Multiply, AvailableOperators::Concatenate];
+    let mut equations = Vec::with_capacity(size);
+    for _ in 0..size {
+        let mut constituents = Vec::new();
+        for _ in 0..rng.random_range(2..=6) {
+            let digits = rng.random_range(1..=3);
+            constituents.push(rng.random_range(1..10i64.pow(digits)));
+        }
+        let mut target = constituents[0];
+        for constituent in constituents[1..].iter() {
+            target = operators.choose(rng).unwrap().apply(target, *constituent);
         }
-        println!("Total of all possible equations for part1 is {:?}", test_value_sum);
+        if rng.random_bool(0.3) {
+            target += rng.random_range(1..10);
+        }
+        let constituents = constituents.iter().map(|constituent| constituent.to_string()).collect::<Vec<_>>();
+        equations.push(format!("{}: {}", target, constituents.join(" ")));
+    }
+    equations.join("\n")
+}
+
+impl SolveAdvent for Day7 {
+    fn parse(input: &str) -> anyhow::Result<()> {
+        parse_lines(input, Equation::try_new)?;
         Ok(())
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
+        let equations = parse_lines(input, Equation::try_new)?;
+        let available_operations = [AvailableOperators::Plus, AvailableOperators::Multiply];
+        let test_value_sum = test_value_sum(&equations, &available_operations);
+        Ok(test_value_sum.into())
+    }
+
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
         //! Exactly the same code as part1 solution but the `available_operations` array
         //! contains the new concatenation operation (`||`) as well as the `+`/`*` ops.
-        let file_contents = read_input_file(path_to_file)?;
-        let equations: Result<Vec<_>, _> = file_contents.lines().map( Equation::try_new).collect();
-        let equations = equations.context("Could not parse all lines into equations")?;
-        let mut test_value_sum = 0;
+        let equations = parse_lines(input, Equation::try_new)?;
         let available_operations = [AvailableOperators::Plus, AvailableOperators::Multiply, AvailableOperators::Concatenate];
-        for equation in equations.iter() {
-            if equation.is_possible(&available_operations) {
-                test_value_sum += equation.target;
-            }
-        }
-        println!("Total of all possible equations for part2 is {:?}", test_value_sum);
-        Ok(())
+        let test_value_sum = test_value_sum(&equations, &available_operations);
+        Ok(test_value_sum.into())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const EXAMPLE: &str = "\
+190: 10 19
+3267: 81 40 27
+83: 17 5
+156: 15 6
+7290: 6 8 6 15
+161011: 16 10 13
+192: 17 8 14
+21037: 9 7 18 13
+292: 11 6 16 20
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day7::solve_part1, EXAMPLE), Answer::Integer(3749));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_example(Day7::solve_part2, EXAMPLE), Answer::Integer(11387));
+    }
+}
@@ -1,13 +1,17 @@
-use anyhow::anyhow;
+use rand::RngExt;
 
-use super::{read_input_file, SolveAdvent};
+use super::{Answer, SolveAdvent};
+use crate::generate::InputRng;
+use crate::input_error::InputError;
 
 pub struct Day9;
 
+const DAY: u8 = 9;
+
 ///Represents the state of a single block of memory, 
 /// which can either be free or occupied by file with id `file_id`.
 #[derive(Debug, Clone)]
-enum MemoryBlock {
+pub enum MemoryBlock {
     Free,
     Occupied{file_id: usize}
 }
@@ -61,18 +65,22 @@ impl FileBlock {
 }
 
 impl MemoryBlock {
-    fn construct_memory_blocks(file_contents: &str) -> anyhow::Result<Vec<MemoryBlock>> {
+    pub fn construct_memory_blocks(file_contents: &str) -> anyhow::Result<Vec<MemoryBlock>> {
         //! Construct memory blocks as described in the problem.
         //! 
         //! Input `2333133121414131402` -> `00...111...2...333.44.5555.6666.777.888899`,
         //! where `MemoryBlock::Free` is the `.` and the numbers represents the file_id of an occupied memory block.
         //! The input numbers alternate between number of free spaces and number of spaces occupied by a file block.
+        if file_contents.is_empty() {
+            return Err(InputError::at_end(DAY, file_contents, "a disk map").into());
+        }
         let mut memory_blocks = Vec::new();
         let mut latest_file_number = 0;
-        for (position, space_size) in file_contents.chars().enumerate()
+        for (position, (offset, space_size)) in file_contents.char_indices().enumerate()
         {
             //The space_size number represents how many consecutive memory blocks are either free or occupied by the file
-            let space_size = space_size.to_digit(10).ok_or(anyhow!("Could not convert available space to a number"))?;
+            let space_size = space_size.to_digit(10)
+                .ok_or_else(|| InputError::at(DAY, file_contents, &file_contents[offset..offset + space_size.len_utf8()], "a digit"))?;
             if position % 2 == 0 {
                 for _ in 0..space_size {
                     memory_blocks.push(MemoryBlock::Occupied { file_id: latest_file_number});
@@ -88,7 +96,7 @@ impl MemoryBlock {
         Ok(memory_blocks)
     }
 
-    fn compactify_memory_blocks_without_fragmentation(memory_blocks: &mut [MemoryBlock]) {
+    pub fn compactify_memory_blocks_without_fragmentation(memory_blocks: &mut [MemoryBlock]) {
         //! Without fragmentation, we can only swap memory if the entire file block can fit into the memory slot.
         let mut file_blocks = FileBlock::from_memory_block(memory_blocks);
         //The `file_blocks` vec is already sorted in ascending order of `file_id`, so taking from the back will satisfy the requirement 
@@ -110,10 +118,11 @@ impl MemoryBlock {
         }
     }
 
-    fn compactify_memory_blocks_with_fragmentation(memory_blocks: &mut [MemoryBlock]) -> Option<()> {
+    pub fn compactify_memory_blocks_with_fragmentation(memory_blocks: &mut [MemoryBlock]) -> Option<()> {
         //! Swap individual memory blocks with fragmentation. 
         let mut lower_bound = 0;
-        let mut upper_bound = memory_blocks.len() - 1;
+        //A disk map of only empty files has no blocks at all
+        let mut upper_bound = memory_blocks.len().checked_sub(1)?;
         loop {
             let next_available_space  = seek_next_free_memory_block(memory_blocks, lower_bound)?;
             let next_occupied_space = seek_next_occupied_memory_block(memory_blocks, upper_bound)?;
@@ -173,7 +182,7 @@ fn seek_next_occupied_memory_block(memory_blocks: &[MemoryBlock], upper_bound: u
     None
 }
 
-fn get_checksum(memory_blocks: &[MemoryBlock]) -> usize {
+pub fn get_checksum(memory_blocks: &[MemoryBlock]) -> usize {
     let mut checksum = 0;
     for (position, memory_block) in memory_blocks.iter().enumerate() {
         if let MemoryBlock::Occupied { file_id } = memory_block {
@@ -183,24 +192,57 @@ fn get_checksum(memory_blocks: &[MemoryBlock]) -> usize {
     checksum
 }
 
+pub fn generate(size: usize, rng: &mut InputRng) -> String {
+    //! A disk map `size` digits long, alternating between files of 1 to 9 blocks and 0 to 9 free blocks.
+    (0..size).map(|position| {
+        let smallest = if position % 2 == 0 { 1 } else { 0 };
+        char::from_digit(rng.random_range(smallest..=9), 10).unwrap()
+    }).collect()
+}
+
 impl SolveAdvent for Day9 {
-    fn solve_part1(path_to_file: &str) -> anyhow::Result<()> {
+    fn parse(input: &str) -> anyhow::Result<()> {
+        MemoryBlock::construct_memory_blocks(input)?;
+        Ok(())
+    }
+
+    fn solve_part1(input: &str) -> anyhow::Result<Answer> {
         //! Swap memory with file fragmentation allowed.
-        let file_contents = read_input_file(path_to_file)?;
-        let mut memory_blocks = MemoryBlock::construct_memory_blocks(&file_contents)?;
+        let mut memory_blocks = MemoryBlock::construct_memory_blocks(input)?;
         MemoryBlock::compactify_memory_blocks_with_fragmentation(&mut memory_blocks);
         let checksum = get_checksum(&memory_blocks);
-        println!("Final checksum after memory compaction is {}", checksum);
-        Ok(())
+        Ok(checksum.into())
     }
 
-    fn solve_part2(path_to_file: &str) -> anyhow::Result<()> {
+    fn solve_part2(input: &str) -> anyhow::Result<Answer> {
         //! Swap memory with file fragmentation not allowed, only whole files are moved
-        let file_contents = read_input_file(path_to_file)?;
-        let mut memory_blocks = MemoryBlock::construct_memory_blocks(&file_contents)?;
+        let mut memory_blocks = MemoryBlock::construct_memory_blocks(input)?;
         MemoryBlock::compactify_memory_blocks_without_fragmentation(&mut memory_blocks);
         let checksum = get_checksum(&memory_blocks);
-        println!("Final checksum after memory compaction is {}", checksum);
-        Ok(())
+        Ok(checksum.into())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::solve_example;
+
+    const EXAMPLE: &str = "2333133121414131402";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_example(Day9::solve_part1, EXAMPLE), Answer::Integer(1928));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_example(Day9::solve_part2, EXAMPLE), Answer::Integer(2858));
+    }
+
+    #[test]
+    fn empty_disk_maps() {
+        assert!(Day9::solve_part1("").is_err());
+        assert_eq!(solve_example(Day9::solve_part1, "0"), Answer::Integer(0));
+    }
+}
@@ -0,0 +1,136 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+///Bytes currently allocated through the `TrackingAllocator`
+static CURRENT_BYTES: AtomicUsize = AtomicUsize::new(0);
+///The high-water mark of `CURRENT_BYTES` since the process started
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+///How many `PeakWindow`s can be open at once before new ones fall back to `PEAK_BYTES`
+const WINDOW_SLOTS: usize = 64;
+///The high-water mark of `CURRENT_BYTES` since each window slot was opened
+static WINDOW_PEAKS: [AtomicUsize; WINDOW_SLOTS] = [const { AtomicUsize::new(0) }; WINDOW_SLOTS];
+///A bit per window slot that some `PeakWindow` holds
+static CLAIMED_WINDOWS: AtomicU64 = AtomicU64::new(0);
+///A bit per window slot whose peak is being kept up to date
+static OPEN_WINDOWS: AtomicU64 = AtomicU64::new(0);
+
+///A thin wrapper around the system allocator that keeps
+/// track of how many bytes are allocated, so that the peak allocation
+/// of each solution can be reported. The counters are process wide, so
+/// anything else allocating at the same time is counted as well.
+pub struct TrackingAllocator;
+
+fn record_allocation(size: usize) {
+    let current_bytes = CURRENT_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK_BYTES.fetch_max(current_bytes, Ordering::Relaxed);
+    let mut open_windows = OPEN_WINDOWS.load(Ordering::Acquire);
+    while open_windows != 0 {
+        WINDOW_PEAKS[open_windows.trailing_zeros() as usize].fetch_max(current_bytes, Ordering::Relaxed);
+        //Clear the lowest set bit
+        open_windows &= open_windows - 1;
+    }
+}
+
+fn record_deallocation(size: usize) {
+    CURRENT_BYTES.fetch_sub(size, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record_deallocation(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                record_allocation(new_size - layout.size());
+            } else {
+                record_deallocation(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+///Measures the most bytes allocated at once between `open` and `peak`. Each window keeps its
+/// own high-water mark, so opening one never disturbs the others, but the allocations are still
+/// counted process wide: anything running while the window is open adds to its peak.
+pub struct PeakWindow {
+    slot: Option<usize>,
+    baseline: usize,
+}
+
+impl PeakWindow {
+    pub fn open() -> Self {
+        //! Start measuring from the bytes allocated right now. Should all the slots be taken, the window
+        //! falls back to the peak since the process started, which can only overstate its peak.
+        let baseline = CURRENT_BYTES.load(Ordering::Relaxed);
+        let claimed = CLAIMED_WINDOWS.fetch_update(Ordering::AcqRel, Ordering::Acquire, |claimed| {
+            (claimed != u64::MAX).then(|| claimed | 1 << (!claimed).trailing_zeros())
+        });
+        let slot = claimed.ok().map(|claimed| (!claimed).trailing_zeros() as usize);
+        if let Some(slot) = slot {
+            WINDOW_PEAKS[slot].store(baseline, Ordering::Relaxed);
+            OPEN_WINDOWS.fetch_or(1 << slot, Ordering::AcqRel);
+        }
+        PeakWindow { slot, baseline }
+    }
+
+    pub fn peak(&self) -> usize {
+        //! The most bytes allocated at once, above those allocated when the window was opened.
+        let peak = match self.slot {
+            Some(slot) => &WINDOW_PEAKS[slot],
+            None => &PEAK_BYTES,
+        };
+        peak.load(Ordering::Relaxed).saturating_sub(self.baseline)
+    }
+}
+
+impl Drop for PeakWindow {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot {
+            OPEN_WINDOWS.fetch_and(!(1 << slot), Ordering::AcqRel);
+            CLAIMED_WINDOWS.fetch_and(!(1 << slot), Ordering::AcqRel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_keep_their_own_peaks() {
+        //The tests do not run under the `TrackingAllocator`, so only these calls move the counters
+        let first = PeakWindow::open();
+        record_allocation(100);
+        record_deallocation(100);
+        let second = PeakWindow::open();
+        record_allocation(40);
+        assert_eq!((first.peak(), second.peak()), (100, 40));
+        drop(first);
+        let third = PeakWindow::open();
+        record_allocation(10);
+        record_deallocation(50);
+        assert_eq!((second.peak(), third.peak()), (50, 10));
+    }
+}
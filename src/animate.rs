@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use crate::visualize::{Image, Scene};
+
+///How much of a simulation to record, and how to play it back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationOptions {
+    ///Playback speed of an animated GIF. A directory of frames has no speed of its own.
+    pub frames_per_second: u16,
+    ///The first tick to record, where tick 0 is the state before anything has moved
+    pub first_tick: usize,
+    ///The last tick to record (inclusive), or `None` to record until the simulation ends
+    pub last_tick: Option<usize>,
+    ///The width and height in pixels of every map cell
+    pub cell_size: usize,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        AnimationOptions {
+            frames_per_second: 10,
+            first_tick: 0,
+            last_tick: None,
+            cell_size: 4,
+        }
+    }
+}
+
+///Where recorded frames go.
+enum FrameSink {
+    ///An animated GIF, whose encoder is created once the first frame gives the size of the image
+    Gif {
+        path: PathBuf,
+        encoder: Option<gif::Encoder<BufWriter<File>>>,
+    },
+    ///A directory of numbered PNG images, one per frame
+    Directory(PathBuf),
+    ///Kept in memory, along with the tick each frame was recorded at
+    Memory(Vec<(usize, Image)>),
+}
+
+///Records one frame per tick of a time-stepped simulation, within the tick range of its `AnimationOptions`.
+/// Frames are written out as they are recorded, so long simulations do not have to fit in memory.
+pub struct Recorder {
+    options: AnimationOptions,
+    sink: FrameSink,
+    frames_recorded: usize,
+}
+
+impl Recorder {
+    pub fn to_path(path: &Path, options: AnimationOptions) -> anyhow::Result<Self> {
+        //! Record to an animated GIF if `path` ends in `.gif`, or else into the directory `path` (created if needed)
+        //! as `frame_00000.png`, `frame_00001.png` and so on.
+        anyhow::ensure!(options.cell_size > 0, "The cell size must be at least 1 pixel");
+        anyhow::ensure!((1..=100).contains(&options.frames_per_second), "GIFs play at between 1 and 100 frames per second");
+        let is_gif = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("gif"));
+        let sink = if is_gif {
+            FrameSink::Gif { path: path.to_path_buf(), encoder: None }
+        } else {
+            fs::create_dir_all(path).with_context(|| format!("Could not create the frame directory {}", path.display()))?;
+            FrameSink::Directory(path.to_path_buf())
+        };
+        Ok(Recorder { options, sink, frames_recorded: 0 })
+    }
+
+    pub fn in_memory(options: AnimationOptions) -> Self {
+        //! Keep the frames in memory rather than writing them anywhere, see `frames`.
+        Recorder {
+            options,
+            sink: FrameSink::Memory(Vec::new()),
+            frames_recorded: 0,
+        }
+    }
+
+    pub fn options(&self) -> &AnimationOptions {
+        &self.options
+    }
+
+    pub fn is_recording(&self, tick: usize) -> bool {
+        //! Whether `tick` is within the range being recorded.
+        tick >= self.options.first_tick && self.options.last_tick.is_none_or(|last_tick| tick <= last_tick)
+    }
+
+    pub fn is_finished(&self, tick: usize) -> bool {
+        //! Whether `tick` is past the range being recorded, so the simulation can stop.
+        self.options.last_tick.is_some_and(|last_tick| tick > last_tick)
+    }
+
+    pub fn record(&mut self, tick: usize, draw: impl FnOnce() -> Scene) -> anyhow::Result<()> {
+        //! Record the frame for `tick`, if it is in range. The scene is only drawn when it is needed.
+        if !self.is_recording(tick) {
+            return Ok(());
+        }
+        let image = draw().render(self.options.cell_size);
+        match &mut self.sink {
+            FrameSink::Gif { path, encoder } => {
+                if encoder.is_none() {
+                    *encoder = Some(start_gif(path, &image)?);
+                }
+                let frame_delay = 100 / self.options.frames_per_second;
+                encoder.as_mut().unwrap().write_frame(&gif_frame(&image, frame_delay)?)?;
+            }
+            FrameSink::Directory(directory) => {
+                image.save(&directory.join(format!("frame_{:05}.png", self.frames_recorded)))?;
+            }
+            FrameSink::Memory(frames) => frames.push((tick, image)),
+        }
+        self.frames_recorded += 1;
+        Ok(())
+    }
+
+    pub fn frames(&self) -> &[(usize, Image)] {
+        //! The frames recorded so far by an `in_memory` recorder (and nothing for any other).
+        match &self.sink {
+            FrameSink::Memory(frames) => frames,
+            _ => &[],
+        }
+    }
+
+    pub fn finish(self) -> anyhow::Result<usize> {
+        //! Finish writing the animation, returning how many frames were recorded. It is an error if the simulation
+        //! ended before the first tick to record, as there is no animation to show (and no GIF is ever created).
+        anyhow::ensure!(
+            self.frames_recorded > 0,
+            "No frames were recorded, the simulation ended before tick {}",
+            self.options.first_tick
+        );
+        if let FrameSink::Gif { encoder: Some(encoder), .. } = self.sink {
+            encoder.into_inner()?.flush()?;
+        }
+        Ok(self.frames_recorded)
+    }
+}
+
+fn start_gif(path: &Path, first_frame: &Image) -> anyhow::Result<gif::Encoder<BufWriter<File>>> {
+    //! Create the GIF at `path`, sized to fit `first_frame`, looping forever.
+    let (width, height) = gif_dimensions(first_frame)?;
+    let file = File::create(path).with_context(|| format!("Could not create {}", path.display()))?;
+    let mut encoder = gif::Encoder::new(BufWriter::new(file), width, height, &[])?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+    Ok(encoder)
+}
+
+fn gif_dimensions(image: &Image) -> anyhow::Result<(u16, u16)> {
+    let width = u16::try_from(image.width).ok();
+    let height = u16::try_from(image.height).ok();
+    width.zip(height).ok_or_else(|| anyhow::anyhow!("A {}x{} frame is too large for a GIF, use a smaller cell size", image.width, image.height))
+}
+
+fn gif_frame(image: &Image, delay: u16) -> anyhow::Result<gif::Frame<'static>> {
+    //! Convert `image` to a GIF frame. Map drawings rarely use more than a handful of colors, so they are
+    //! indexed exactly, only falling back to (slower, lossy) quantization when there are more than 256.
+    let (width, height) = gif_dimensions(image)?;
+    let mut palette = Vec::new();
+    let mut color_indexes: HashMap<&[u8], u8> = HashMap::new();
+    let mut indexed_pixels = Vec::with_capacity(image.width * image.height);
+    for pixel in image.pixels.chunks_exact(3) {
+        let next_index = color_indexes.len();
+        let index = match color_indexes.get(pixel) {
+            Some(index) => *index,
+            None if next_index < 256 => {
+                color_indexes.insert(pixel, next_index as u8);
+                palette.extend_from_slice(pixel);
+                next_index as u8
+            }
+            None => {
+                let mut frame = gif::Frame::from_rgb_speed(width, height, &image.pixels, 10);
+                frame.delay = delay;
+                return Ok(frame);
+            }
+        };
+        indexed_pixels.push(index);
+    }
+    let mut frame = gif::Frame::from_palette_pixels(width, height, indexed_pixels, palette, None);
+    frame.delay = delay;
+    Ok(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Point;
+    use crate::grid::Grid;
+    use crate::visualize::Color;
+
+    fn moving_dot(recorder: &mut Recorder) -> anyhow::Result<()> {
+        //A dot moving right along a 1x5 strip, one cell per tick
+        for tick in 0..5 {
+            if recorder.is_finished(tick) {
+                break;
+            }
+            recorder.record(tick, || {
+                Scene::new(Grid::filled(1, 5, '.')).overlay("dot", Color::RED, [Point::new(0, tick as i64)])
+            })?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn only_the_tick_range_is_recorded() {
+        let options = AnimationOptions { first_tick: 1, last_tick: Some(3), cell_size: 1, ..AnimationOptions::default() };
+        let mut recorder = Recorder::in_memory(options);
+        moving_dot(&mut recorder).unwrap();
+        let ticks = recorder.frames().iter().map(|(tick, _)| *tick).collect::<Vec<_>>();
+        assert_eq!(ticks, vec![1, 2, 3]);
+        //The dot is in the second pixel of the first frame
+        assert_eq!(&recorder.frames()[0].1.pixels[3..6], &[230, 57, 70]);
+        assert_eq!(recorder.finish().unwrap(), 3);
+    }
+
+    #[test]
+    fn recording_nothing_is_an_error() {
+        let options = AnimationOptions { first_tick: 10, ..AnimationOptions::default() };
+        let mut recorder = Recorder::in_memory(options);
+        moving_dot(&mut recorder).unwrap();
+        let error = recorder.finish().unwrap_err();
+        assert_eq!(error.to_string(), "No frames were recorded, the simulation ended before tick 10");
+    }
+
+    #[test]
+    fn frames_are_written_as_a_gif_or_numbered_images() {
+        let directory = std::env::temp_dir().join(format!("aoc-animate-test-{}", std::process::id()));
+        let gif_path = directory.join("dot.gif");
+        fs::create_dir_all(&directory).unwrap();
+        let mut recorder = Recorder::to_path(&gif_path, AnimationOptions::default()).unwrap();
+        moving_dot(&mut recorder).unwrap();
+        assert_eq!(recorder.finish().unwrap(), 5);
+        let mut decoder = gif::DecodeOptions::new().read_info(File::open(&gif_path).unwrap()).unwrap();
+        //5 cells of 4 pixels wide, 1 cell tall
+        assert_eq!((decoder.width(), decoder.height()), (20, 4));
+        let mut delays = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            delays.push(frame.delay);
+        }
+        assert_eq!(delays, vec![10; 5]);
+
+        let frame_directory = directory.join("frames");
+        let mut recorder = Recorder::to_path(&frame_directory, AnimationOptions { last_tick: Some(1), ..AnimationOptions::default() }).unwrap();
+        moving_dot(&mut recorder).unwrap();
+        recorder.finish().unwrap();
+        let mut frames = fs::read_dir(&frame_directory).unwrap().map(|entry| entry.unwrap().file_name()).collect::<Vec<_>>();
+        frames.sort();
+        assert_eq!(frames, vec!["frame_00000.png", "frame_00001.png"]);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
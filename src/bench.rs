@@ -0,0 +1,299 @@
+use std::fmt::Display;
+use std::fs;
+use std::hint::black_box;
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::advent_solutions::{DayEntry, ParseFn, SolveFn};
+use crate::runner::{format_duration, SOLUTION_STACK_SIZE};
+
+///A separately timed phase of a day's solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Phase {
+    ///Parsing the input on its own
+    Parse,
+    ///Solving part 1, less the time parsing takes (unless the day has no parse phase)
+    Part1,
+    ///Solving part 2, less the time parsing takes (unless the day has no parse phase)
+    Part2,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 3] = [Phase::Parse, Phase::Part1, Phase::Part2];
+
+    pub fn part(&self) -> Option<u8> {
+        //! The part this phase solves, `None` for parsing.
+        match self {
+            Phase::Parse => None,
+            Phase::Part1 => Some(1),
+            Phase::Part2 => Some(2),
+        }
+    }
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::Parse => write!(f, "parse"),
+            Phase::Part1 => write!(f, "part1"),
+            Phase::Part2 => write!(f, "part2"),
+        }
+    }
+}
+
+///Summary statistics of the time a phase took over several iterations, in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BenchStats {
+    pub iterations: usize,
+    pub mean: f64,
+    pub median: f64,
+    ///The sample standard deviation, 0 for a single iteration
+    pub stddev: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl BenchStats {
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        //! The statistics of `samples`, or `None` if there are none.
+        if samples.is_empty() {
+            return None;
+        }
+        let mut seconds = samples.iter().map(Duration::as_secs_f64).collect::<Vec<_>>();
+        seconds.sort_by(f64::total_cmp);
+        let count = seconds.len();
+        let mean = seconds.iter().sum::<f64>() / count as f64;
+        let median = if count % 2 == 0 {
+            (seconds[count / 2 - 1] + seconds[count / 2]) / 2.0
+        } else {
+            seconds[count / 2]
+        };
+        let variance = if count > 1 {
+            seconds.iter().map(|second| (second - mean).powi(2)).sum::<f64>() / (count - 1) as f64
+        } else {
+            0.0
+        };
+        Some(BenchStats {
+            iterations: count,
+            mean,
+            median,
+            stddev: variance.sqrt(),
+            min: seconds[0],
+            max: seconds[count - 1],
+        })
+    }
+}
+
+///The timing of one phase of one day. A list of these saved as JSON is a baseline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchRecord {
+    pub day: u8,
+    pub phase: Phase,
+    #[serde(flatten)]
+    pub stats: BenchStats,
+}
+
+pub fn bench_phase(day_entry: &DayEntry, phase: Phase, input: &str, warmup: usize, iterations: usize, timeout: Duration) -> anyhow::Result<Option<BenchStats>> {
+    //! Time `phase` of the day against the puzzle `input` text for `iterations` runs, after `warmup` untimed runs.
+    //! `None` if the day has no such phase (such as an unsolved part). Any error, or a run taking longer than `timeout`,
+    //! stops the benchmark. Just like with the runner, a timed out run cannot be killed, so its thread is left running
+    //! in the background until the process exits.
+    let solve_fn = match phase.part() {
+        None if day_entry.parse.is_none() => return Ok(None),
+        None => None,
+        Some(part) => match day_entry.part(part) {
+            Some(solve_fn) => Some(solve_fn),
+            None => return Ok(None),
+        },
+    };
+    let (parse_fn, runs, input) = (day_entry.parse, warmup + iterations, input.to_string());
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .name(format!("day{}-{}", day_entry.day, phase))
+        .stack_size(SOLUTION_STACK_SIZE)
+        .spawn(move || {
+            for _ in 0..runs {
+                let sample = time_phase(parse_fn, solve_fn, &input);
+                let failed = sample.is_err();
+                //The receiver is gone if a run timed out, in which case nobody cares about the rest
+                if sender.send(sample).is_err() || failed {
+                    break;
+                }
+            }
+        })
+        .context("Failed to spawn the benchmark thread")?;
+    let mut samples = Vec::with_capacity(iterations);
+    for iteration in 0..runs {
+        let sample = match receiver.recv_timeout(timeout) {
+            Ok(sample) => sample.with_context(|| format!("Day {} {} failed", day_entry.day, phase))?,
+            Err(RecvTimeoutError::Timeout) => anyhow::bail!("Day {} {} did not finish within {}", day_entry.day, phase, format_duration(timeout)),
+            Err(RecvTimeoutError::Disconnected) => anyhow::bail!("Day {} {} panicked", day_entry.day, phase),
+        };
+        if iteration >= warmup {
+            samples.push(sample);
+        }
+    }
+    Ok(BenchStats::from_samples(&samples))
+}
+
+fn time_phase(parse_fn: Option<ParseFn>, solve_fn: Option<SolveFn>, input: &str) -> anyhow::Result<Duration> {
+    //! Time a single run of parsing, or of solving when there is a `solve_fn`. The parts parse their own input, so
+    //! the input is parsed on its own first and that time is taken off the time solving took, leaving just the solving.
+    let parse_time = match parse_fn {
+        Some(parse_fn) => {
+            let start = Instant::now();
+            parse_fn(black_box(input))?;
+            start.elapsed()
+        }
+        None => Duration::ZERO,
+    };
+    let Some(solve_fn) = solve_fn else {
+        return Ok(parse_time);
+    };
+    let start = Instant::now();
+    //Only the time matters, but the answer must not be optimized away
+    black_box(solve_fn(black_box(input))?);
+    Ok(start.elapsed().saturating_sub(parse_time))
+}
+
+pub fn save_baseline(path: &Path, records: &[BenchRecord]) -> anyhow::Result<()> {
+    //! Save `records` as a JSON baseline that later runs can be compared against.
+    let json = serde_json::to_string_pretty(records)?;
+    fs::write(path, json + "\n").with_context(|| format!("Could not write the baseline {}", path.display()))
+}
+
+pub fn load_baseline(path: &Path) -> anyhow::Result<Vec<BenchRecord>> {
+    let json = fs::read_to_string(path).with_context(|| format!("Could not read the baseline {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("{} is not a benchmark baseline", path.display()))
+}
+
+///How the median time of a phase changed from its baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+    pub day: u8,
+    pub phase: Phase,
+    pub baseline_median: f64,
+    pub median: f64,
+}
+
+impl Comparison {
+    pub fn change(&self) -> Option<f64> {
+        //! The relative change in the median, so `0.25` is 25% slower and `-0.5` twice as fast. `None` when the
+        //! baseline median is 0 (too quick to measure), as no change is relative to that.
+        (self.baseline_median > 0.0).then(|| (self.median - self.baseline_median) / self.baseline_median)
+    }
+
+    pub fn is_regression(&self, threshold: f64) -> bool {
+        //! Whether the phase got slower by more than `threshold` (a fraction, like `change`).
+        self.change().is_some_and(|change| change > threshold)
+    }
+}
+
+pub fn compare(records: &[BenchRecord], baseline: &[BenchRecord]) -> Vec<Comparison> {
+    //! Compare every record with its day and phase in the `baseline`, skipping any the baseline does not have.
+    //! Medians are compared, as they are less thrown off by the odd slow iteration than means.
+    records.iter().filter_map(|record| {
+        let baseline_record = baseline.iter().find(|baseline_record| (baseline_record.day, baseline_record.phase) == (record.day, record.phase))?;
+        Some(Comparison {
+            day: record.day,
+            phase: record.phase,
+            baseline_median: baseline_record.stats.median,
+            median: record.stats.median,
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::find_day;
+
+    #[test]
+    fn statistics_of_the_samples() {
+        let samples = [4, 1, 3, 2].map(Duration::from_secs);
+        let stats = BenchStats::from_samples(&samples).unwrap();
+        assert_eq!((stats.iterations, stats.mean, stats.median, stats.min, stats.max), (4, 2.5, 2.5, 1.0, 4.0));
+        assert!((stats.stddev - (5.0f64 / 3.0).sqrt()).abs() < 1e-9);
+        assert_eq!(BenchStats::from_samples(&samples[..1]).unwrap().stddev, 0.0);
+        assert_eq!(BenchStats::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn phases_are_timed_separately() {
+        let example = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3";
+        let stats = bench_phase(find_day(1).unwrap(), Phase::Parse, example, 1, 3, Duration::MAX).unwrap().unwrap();
+        assert_eq!(stats.iterations, 3);
+        assert!(stats.min <= stats.median && stats.median <= stats.max);
+        //Day 3 parses as it solves, and Day 25 has no part 2
+        assert_eq!(bench_phase(find_day(3).unwrap(), Phase::Parse, example, 0, 1, Duration::MAX).unwrap(), None);
+        assert_eq!(bench_phase(find_day(25).unwrap(), Phase::Part2, example, 0, 1, Duration::MAX).unwrap(), None);
+        let error = bench_phase(find_day(1).unwrap(), Phase::Part1, "3 x", 0, 1, Duration::MAX).unwrap_err();
+        assert_eq!(error.to_string(), "Day 1 part1 failed");
+    }
+
+    #[test]
+    fn parts_are_timed_without_their_parsing() {
+        //Parsing takes 50ms and solving parses first, then takes 1ms more
+        fn slow_parse(_input: &str) -> anyhow::Result<()> {
+            std::thread::sleep(Duration::from_millis(50));
+            Ok(())
+        }
+        let day_entry = DayEntry {
+            day: 1,
+            title: "Slow parsing",
+            parse: Some(slow_parse),
+            part1: Some(|input| {
+                slow_parse(input)?;
+                std::thread::sleep(Duration::from_millis(1));
+                Ok(1.into())
+            }),
+            part2: None,
+        };
+        let parse = bench_phase(&day_entry, Phase::Parse, "", 0, 1, Duration::MAX).unwrap().unwrap();
+        let part1 = bench_phase(&day_entry, Phase::Part1, "", 0, 1, Duration::MAX).unwrap().unwrap();
+        assert!(parse.median >= 0.05);
+        assert!(part1.median < 0.025);
+    }
+
+    #[test]
+    fn runs_that_take_too_long_time_out() {
+        let day_entry = DayEntry {
+            day: 1,
+            title: "Never finishes",
+            parse: None,
+            part1: Some(|_| loop {
+                std::thread::sleep(Duration::from_secs(1));
+            }),
+            part2: None,
+        };
+        let error = bench_phase(&day_entry, Phase::Part1, "", 0, 1, Duration::from_millis(10)).unwrap_err();
+        assert_eq!(error.to_string(), "Day 1 part1 did not finish within 10.00 ms");
+    }
+
+    #[test]
+    fn regressions_beyond_the_threshold_are_flagged() {
+        let record = |day, median| BenchRecord {
+            day,
+            phase: Phase::Part1,
+            stats: BenchStats { iterations: 1, mean: median, median, stddev: 0.0, min: median, max: median },
+        };
+        let baseline = vec![record(1, 1.0), record(2, 1.0), record(4, 0.0)];
+        let json = serde_json::to_string(&baseline).unwrap();
+        assert!(json.starts_with(r#"[{"day":1,"phase":"part1","iterations":1,"#));
+        assert_eq!(serde_json::from_str::<Vec<BenchRecord>>(&json).unwrap(), baseline);
+        let comparisons = compare(&[record(1, 1.05), record(2, 1.5), record(3, 9.0), record(4, 0.5)], &baseline);
+        assert_eq!(comparisons.len(), 3);
+        assert!(!comparisons[0].is_regression(0.1));
+        assert!(comparisons[1].is_regression(0.1));
+        assert!((comparisons[1].change().unwrap() - 0.5).abs() < 1e-9);
+        //Nothing is relative to a baseline that took no time at all
+        assert_eq!(comparisons[2].change(), None);
+        assert!(!comparisons[2].is_regression(0.1));
+    }
+}
@@ -0,0 +1,573 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rand::SeedableRng;
+
+use advent_of_code_2024::input::InputSource;
+use advent_of_code_2024::runner::{self, default_input_path, format_bytes, format_duration, input_path_for, PartReport};
+use advent_of_code_2024::verify::{self, Verdict};
+use advent_of_code_2024::animate::{AnimationOptions, Recorder};
+use advent_of_code_2024::bench::{self, BenchRecord, Phase};
+use advent_of_code_2024::parallel::Jobs;
+use advent_of_code_2024::day11::{self, StoneEvolution};
+use advent_of_code_2024::{day17, day18};
+use advent_of_code_2024::generate::InputRng;
+use advent_of_code_2024::{find_animation, find_day, find_generator, find_visualization, solve_day, REGISTRY};
+
+///Command line interface for running the advent calendar solutions,
+/// so that picking a day no longer means editing `main.rs`.
+#[derive(Debug, Parser)]
+#[command(name = "aoc", about = "Run the Advent of Code 2024 solutions")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    ///Solve a single day's puzzle.
+    Run(RunArgs),
+    ///List every day of the advent calendar and which parts have been solved.
+    List,
+    ///Run every day and part against its input and report how long each took.
+    All(AllArgs),
+    ///Check the solutions against the expected answers recorded in an answers file.
+    Verify(VerifyArgs),
+    ///Draw a day's map with its solution highlighted, in the terminal or as an image.
+    Visualize(VisualizeArgs),
+    ///Record a day's simulation tick by tick, as an animated GIF or a directory of numbered frames.
+    Animate(AnimateArgs),
+    ///Time the parse, part 1 and part 2 phases of each day over several iterations, optionally against a saved baseline.
+    Bench(BenchArgs),
+    ///Write a random but valid puzzle input for a day, the same one every time for the same seed.
+    Generate(GenerateArgs),
+    ///Study how Day 11's stones change blink by blink, as a table of statistics and a graph of which numbers become which.
+    Stones(StonesArgs),
+    ///List the instructions of Day 17's program and render it as pseudo code, without running it.
+    Disassemble(DisassembleArgs),
+    ///Compile assembly for Day 17's computer (`adv 3`, `out A`, `jnz start` and so on) into a `Program: ` line.
+    Assemble(AssembleArgs),
+}
+
+///Where to read a day's puzzle input from.
+#[derive(Debug, Args)]
+pub struct InputArgs {
+    ///Path to the puzzle input, or `-` to read it from stdin. Defaults to `inputs/day<DAY>.txt`.
+    #[arg(short, long)]
+    input: Option<String>,
+    ///The puzzle input itself, given inline instead of as a file.
+    #[arg(long, conflicts_with = "input")]
+    input_text: Option<String>,
+}
+
+impl InputArgs {
+    fn read(&self, day: u8) -> anyhow::Result<(InputSource, String)> {
+        //! Read the input for `day` from wherever it was given, along with where that was.
+        let input_source = match (&self.input_text, &self.input) {
+            (Some(text), _) => InputSource::Inline(text.clone()),
+            (None, Some(arg)) => InputSource::from_arg(arg),
+            (None, None) => InputSource::File(default_input_path(day)),
+        };
+        let input = input_source.read().with_context(|| {
+            format!("Could not read the input from {}. Place the puzzle input there or pass --input <PATH>", input_source)
+        })?;
+        Ok((input_source, input))
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    ///The day of the puzzle to solve (1-25).
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: u8,
+    ///Which part of the puzzle to solve. Both parts are run if omitted.
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+    #[command(flatten)]
+    input: InputArgs,
+    ///Print a sentence per part, or one JSON object per line with the status and timing of each part.
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    ///How many threads the solutions may spread their work over. The answers are the same however many there are.
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    jobs: u64,
+}
+
+///How results are written to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    ///Human readable sentences and tables
+    Text,
+    ///One JSON object per day/part, one per line
+    Json,
+}
+
+fn print_json_records(reports: &[PartReport]) -> anyhow::Result<()> {
+    //! Print each report as a single line JSON object.
+    for report in reports {
+        println!("{}", serde_json::to_string(&report.to_record())?);
+    }
+    Ok(())
+}
+
+pub fn list_days() {
+    //! Print every registered day, marking the parts that have not been solved yet.
+    let solved_marker = |solved: bool| if solved { "solved" } else { "unsolved" };
+    for day_entry in REGISTRY.iter() {
+        println!(
+            "Day {:>2}: {:<24} part 1 {:<8} part 2 {}",
+            day_entry.day,
+            day_entry.title,
+            solved_marker(day_entry.part1.is_some()),
+            solved_marker(day_entry.part2.is_some())
+        );
+    }
+}
+
+impl RunArgs {
+    pub fn execute(&self) -> anyhow::Result<()> {
+        //! Read the input once, then dispatch to the requested day and part(s).
+        //! Without `--part`, only the parts that have been solved are run.
+        let (input_source, input) = self.input.read(self.day)?;
+        let jobs = Jobs::new(self.jobs as usize)?;
+        let day_entry = find_day(self.day)?;
+        let parts = match self.part {
+            Some(part) => vec![part],
+            None => [1, 2].into_iter().filter(|part| day_entry.part(*part).is_some()).collect(),
+        };
+        if self.format == OutputFormat::Json {
+            //Failures are reported in the JSON rather than as an error. There is no time limit on `run`.
+            let reports = parts.into_iter()
+                .map(|part| runner::run_part(day_entry, part, &input_source, &input, Duration::MAX, &jobs))
+                .collect::<Vec<_>>();
+            return print_json_records(&reports);
+        }
+        for part in parts {
+            let answer = jobs.install(|| solve_day(self.day, part, &input)).with_context(|| format!("Day {} part {} failed", self.day, part))?;
+            println!("Day {} part {}: {}", self.day, part, answer);
+        }
+        Ok(())
+    }
+}
+
+///How to order the rows of the `all` report.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SortKey {
+    ///Calendar order
+    Day,
+    ///Slowest part first
+    Time,
+    ///Most memory hungry part first
+    Memory,
+}
+
+#[derive(Debug, Args)]
+pub struct AllArgs {
+    ///Give up on a part after this many seconds. Timed out parts keep running
+    /// in the background, which can skew the measurements of the parts after them.
+    #[arg(short, long, default_value_t = 60)]
+    timeout: u64,
+    ///How to order the report.
+    #[arg(short, long, value_enum, default_value_t = SortKey::Day)]
+    sort: SortKey,
+    ///Directory containing the `day<DAY>.txt` inputs.
+    #[arg(long, default_value = runner::DEFAULT_INPUT_DIR)]
+    inputs: PathBuf,
+    ///Print a table, or one JSON object per line for each day/part.
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    ///How many threads to share between the days, several of which run at once, and the solutions inside them.
+    /// The answers are the same however many there are, but the times and memory peaks are less telling.
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    jobs: u64,
+}
+
+impl AllArgs {
+    pub fn execute(&self) -> anyhow::Result<()> {
+        let jobs = Jobs::new(self.jobs as usize)?;
+        let mut reports = runner::run_all(&self.inputs, Duration::from_secs(self.timeout), &jobs);
+        match self.sort {
+            SortKey::Day => reports.sort_by_key(|report| (report.day, report.part)),
+            SortKey::Time => reports.sort_by_key(|report| std::cmp::Reverse(report.elapsed)),
+            SortKey::Memory => reports.sort_by_key(|report| std::cmp::Reverse(report.peak_allocation)),
+        }
+        match self.format {
+            OutputFormat::Text => print_report_table(&reports),
+            OutputFormat::Json => print_json_records(&reports)?,
+        }
+        Ok(())
+    }
+}
+
+fn print_report_table(reports: &[PartReport]) {
+    //! Print one row per part, followed by the total time and the largest peak allocation.
+    const MAX_ANSWER_WIDTH: usize = 40;
+    println!("{:>3}  {:>4}  {:<24}  {:<13}  {:<40}  {:>10}  {:>11}", "Day", "Part", "Title", "Status", "Answer", "Time", "Peak memory");
+    for report in reports {
+        let mut answer = report.outcome.summary();
+        if answer.chars().count() > MAX_ANSWER_WIDTH {
+            answer = answer.chars().take(MAX_ANSWER_WIDTH - 3).collect::<String>() + "...";
+        }
+        println!(
+            "{:>3}  {:>4}  {:<24}  {:<13}  {:<40}  {:>10}  {:>11}",
+            report.day,
+            report.part,
+            report.title,
+            report.outcome.status(),
+            answer,
+            format_duration(report.elapsed),
+            format_bytes(report.peak_allocation)
+        );
+    }
+    let total_time = reports.iter().map(|report| report.elapsed).sum::<Duration>();
+    let max_peak = reports.iter().map(|report| report.peak_allocation).max().unwrap_or(0);
+    println!("{:<92}  {:>10}  {:>11}", "Total", format_duration(total_time), format_bytes(max_peak));
+}
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    ///The answers file, made of `[[answer]]` tables with a `day`, `part`, `expected` and optional `input`.
+    #[arg(short, long, default_value = verify::DEFAULT_ANSWERS_FILE)]
+    answers: PathBuf,
+    ///Give up on a part after this many seconds, counting it as a failure.
+    #[arg(short, long, default_value_t = 60)]
+    timeout: u64,
+}
+
+impl VerifyArgs {
+    pub fn execute(&self) -> anyhow::Result<()> {
+        //! Report pass/fail/missing for every entry. Any failure is returned as an error
+        //! so that the process exits with a non-zero code.
+        let entries = verify::load_answers(&self.answers)?;
+        let (mut passed, mut failed, mut missing) = (0, 0, 0);
+        for entry in entries.iter() {
+            let label = format!("Day {} part {} ({})", entry.day, entry.part, entry.input_path().display());
+            match verify::verify_entry(entry, Duration::from_secs(self.timeout))? {
+                Verdict::Pass => {
+                    passed += 1;
+                    println!("PASS     {}", label);
+                }
+                Verdict::Fail(reason) => {
+                    failed += 1;
+                    println!("FAIL     {}", label);
+                    for line in reason.lines() {
+                        println!("         {}", line);
+                    }
+                }
+                Verdict::Missing => {
+                    missing += 1;
+                    println!("MISSING  {}: input file not found", label);
+                }
+            }
+        }
+        println!("{} passed, {} failed, {} missing", passed, failed, missing);
+        anyhow::ensure!(failed == 0, "{} of {} answers did not match", failed, entries.len());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct VisualizeArgs {
+    ///The day of the puzzle to visualize.
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: u8,
+    #[command(flatten)]
+    input: InputArgs,
+    ///Save the drawing as a `.png` or `.ppm` image rather than printing it with terminal colors.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    ///The width and height in pixels of every map cell in a saved image.
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..=64))]
+    cell_size: u16,
+}
+
+impl VisualizeArgs {
+    pub fn execute(&self) -> anyhow::Result<()> {
+        let visualize = find_visualization(self.day)?;
+        let (_, input) = self.input.read(self.day)?;
+        let scene = visualize(&input).with_context(|| format!("Day {} could not be visualized", self.day))?;
+        match &self.output {
+            Some(path) => {
+                scene.save(path, self.cell_size as usize)?;
+                println!("Saved the day {} visualization to {}", self.day, path.display());
+            }
+            None => print!("{}", scene.to_ansi()),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct AnimateArgs {
+    ///The day of the puzzle to animate.
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: u8,
+    #[command(flatten)]
+    input: InputArgs,
+    ///Where to save the animation: a `.gif` file, or else a directory to fill with numbered PNG frames.
+    #[arg(short, long)]
+    output: PathBuf,
+    ///Playback speed of a GIF.
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u16).range(1..=100))]
+    fps: u16,
+    ///The first tick to record, where tick 0 is the starting state.
+    #[arg(long, default_value_t = 0)]
+    from_tick: usize,
+    ///The last tick to record. Records until the simulation ends if omitted.
+    #[arg(long)]
+    to_tick: Option<usize>,
+    ///The width and height in pixels of every map cell.
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..=64))]
+    cell_size: u16,
+}
+
+impl AnimateArgs {
+    pub fn execute(&self) -> anyhow::Result<()> {
+        let animate = find_animation(self.day)?;
+        if let Some(to_tick) = self.to_tick {
+            anyhow::ensure!(to_tick >= self.from_tick, "--to-tick {} is before --from-tick {}", to_tick, self.from_tick);
+        }
+        let (_, input) = self.input.read(self.day)?;
+        let options = AnimationOptions {
+            frames_per_second: self.fps,
+            first_tick: self.from_tick,
+            last_tick: self.to_tick,
+            cell_size: self.cell_size as usize,
+        };
+        let mut recorder = Recorder::to_path(&self.output, options)?;
+        animate(&input, &mut recorder).with_context(|| format!("Day {} could not be animated", self.day))?;
+        let frames_recorded = recorder.finish()?;
+        println!("Saved {} frames of the day {} animation to {}", frames_recorded, self.day, self.output.display());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct BenchArgs {
+    ///The days to benchmark, every day with an input if omitted. Can be given more than once.
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: Vec<u8>,
+    ///How many timed runs of each phase.
+    #[arg(short = 'n', long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+    iterations: u64,
+    ///How many untimed runs of each phase to do first.
+    #[arg(long, default_value_t = 1)]
+    warmup: u64,
+    ///Give up on the benchmark if a single run takes more than this many seconds. Timed out runs keep
+    /// running in the background, which can skew the measurements of the phases after them.
+    #[arg(long, default_value_t = 60)]
+    timeout: u64,
+    ///Directory containing the `day<DAY>.txt` inputs.
+    #[arg(long, default_value = runner::DEFAULT_INPUT_DIR)]
+    inputs: PathBuf,
+    ///A baseline saved by `--save-baseline` to compare the median times against.
+    #[arg(short, long)]
+    baseline: Option<PathBuf>,
+    ///Fail if a median time is more than this fraction slower than the baseline (0.1 is 10% slower).
+    #[arg(short, long, default_value_t = 0.1)]
+    threshold: f64,
+    ///Save the results as a JSON baseline.
+    #[arg(long)]
+    save_baseline: Option<PathBuf>,
+    ///Print a table, or one JSON object per line for each day/phase.
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+}
+
+impl BenchArgs {
+    pub fn execute(&self) -> anyhow::Result<()> {
+        //! Benchmark the requested days, then report any regressions from the baseline as an error
+        //! so that the process exits with a non-zero code.
+        let baseline = self.baseline.as_deref().map(bench::load_baseline).transpose()?;
+        let days = if self.day.is_empty() { REGISTRY.iter().map(|entry| entry.day).collect() } else { self.day.clone() };
+        let mut records = Vec::new();
+        let mut skipped_days = Vec::new();
+        for day in days {
+            let day_entry = find_day(day)?;
+            let input_path = input_path_for(&self.inputs, day);
+            let Ok(input) = InputSource::File(input_path.clone()).read() else {
+                if !self.day.is_empty() {
+                    anyhow::bail!("Could not read the day {} input from {}", day, input_path.display());
+                }
+                //Benchmarking every day skips the inputs that have not been placed
+                skipped_days.push(day.to_string());
+                continue;
+            };
+            for phase in Phase::ALL {
+                if let Some(stats) = bench::bench_phase(day_entry, phase, &input, self.warmup as usize, self.iterations as usize, Duration::from_secs(self.timeout))? {
+                    let record = BenchRecord { day, phase, stats };
+                    match self.format {
+                        OutputFormat::Text => print_bench_row(&record, baseline.as_deref()),
+                        OutputFormat::Json => println!("{}", serde_json::to_string(&record)?),
+                    }
+                    records.push(record);
+                }
+            }
+        }
+        if !skipped_days.is_empty() {
+            eprintln!("Skipped days {}, which have no input in {}", skipped_days.join(", "), self.inputs.display());
+        }
+        if let Some(path) = &self.save_baseline {
+            bench::save_baseline(path, &records)?;
+            eprintln!("Saved the baseline to {}", path.display());
+        }
+        let Some(baseline) = baseline else {
+            return Ok(());
+        };
+        let regressions = bench::compare(&records, &baseline).into_iter()
+            .filter(|comparison| comparison.is_regression(self.threshold))
+            .map(|comparison| format!("Day {} {} is {:.1}% slower", comparison.day, comparison.phase, comparison.change().unwrap_or_default() * 100.0))
+            .collect::<Vec<_>>();
+        anyhow::ensure!(regressions.is_empty(), "{} phases are more than {:.1}% slower than the baseline:\n{}", regressions.len(), self.threshold * 100.0, regressions.join("\n"));
+        Ok(())
+    }
+}
+
+fn print_bench_row(record: &BenchRecord, baseline: Option<&[BenchRecord]>) {
+    //! Print the timings of a single day/phase, along with its change from the baseline when there is one.
+    let seconds = |seconds: f64| format_duration(Duration::from_secs_f64(seconds));
+    let change = baseline
+        .and_then(|baseline| bench::compare(std::slice::from_ref(record), baseline).pop())
+        .map(|comparison| comparison.change().map_or("n/a".to_string(), |change| format!("{:+.1}%", change * 100.0)))
+        .unwrap_or_default();
+    println!(
+        "Day {:>2} {:<5}  mean {:>10}  median {:>10}  stddev {:>10}  min {:>10}  max {:>10}  {}",
+        record.day,
+        record.phase,
+        seconds(record.stats.mean),
+        seconds(record.stats.median),
+        seconds(record.stats.stddev),
+        seconds(record.stats.min),
+        seconds(record.stats.max),
+        change
+    );
+}
+
+#[derive(Debug, Args)]
+pub struct GenerateArgs {
+    ///The day of the puzzle to generate an input for.
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: u8,
+    ///How large an input to generate, in the day's own unit (such as lines, bits or positions wide).
+    /// Defaults to the size of the real puzzle inputs.
+    #[arg(short, long)]
+    size: Option<usize>,
+    ///Seed for the random input. A random seed is picked (and reported) if omitted.
+    #[arg(long)]
+    seed: Option<u64>,
+    ///Day 18 only: the width (and height) of the memory space the bytes fall into, 71 in the real puzzle.
+    /// The size then defaults to two thirds of the memory space.
+    #[arg(long)]
+    grid: Option<usize>,
+    ///Write the input to this file rather than printing it.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+impl GenerateArgs {
+    pub fn execute(&self) -> anyhow::Result<()> {
+        let generator = find_generator(self.day)?;
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let (size, input) = match self.grid {
+            Some(grid_width) => {
+                anyhow::ensure!(self.day == 18, "Only day 18 inputs can be given a --grid size");
+                let size = self.size.unwrap_or(grid_width * grid_width * 2 / 3);
+                (size, day18::generate_for_grid(size, grid_width, &mut InputRng::seed_from_u64(seed))?)
+            }
+            None => {
+                let size = self.size.unwrap_or(generator.default_size);
+                (size, generator.generate(size, seed)?)
+            }
+        };
+        match &self.output {
+            Some(path) => fs::write(path, input + "\n").with_context(|| format!("Could not write the input to {}", path.display()))?,
+            None => println!("{}", input),
+        }
+        //Reported on stderr so that the input itself can be piped
+        eprintln!("Generated a day {} input of {} {} with seed {}", self.day, size, generator.size_unit, seed);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct StonesArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    ///How many times the stones blink.
+    #[arg(short, long, default_value_t = 75)]
+    blinks: usize,
+    ///Also write the graph of which numbers become which to this file, in the DOT language.
+    #[arg(long)]
+    dot: Option<PathBuf>,
+}
+
+impl StonesArgs {
+    pub fn execute(&self) -> anyhow::Result<()> {
+        //! Print a row of statistics per blink, then when the numbers on the stones stopped changing.
+        let (_, input) = self.input.read(11)?;
+        let stones = day11::parse_stones(&input)?;
+        let mut evolution = StoneEvolution::new(&stones);
+        let stats = evolution.blink_with_stats(self.blinks)?;
+        println!("{:>5}  {:>8}  {:>8}  {:>20}  {:>20}", "Blink", "Distinct", "Seen", "Largest", "Stones");
+        for blink_stats in stats.iter() {
+            let largest_value = blink_stats.largest_value.map_or("-".to_string(), |value| value.to_string());
+            println!(
+                "{:>5}  {:>8}  {:>8}  {:>20}  {:>20}",
+                blink_stats.blink, blink_stats.distinct_values, blink_stats.values_seen, largest_value, blink_stats.total_stones
+            );
+        }
+        match day11::saturation_blink(&stats) {
+            Some(blink) => println!("Every number the stones can be engraved with had been seen by blink {}, {} in all", blink, stats[blink].values_seen),
+            None => println!("New numbers were still being engraved after {} blinks", self.blinks),
+        }
+        if let Some(path) = &self.dot {
+            fs::write(path, evolution.transition_graph()).with_context(|| format!("Could not write the graph to {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct DisassembleArgs {
+    ///The puzzle input, or just its `Program: ` line.
+    #[command(flatten)]
+    input: InputArgs,
+}
+
+impl DisassembleArgs {
+    pub fn execute(&self) -> anyhow::Result<()> {
+        let (input_source, input) = self.input.read(17)?;
+        let program_line = input.lines()
+            .find(|line| line.starts_with("Program:"))
+            .with_context(|| format!("There is no `Program: ` line in {}", input_source))?;
+        let program = day17::parse_program(program_line)?;
+        println!("{}", day17::listing(&program)?);
+        print!("{}", day17::pseudo_code(&program)?);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct AssembleArgs {
+    ///Path to the assembly, or `-` to read it from stdin.
+    source: String,
+    ///Write a whole puzzle input, with register A starting at this value and B and C at 0.
+    #[arg(short = 'a', long)]
+    register_a: Option<i64>,
+}
+
+impl AssembleArgs {
+    pub fn execute(&self) -> anyhow::Result<()> {
+        let source = InputSource::from_arg(&self.source).read()?;
+        let program = day17::assemble(&source)?;
+        let program = program.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(",");
+        if let Some(register_a) = self.register_a {
+            println!("Register A: {}\nRegister B: 0\nRegister C: 0\n", register_a);
+        }
+        println!("Program: {}", program);
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+
+use rand::rngs::Xoshiro256PlusPlus;
+use rand::seq::SliceRandom;
+use rand::{RngExt, SeedableRng};
+
+use crate::geometry::Point;
+use crate::grid::Grid;
+
+///The random number generator every input generator draws from. Its algorithm is fixed,
+/// so the same seed always generates the same input.
+pub type InputRng = Xoshiro256PlusPlus;
+
+///The signature shared by every day's `generate`, which writes a random puzzle input of the given size.
+pub type GenerateFn = fn(usize, &mut InputRng) -> String;
+
+///How to generate random (but valid) puzzle inputs for a day, so that the solutions can be
+/// tested, fuzzed and benchmarked without the real inputs.
+#[derive(Debug, Clone)]
+pub struct Generator {
+    pub day: u8,
+    ///What the size of an input measures for this day, such as `lines` or `bits`
+    pub size_unit: &'static str,
+    ///The sizes this day can generate inputs of
+    pub sizes: RangeInclusive<usize>,
+    ///The size of the real puzzle inputs
+    pub default_size: usize,
+    pub generate: GenerateFn,
+}
+
+impl Generator {
+    pub const fn new(day: u8, size_unit: &'static str, sizes: RangeInclusive<usize>, default_size: usize, generate: GenerateFn) -> Self {
+        Generator {
+            day,
+            size_unit,
+            sizes,
+            default_size,
+            generate,
+        }
+    }
+
+    pub fn generate(&self, size: usize, seed: u64) -> anyhow::Result<String> {
+        //! Generate an input of `size`, the same one every time for the same `seed`.
+        anyhow::ensure!(
+            self.sizes.contains(&size),
+            "Day {} inputs are between {} and {} {}, not {}", self.day, self.sizes.start(), self.sizes.end(), self.size_unit, size
+        );
+        let mut rng = InputRng::seed_from_u64(seed);
+        Ok((self.generate)(size, &mut rng))
+    }
+}
+
+pub fn nearest_seeds(rows: usize, cols: usize, seeds: &[Point]) -> Grid<(usize, usize)> {
+    //! For every cell, the index of the closest of the `seeds` and how many steps away it is,
+    //! ties going to the seed listed first. Grows patches around points, such as regions or hills.
+    let mut nearest = Grid::filled(rows, cols, (usize::MAX, usize::MAX));
+    let mut queue = VecDeque::new();
+    for (index, seed) in seeds.iter().enumerate() {
+        if nearest.get(*seed).is_some_and(|(_, distance)| *distance == usize::MAX) {
+            nearest[*seed] = (index, 0);
+            queue.push_back(*seed);
+        }
+    }
+    while let Some(position) = queue.pop_front() {
+        let (index, distance) = nearest[position];
+        for neighbor in position.neighbors4() {
+            if nearest.get(neighbor).is_some_and(|(_, neighbor_distance)| *neighbor_distance == usize::MAX) {
+                nearest[neighbor] = (index, distance + 1);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    nearest
+}
+
+pub fn maze(rows: usize, cols: usize, shortcut_chance: f64, rng: &mut InputRng) -> Grid<char> {
+    //! A maze of `#` walls and `.` corridors, surrounded by walls. Corridors run through every cell whose row
+    //! and column are both odd, and without shortcuts there is exactly one way between any two of them.
+    //! Each wall left between two corridors is knocked down with `shortcut_chance`, adding loops.
+    let mut maze = Grid::filled(rows, cols, '#');
+    let start = Point::new(1, 1);
+    maze[start] = '.';
+    let mut stack = vec![start];
+    while let Some(&position) = stack.last() {
+        let mut unvisited = [Point::new(-2, 0), Point::new(0, 2), Point::new(2, 0), Point::new(0, -2)]
+            .map(|offset| position + offset)
+            .into_iter()
+            .filter(|next| next.row < rows as i64 - 1 && next.col < cols as i64 - 1 && maze.get(*next) == Some(&'#'))
+            .collect::<Vec<_>>();
+        unvisited.shuffle(rng);
+        match unvisited.first() {
+            Some(&next) => {
+                maze[Point::new((position.row + next.row) / 2, (position.col + next.col) / 2)] = '.';
+                maze[next] = '.';
+                stack.push(next);
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+    for row in 1..rows.saturating_sub(1) {
+        for col in 1..cols.saturating_sub(1) {
+            let position = Point::new(row as i64, col as i64);
+            //Walls between two corridors have exactly one odd coordinate, the rest are the corners between walls
+            let between = |offset: Point| maze.get(position + offset) == Some(&'.') && maze.get(position - offset) == Some(&'.');
+            let separates_corridors = (row + col) % 2 == 1 && (between(Point::new(0, 1)) || between(Point::new(1, 0)));
+            if maze[position] == '#' && separates_corridors && rng.random_bool(shortcut_chance) {
+                maze[position] = '.';
+            }
+        }
+    }
+    maze
+}
+
+pub fn last_corridor(length: usize) -> i64 {
+    //! The last row (or column) of a `maze` `length` cells tall (or wide) with corridors in it.
+    (length as i64 - 3) | 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::{find_day, GENERATORS};
+    use crate::search::bfs;
+
+    fn test_size(generator: &Generator) -> usize {
+        //Small enough to solve quickly, even unoptimized
+        (generator.default_size / 4).clamp(*generator.sizes.start(), *generator.sizes.end())
+    }
+
+    #[test]
+    fn generated_inputs_can_be_solved() {
+        for generator in GENERATORS.iter() {
+            let input = generator.generate(test_size(generator), 2024).unwrap();
+            let day_entry = find_day(generator.day).unwrap();
+            if let Some(parse) = day_entry.parse {
+                parse(&input).unwrap_or_else(|error| panic!("Day {} generated an input it cannot parse: {}", generator.day, error));
+            }
+            day_entry.solve(1, &input).unwrap();
+            //Day 17's random programs seldom have a quine, while Days 18 and 22 take too long unoptimized
+            if day_entry.part2.is_some() && ![17, 18, 22].contains(&generator.day) {
+                day_entry.solve(2, &input).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn the_seed_decides_the_input() {
+        let generator = &GENERATORS[8];
+        let input = generator.generate(19, 7).unwrap();
+        assert_eq!(input.len(), 19);
+        assert_eq!(generator.generate(19, 7).unwrap(), input);
+        assert_ne!(generator.generate(19, 8).unwrap(), input);
+        let error = generator.generate(0, 7).unwrap_err();
+        assert_eq!(error.to_string(), "Day 9 inputs are between 1 and 100000 digits, not 0");
+    }
+
+    #[test]
+    fn maze_corridors_are_all_connected() {
+        let mut rng = InputRng::seed_from_u64(0);
+        let maze = maze(9, 12, 0.0, &mut rng);
+        assert_eq!((last_corridor(9), last_corridor(12)), (7, 9));
+        //A tree of the 4x5 corridor cells has 19 passages joining them
+        assert_eq!(maze.find_all(&'.').count(), 20 + 19);
+        let open_neighbors = |position: &Point| maze.neighbors4(*position).filter(|next| maze[*next] == '.').collect::<Vec<_>>();
+        assert_eq!(bfs(Point::new(1, 1), open_neighbors, |_| false).distances().len(), 39);
+        let shortcuts = super::maze(9, 12, 1.0, &mut rng);
+        assert_eq!(shortcuts.find_all(&'.').count(), 20 + 31);
+    }
+
+    #[test]
+    fn cells_belong_to_the_nearest_seed() {
+        let nearest = nearest_seeds(3, 4, &[Point::new(0, 0), Point::new(2, 3), Point::new(2, 3)]);
+        assert_eq!(nearest.map(|(index, _)| *index).to_string(), "0001\n0011\n0111");
+        assert_eq!((nearest[Point::new(2, 0)], nearest[Point::new(2, 3)]), ((0, 2), (1, 0)));
+    }
+}
@@ -0,0 +1,205 @@
+use std::fmt::Display;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+///A `(row, col)` position (or offset) on a map. Rows grow downwards, matching the
+/// order the puzzle input is read in. Signed, so that stepping off the top or left
+/// edge of a map gives a point that is simply not on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point {
+    pub row: i64,
+    pub col: i64,
+}
+
+impl Point {
+    ///The top left corner of a map, or an offset of nothing.
+    pub const ORIGIN: Point = Point::new(0, 0);
+
+    ///The offsets to all 8 neighbors, clockwise starting from up.
+    pub const ALL_NEIGHBOR_OFFSETS: [Point; 8] = [
+        Point::new(-1, 0), Point::new(-1, 1), Point::new(0, 1), Point::new(1, 1),
+        Point::new(1, 0), Point::new(1, -1), Point::new(0, -1), Point::new(-1, -1),
+    ];
+
+    pub const fn new(row: i64, col: i64) -> Self {
+        Point { row, col }
+    }
+
+    pub fn manhattan_distance(&self, other: Point) -> i64 {
+        //! The number of orthogonal steps between two points.
+        (self.row - other.row).abs() + (self.col - other.col).abs()
+    }
+
+    pub fn step(&self, direction: Direction) -> Point {
+        //! The point one step away in `direction`.
+        *self + direction.delta()
+    }
+
+    pub fn neighbors4(&self) -> impl Iterator<Item = Point> {
+        //! The 4 orthogonally adjacent points, whether or not they are on any map.
+        let point = *self;
+        Direction::ALL.into_iter().map(move |direction| point.step(direction))
+    }
+
+    pub fn neighbors8(&self) -> impl Iterator<Item = Point> {
+        //! All 8 adjacent points, diagonals included.
+        let point = *self;
+        Point::ALL_NEIGHBOR_OFFSETS.into_iter().map(move |offset| point + offset)
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.row + other.row, self.col + other.col)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.row - other.row, self.col - other.col)
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Point) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Point;
+
+    fn mul(self, scale: i64) -> Point {
+        Point::new(self.row * scale, self.col * scale)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::new(-self.row, -self.col)
+    }
+}
+
+impl From<(i64, i64)> for Point {
+    fn from((row, col): (i64, i64)) -> Self {
+        Point::new(row, col)
+    }
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.row, self.col)
+    }
+}
+
+///One of the 4 orthogonal directions on a map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    ///Every direction, clockwise starting from up.
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+    pub fn rotate_clockwise(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn rotate_counterclockwise(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    pub fn opposite(&self) -> Self {
+        self.rotate_clockwise().rotate_clockwise()
+    }
+
+    pub fn delta(&self) -> Point {
+        //! The offset of a single step in this direction.
+        match self {
+            Direction::Up => Point::new(-1, 0),
+            Direction::Right => Point::new(0, 1),
+            Direction::Down => Point::new(1, 0),
+            Direction::Left => Point::new(0, -1),
+        }
+    }
+
+    pub fn from_delta(delta: Point) -> Option<Self> {
+        //! The direction of a single orthogonal step, if `delta` is one.
+        Direction::ALL.into_iter().find(|direction| direction.delta() == delta)
+    }
+
+    pub fn from_arrow(arrow: char) -> Option<Self> {
+        //! Parse one of the `^`, `>`, `v` or `<` arrows the puzzles draw directions with.
+        match arrow {
+            '^' => Some(Direction::Up),
+            '>' => Some(Direction::Right),
+            'v' => Some(Direction::Down),
+            '<' => Some(Direction::Left),
+            _ => None,
+        }
+    }
+
+    pub fn arrow(&self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_arithmetic() {
+        let point = Point::new(2, 3);
+        assert_eq!(point + Point::new(1, -4), Point::new(3, -1));
+        assert_eq!(point - Point::new(1, -4), Point::new(1, 7));
+        assert_eq!(point * 3, Point::new(6, 9));
+        assert_eq!(-point, Point::new(-2, -3));
+        assert_eq!(point.manhattan_distance(Point::new(-1, 5)), 5);
+        assert_eq!(point.step(Direction::Up), Point::new(1, 3));
+        assert_eq!(point.neighbors8().count(), 8);
+    }
+
+    #[test]
+    fn directions_rotate_and_reverse() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.rotate_clockwise().rotate_counterclockwise(), direction);
+            assert_eq!(direction.opposite().delta(), -direction.delta());
+            assert_eq!(Direction::from_delta(direction.delta()), Some(direction));
+            assert_eq!(Direction::from_arrow(direction.arrow()), Some(direction));
+        }
+        assert_eq!(Direction::Left.rotate_clockwise(), Direction::Up);
+        assert_eq!(Direction::from_delta(Point::new(1, 1)), None);
+        assert_eq!(Direction::from_arrow('x'), None);
+    }
+}
@@ -0,0 +1,230 @@
+use std::fmt::Display;
+use std::ops::{Index, IndexMut};
+
+use crate::geometry::Point;
+use crate::input_error::InputError;
+
+///A rectangular grid of cells, stored row by row. Most puzzles
+/// are a map of some kind, so this handles the parsing and bounds checks once.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn from_rows(rows: Vec<Vec<T>>) -> anyhow::Result<Self> {
+        //! Build a grid from its rows, which must all be the same length.
+        let cols = rows.first().map_or(0, Vec::len);
+        let row_count = rows.len();
+        let mut cells = Vec::with_capacity(row_count * cols);
+        for (row_number, row) in rows.into_iter().enumerate() {
+            anyhow::ensure!(row.len() == cols, "Row {} is {} cells wide, but the first row is {} wide", row_number, row.len(), cols);
+            cells.extend(row);
+        }
+        Ok(Grid { cells, rows: row_count, cols })
+    }
+
+    pub fn parse(day: u8, text: &str, mut parse_cell: impl FnMut(char) -> Option<T>, expected: &str) -> Result<Self, InputError> {
+        //! Parse one cell per character of `text`, one row per line. `parse_cell` returns `None` for
+        //! characters that are not allowed, which are reported as not being the `expected` symbols.
+        let mut cells = Vec::with_capacity(text.len());
+        let mut cols = None;
+        let mut rows = 0;
+        for (line_index, line) in text.lines().enumerate() {
+            for (offset, symbol) in line.char_indices() {
+                let cell = parse_cell(symbol)
+                    .ok_or_else(|| InputError::at(day, line, &line[offset..offset + symbol.len_utf8()], expected).on_line(line_index + 1))?;
+                cells.push(cell);
+            }
+            let width = line.chars().count();
+            match cols {
+                None => cols = Some(width),
+                Some(cols) if cols != width => {
+                    return Err(InputError::at_end(day, line, format!("a row {} characters wide, like the first", cols)).on_line(line_index + 1));
+                }
+                Some(_) => {}
+            }
+            rows += 1;
+        }
+        Ok(Grid { cells, rows, cols: cols.unwrap_or(0) })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn contains(&self, position: Point) -> bool {
+        position.row >= 0 && position.col >= 0 && (position.row as usize) < self.rows && (position.col as usize) < self.cols
+    }
+
+    fn cell_index(&self, position: Point) -> Option<usize> {
+        self.contains(position).then(|| position.row as usize * self.cols + position.col as usize)
+    }
+
+    pub fn get(&self, position: Point) -> Option<&T> {
+        //! The cell at `position`, or `None` if it is off the grid.
+        self.cell_index(position).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, position: Point) -> Option<&mut T> {
+        self.cell_index(position).map(|index| &mut self.cells[index])
+    }
+
+    pub fn get_wrapping(&self, position: Point) -> &T {
+        //! The cell at `position`, wrapping around to the other side of the grid when it runs off an edge.
+        //! Panics if the grid is empty.
+        &self[Point::new(position.row.rem_euclid(self.rows as i64), position.col.rem_euclid(self.cols as i64))]
+    }
+
+    pub fn neighbors4(&self, position: Point) -> impl Iterator<Item = Point> + '_ {
+        //! The orthogonal neighbors of `position` that are on the grid.
+        position.neighbors4().filter(|neighbor| self.contains(*neighbor))
+    }
+
+    pub fn neighbors8(&self, position: Point) -> impl Iterator<Item = Point> + '_ {
+        //! The orthogonal and diagonal neighbors of `position` that are on the grid.
+        position.neighbors8().filter(|neighbor| self.contains(*neighbor))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> + '_ {
+        self.cells.iter().skip(col).step_by(self.cols.max(1))
+    }
+
+    pub fn ray(&self, start: Point, step: Point) -> impl Iterator<Item = &T> + '_ {
+        //! The cells from `start` (inclusive) taking `step` at a time until falling off the grid,
+        //! such as a diagonal with a step of `(1, 1)`.
+        std::iter::successors(Some(start), move |position| Some(*position + step))
+            .map_while(|position| self.get(position))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Point> {
+        //! Every position on the grid, row by row.
+        let cols = self.cols as i64;
+        (0..self.rows as i64).flat_map(move |row| (0..cols).map(move |col| Point::new(row, col)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn find(&self, value: &T) -> Option<Point> where T: PartialEq {
+        //! The first position (row by row) holding `value`.
+        self.find_all(value).next()
+    }
+
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Point> + 'a where T: PartialEq {
+        self.iter().filter(move |(_, cell)| *cell == value).map(|(position, _)| position)
+    }
+
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(&mut f).collect(),
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled(rows: usize, cols: usize, value: T) -> Self {
+        //! A `rows` x `cols` grid with every cell set to `value`.
+        Grid { cells: vec![value; rows * cols], rows, cols }
+    }
+}
+
+impl Grid<char> {
+    pub fn parse_chars(day: u8, text: &str) -> Result<Self, InputError> {
+        //! Parse `text` into a grid of its characters. Only ragged rows are an error.
+        Grid::parse(day, text, Some, "any character")
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Point) -> &T {
+        self.get(position).unwrap_or_else(|| panic!("Position {} is outside the {}x{} grid", position, self.rows, self.cols))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, position: Point) -> &mut T {
+        let (rows, cols) = (self.rows, self.cols);
+        self.get_mut(position).unwrap_or_else(|| panic!("Position {} is outside the {}x{} grid", position, rows, cols))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in 0..self.rows {
+            if row > 0 {
+                writeln!(f)?;
+            }
+            for cell in self.row(row) {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "ab.\n.cd\ne.f";
+
+    #[test]
+    fn parse_and_display_round_trip() {
+        let grid = Grid::parse_chars(4, TEXT).unwrap();
+        assert_eq!((grid.rows(), grid.cols()), (3, 3));
+        assert_eq!(grid[Point::new(1, 2)], 'd');
+        assert_eq!(grid.to_string(), TEXT);
+        assert_eq!(grid.find(&'e'), Some(Point::new(2, 0)));
+        assert_eq!(grid.find_all(&'.').collect::<Vec<_>>(), vec![Point::new(0, 2), Point::new(1, 0), Point::new(2, 1)]);
+    }
+
+    #[test]
+    fn bad_cells_and_ragged_rows_are_located() {
+        let error = Grid::parse(10, "012\n3x5", |symbol| symbol.to_digit(10), "a digit").unwrap_err();
+        assert_eq!((error.line, error.column, error.found.as_str()), (2, 2, "x"));
+        let error = Grid::parse_chars(6, "...\n..\n...").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 3));
+    }
+
+    #[test]
+    fn checked_and_wrapping_reads() {
+        let grid = Grid::parse_chars(4, TEXT).unwrap();
+        assert_eq!(grid.get(Point::new(-1, 0)), None);
+        assert_eq!(grid.get(Point::new(0, 3)), None);
+        assert_eq!(grid.get_wrapping(Point::new(-1, 3)), &'e');
+        assert_eq!(grid.get_wrapping(Point::new(4, -1)), &'d');
+    }
+
+    #[test]
+    fn neighbors_stay_on_the_grid() {
+        let grid = Grid::parse_chars(4, TEXT).unwrap();
+        assert_eq!(grid.neighbors4(Point::new(0, 0)).collect::<Vec<_>>(), vec![Point::new(0, 1), Point::new(1, 0)]);
+        assert_eq!(grid.neighbors8(Point::new(0, 0)).collect::<Vec<_>>(), vec![Point::new(0, 1), Point::new(1, 1), Point::new(1, 0)]);
+        assert_eq!(grid.neighbors8(Point::new(1, 1)).count(), 8);
+    }
+
+    #[test]
+    fn rows_columns_and_rays() {
+        let grid = Grid::parse_chars(4, TEXT).unwrap();
+        assert_eq!(grid.row(1), &['.', 'c', 'd']);
+        assert_eq!(grid.column(2).collect::<String>(), ".df");
+        assert_eq!(grid.ray(Point::new(0, 0), Point::new(1, 1)).collect::<String>(), "acf");
+        assert_eq!(grid.ray(Point::new(2, 0), Point::new(-1, 1)).collect::<String>(), "ec.");
+    }
+}
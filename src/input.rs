@@ -0,0 +1,98 @@
+use std::fmt::Display;
+use std::io::Read;
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::advent_solutions::read_input_file;
+
+///The command line argument that selects standard input instead of a file.
+pub const STDIN_ARG: &str = "-";
+
+///Where the text of a puzzle input comes from. Solutions only ever see the
+/// text, so reading (and normalizing) it is done once, here.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    ///A file on disk, such as `inputs/day16.txt`
+    File(PathBuf),
+    ///Everything piped into standard input
+    Stdin,
+    ///Puzzle text given directly, such as on the command line
+    Inline(String),
+}
+
+impl InputSource {
+    pub fn from_arg(arg: &str) -> Self {
+        //! Interpret a command line input argument, where `-` means standard input.
+        if arg == STDIN_ARG {
+            InputSource::Stdin
+        } else {
+            InputSource::File(PathBuf::from(arg))
+        }
+    }
+
+    pub fn read(&self) -> anyhow::Result<String> {
+        //! Read the raw input and normalize it with `normalize_input`.
+        let raw_input = match self {
+            InputSource::File(path) => {
+                anyhow::ensure!(path.is_file(), "Input file {} does not exist", path.display());
+                read_input_file(path).with_context(|| format!("Failed to read input file {}", path.display()))?
+            }
+            InputSource::Stdin => {
+                let mut raw_input = String::new();
+                std::io::stdin().read_to_string(&mut raw_input).context("Failed to read the input from stdin")?;
+                raw_input
+            }
+            InputSource::Inline(text) => text.clone(),
+        };
+        Ok(normalize_input(&raw_input))
+    }
+}
+
+impl Display for InputSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputSource::File(path) => write!(f, "{}", path.display()),
+            InputSource::Stdin => write!(f, "<stdin>"),
+            InputSource::Inline(_) => write!(f, "<inline>"),
+        }
+    }
+}
+
+pub fn normalize_input(raw_input: &str) -> String {
+    //! Convert Windows (`\r\n`) line endings to `\n` and strip trailing whitespace from every line,
+    //! and trailing blank lines from the end of the input. Inputs saved on different machines
+    //! then look the same to the solutions.
+    let lines = raw_input.lines().map(str::trim_end).collect::<Vec<_>>();
+    lines.join("\n").trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crlf_and_trailing_whitespace_are_normalized() {
+        assert_eq!(normalize_input("a b  \r\nc\t\r\n\r\nd\r\n\r\n"), "a b\nc\n\nd");
+        assert_eq!(normalize_input("2333133121414131402\n"), "2333133121414131402");
+    }
+
+    #[test]
+    fn dash_selects_stdin() {
+        assert_eq!(InputSource::from_arg("-"), InputSource::Stdin);
+        assert_eq!(InputSource::from_arg("inputs/day1.txt"), InputSource::File(PathBuf::from("inputs/day1.txt")));
+    }
+
+    #[test]
+    fn inline_input_is_normalized() {
+        let source = InputSource::Inline("125 17\r\n".to_string());
+        assert_eq!(source.read().unwrap(), "125 17");
+        assert_eq!(source.to_string(), "<inline>");
+    }
+
+    #[test]
+    fn missing_files_are_reported() {
+        let error = InputSource::File(PathBuf::from("does/not/exist.txt")).read().unwrap_err();
+        assert_eq!(error.to_string(), "Input file does/not/exist.txt does not exist");
+    }
+}
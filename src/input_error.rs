@@ -0,0 +1,148 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+///The most characters of a line shown when rendering an error. Some inputs
+/// are a single line thousands of characters long.
+const MAX_RENDERED_WIDTH: usize = 80;
+
+///A problem with a days puzzle input, pinned to the line and column where it was found.
+/// Rendered with the offending line and a caret under the bad character:
+/// ```text
+/// Day 14 input line 3, column 9: expected an integer, found `3;-3`
+///    3 | p=0,4 v=3;-3
+///      |         ^
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputError {
+    pub day: u8,
+    ///1-based line number in the input
+    pub line: usize,
+    ///1-based column, counted in characters
+    pub column: usize,
+    ///The text that could not be parsed, empty if something was missing
+    pub found: String,
+    ///What the parser was expecting to find instead
+    pub expected: String,
+    ///The full line the error was found on
+    pub line_text: String,
+}
+
+impl InputError {
+    pub fn at(day: u8, source: &str, fragment: &str, expected: impl Into<String>) -> Self {
+        //! An error pointing at `fragment`, which must be a slice of `source` (as handed out by `split`, `trim`, etc.).
+        //! A fragment from anywhere else is reported at the start of `source`.
+        let offset = (fragment.as_ptr() as usize)
+            .checked_sub(source.as_ptr() as usize)
+            .filter(|offset| offset + fragment.len() <= source.len())
+            .unwrap_or(0);
+        let mut error = InputError::at_offset(day, source, offset, expected);
+        error.found = fragment.to_string();
+        error
+    }
+
+    pub fn at_offset(day: u8, source: &str, offset: usize, expected: impl Into<String>) -> Self {
+        //! An error pointing at the byte `offset` into `source`, with nothing found there.
+        let offset = offset.min(source.len());
+        let line_start = source[..offset].rfind('\n').map_or(0, |newline| newline + 1);
+        let line_end = source[offset..].find('\n').map_or(source.len(), |newline| offset + newline);
+        InputError {
+            day,
+            line: source[..offset].matches('\n').count() + 1,
+            column: source[line_start..offset].chars().count() + 1,
+            found: String::new(),
+            expected: expected.into(),
+            line_text: source[line_start..line_end].to_string(),
+        }
+    }
+
+    pub fn at_end(day: u8, source: &str, expected: impl Into<String>) -> Self {
+        //! Something was missing from the end of `source`.
+        InputError::at_offset(day, source, source.len(), expected)
+    }
+
+    pub fn on_line(mut self, first_line: usize) -> Self {
+        //! Errors are located relative to the text handed to the parser. When that text
+        //! started on `first_line` of the input, shift the error there.
+        self.line += first_line - 1;
+        self
+    }
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Day {} input line {}, column {}: expected {}", self.day, self.line, self.column, self.expected)?;
+        if self.found.is_empty() {
+            writeln!(f, ", found nothing")?;
+        } else {
+            writeln!(f, ", found `{}`", self.found)?;
+        }
+        //Show a window of the line around the bad character, so that long lines stay readable
+        let characters = self.line_text.chars().collect::<Vec<_>>();
+        let window_start = (self.column - 1).saturating_sub(MAX_RENDERED_WIDTH / 2).min(characters.len().saturating_sub(MAX_RENDERED_WIDTH));
+        let window_end = (window_start + MAX_RENDERED_WIDTH).min(characters.len());
+        let prefix = if window_start > 0 { "..." } else { "" };
+        let suffix = if window_end < characters.len() { "..." } else { "" };
+        let window = characters[window_start..window_end].iter().collect::<String>();
+        let gutter = self.line.to_string().len();
+        writeln!(f, "{:>gutter$} | {}{}{}", self.line, prefix, window, suffix)?;
+        let caret_position = prefix.len() + self.column - 1 - window_start;
+        write!(f, "{:>gutter$} | {}^", "", " ".repeat(caret_position))
+    }
+}
+
+impl std::error::Error for InputError {}
+
+pub fn parse_number<T: FromStr>(day: u8, source: &str, fragment: &str) -> Result<T, InputError> {
+    //! Parse `fragment` (a slice of `source`) as a number, pointing at it if it is not one.
+    fragment.parse::<T>().map_err(|_| InputError::at(day, source, fragment, "a number"))
+}
+
+pub fn parse_lines<'a, T>(input: &'a str, parse_line: impl FnMut(&'a str) -> Result<T, InputError>) -> Result<Vec<T>, InputError> {
+    //! Parse every line of `input` with `parse_line`, which locates errors within its line.
+    let mut parse_line = parse_line;
+    input.lines()
+        .enumerate()
+        .map(|(line_index, line)| parse_line(line).map_err(|error| error.on_line(line_index + 1)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_point_at_the_offending_fragment() {
+        let input = "p=0,4 v=3,-3\np=6,3 v=-1,-3\np=10,3 v=3;-3";
+        let third_line = input.lines().nth(2).unwrap();
+        let fragment = &third_line[9..];
+        let error = InputError::at(14, third_line, fragment, "an integer").on_line(3);
+        assert_eq!((error.line, error.column, error.found.as_str()), (3, 10, "3;-3"));
+        assert_eq!(
+            error.to_string(),
+            "Day 14 input line 3, column 10: expected an integer, found `3;-3`\n3 | p=10,3 v=3;-3\n  |          ^"
+        );
+    }
+
+    #[test]
+    fn offsets_span_multiple_lines() {
+        let error = InputError::at_end(13, "Button A: X+94, Y+34\nButton B: X+22", ", Y+<number>");
+        assert_eq!((error.line, error.column, error.line_text.as_str()), (2, 15, "Button B: X+22"));
+        assert!(error.to_string().ends_with("2 | Button B: X+22\n  |               ^"));
+    }
+
+    #[test]
+    fn long_lines_are_rendered_around_the_column() {
+        let line = "1".repeat(200) + "x" + &"1".repeat(200);
+        let error = InputError::at(9, &line, &line[200..201], "a digit");
+        let rendered = error.to_string();
+        let [_, text, caret]: [&str; 3] = rendered.lines().collect::<Vec<_>>().try_into().unwrap();
+        assert!(text.starts_with("1 | ...") && text.ends_with("..."));
+        assert_eq!(text.find('x'), caret.find('^'));
+    }
+
+    #[test]
+    fn parse_lines_reports_the_line_number() {
+        let error = parse_lines("1\n2\nthree", |line| parse_number::<i64>(1, line, line)).unwrap_err();
+        assert_eq!((error.line, error.column, error.found.as_str()), (3, 1, "three"));
+    }
+}
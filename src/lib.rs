@@ -0,0 +1,30 @@
+//! Solutions to Advent of Code 2024. Each day module exposes its parsers, domain types
+//! and solve functions, and the `aoc` binary is a thin command line consumer of this library.
+pub mod advent_solutions;
+pub mod allocation;
+pub mod animate;
+pub mod bench;
+pub mod generate;
+pub mod geometry;
+pub mod grid;
+pub mod input;
+pub mod input_error;
+pub mod memo;
+pub mod parallel;
+pub mod parsing;
+pub mod runner;
+pub mod search;
+pub mod verify;
+pub mod visualize;
+
+pub use advent_solutions::{
+    day1, day2, day3, day4, day5, day6, day7, day8, day9, day10, day11, day12, day13,
+    day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24, day25,
+};
+pub use geometry::{Direction, Point};
+pub use grid::Grid;
+pub use input_error::InputError;
+pub use advent_solutions::{
+    find_animation, find_day, find_generator, find_visualization, read_input_file, solve_day, AnimateFn, Answer, DayEntry, ParseFn, SolveAdvent, SolveFn, VisualizeFn,
+    ANIMATIONS, GENERATORS, REGISTRY, VISUALIZATIONS,
+};
@@ -1,9 +1,29 @@
-mod advent_solutions;
-use crate::advent_solutions::day21::Day21;
-use crate::advent_solutions::SolveAdvent;
+mod cli;
+
+use advent_of_code_2024::allocation::TrackingAllocator;
+use clap::Parser;
+
+use crate::cli::{Cli, Command};
+
+#[global_allocator]
+static GLOBAL: TrackingAllocator = TrackingAllocator;
 
 fn main() -> anyhow::Result<()> {
-    Day21::solve_part1("input.txt")?;
-    Day21::solve_part2("test.txt")?;
-    Ok(())
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Run(run_args) => run_args.execute(),
+        Command::List => {
+            cli::list_days();
+            Ok(())
+        }
+        Command::All(all_args) => all_args.execute(),
+        Command::Verify(verify_args) => verify_args.execute(),
+        Command::Visualize(visualize_args) => visualize_args.execute(),
+        Command::Animate(animate_args) => animate_args.execute(),
+        Command::Bench(bench_args) => bench_args.execute(),
+        Command::Generate(generate_args) => generate_args.execute(),
+        Command::Stones(stones_args) => stones_args.execute(),
+        Command::Disassemble(disassemble_args) => disassemble_args.execute(),
+        Command::Assemble(assemble_args) => assemble_args.execute(),
+    }
 }
@@ -0,0 +1,151 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+///How often a `Memo` has been able to answer from its cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+    ///Entries dropped to stay within the capacity
+    pub evictions: usize,
+}
+
+impl MemoStats {
+    pub fn hit_rate(&self) -> f64 {
+        //! The fraction of lookups answered from the cache, or 0 before any lookups.
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / lookups as f64
+    }
+}
+
+///A cache of already computed results keyed by the state they were computed for,
+/// for memoizing recursive solutions. Optionally bounded, in which case the oldest
+/// entries are evicted first once the capacity is reached.
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    entries: HashMap<K, V>,
+    capacity: Option<usize>,
+    ///Keys in the order they were inserted, only tracked when bounded
+    insertion_order: VecDeque<K>,
+    stats: MemoStats,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Memo {
+            entries: HashMap::new(),
+            capacity: None,
+            insertion_order: VecDeque::new(),
+            stats: MemoStats::default(),
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        //! An unbounded memo.
+        Memo::default()
+    }
+
+    pub fn bounded(capacity: usize) -> Self {
+        //! A memo holding at most `capacity` entries.
+        Memo {
+            capacity: Some(capacity),
+            ..Memo::default()
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        //! The cached value for `key`, counted as a hit or a miss.
+        let value = self.entries.get(key).cloned();
+        if value.is_some() {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        value
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if let Some(capacity) = self.capacity {
+            if capacity == 0 {
+                return;
+            }
+            if !self.entries.contains_key(&key) {
+                while self.entries.len() >= capacity {
+                    let Some(oldest) = self.insertion_order.pop_front() else { break };
+                    self.entries.remove(&oldest);
+                    self.stats.evictions += 1;
+                }
+                self.insertion_order.push_back(key.clone());
+            }
+        }
+        self.entries.insert(key, value);
+    }
+
+    pub fn get_or_insert_with(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V {
+        //! The cached value for `key`, or else `compute` it and cache the result. `compute` is handed
+        //! the memo so that recursive solutions can look up their subproblems in it.
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        let value = compute(self);
+        self.insert(key, value.clone());
+        value
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        self.stats
+    }
+
+    pub fn clear(&mut self) {
+        //! Drop every entry, keeping the statistics.
+        self.entries.clear();
+        self.insertion_order.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fibonacci(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        memo.get_or_insert_with(n, |memo| fibonacci(n - 1, memo) + fibonacci(n - 2, memo))
+    }
+
+    #[test]
+    fn recursive_results_are_reused() {
+        let mut memo = Memo::new();
+        assert_eq!(fibonacci(90, &mut memo), 2880067194370816120);
+        assert_eq!(memo.len(), 89);
+        assert_eq!(memo.stats(), MemoStats { hits: 87, misses: 89, evictions: 0 });
+        assert_eq!(fibonacci(90, &mut memo), 2880067194370816120);
+        assert_eq!(memo.stats().hits, 88);
+    }
+
+    #[test]
+    fn bounded_memos_evict_the_oldest_entries() {
+        let mut memo = Memo::bounded(2);
+        memo.insert('a', 1);
+        memo.insert('b', 2);
+        memo.insert('a', 3);
+        memo.insert('c', 4);
+        assert_eq!((memo.get(&'a'), memo.get(&'b'), memo.get(&'c')), (None, Some(2), Some(4)));
+        assert_eq!(memo.stats(), MemoStats { hits: 2, misses: 1, evictions: 1 });
+        assert!((memo.stats().hit_rate() - 2.0 / 3.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,151 @@
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use rayon::prelude::*;
+
+use crate::runner::SOLUTION_STACK_SIZE;
+
+thread_local! {
+    ///The pool that the `map`s on this thread spread over, set by `Jobs::install`
+    static INSTALLED_POOL: RefCell<Option<Arc<rayon::ThreadPool>>> = const { RefCell::new(None) };
+}
+
+///The worker threads of the opt-in parallel mode, shared by the days running at the same time
+/// and by the independent loops inside their solutions. A single job runs everything one at a time, as before.
+#[derive(Debug, Clone, Default)]
+pub struct Jobs {
+    ///`None` for a single job, which needs no pool
+    pool: Option<Arc<rayon::ThreadPool>>,
+}
+
+impl Jobs {
+    pub fn new(count: usize) -> anyhow::Result<Self> {
+        //! Spread work over `count` threads.
+        anyhow::ensure!(count > 0, "At least one job is needed");
+        if count == 1 {
+            return Ok(Jobs::sequential());
+        }
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(count)
+            .stack_size(SOLUTION_STACK_SIZE)
+            .thread_name(|index| format!("aoc-worker-{}", index))
+            .build()?;
+        Ok(Jobs { pool: Some(Arc::new(pool)) })
+    }
+
+    pub fn sequential() -> Self {
+        Jobs { pool: None }
+    }
+
+    pub fn count(&self) -> usize {
+        self.pool.as_ref().map_or(1, |pool| pool.current_num_threads())
+    }
+
+    pub fn install<R>(&self, work: impl FnOnce() -> R) -> R {
+        //! Run `work` on the calling thread, with the `map`s inside it spread over the jobs. Only the `map`s
+        //! take up the jobs' threads, and only until they finish, so a solution that never finishes does not
+        //! keep one of them from everything else.
+        let previous_pool = INSTALLED_POOL.replace(self.pool.clone());
+        let result = work();
+        INSTALLED_POOL.set(previous_pool);
+        result
+    }
+
+    pub fn map_blocking<T: Sync, R: Send>(&self, items: &[T], work: impl Fn(&T) -> R + Sync) -> Vec<R> {
+        //! `work` on up to `count` items at a time, the results in the order of the `items`. Meant for work
+        //! that waits on the jobs (such as running days, whose solutions `map` over them), which would deadlock
+        //! if it held on to the very threads it waits for, so it runs on threads of its own.
+        let next_item = AtomicUsize::new(0);
+        let results = Mutex::new((0..items.len()).map(|_| None).collect::<Vec<_>>());
+        thread::scope(|scope| {
+            for _ in 0..self.count().min(items.len()) {
+                scope.spawn(|| {
+                    loop {
+                        let index = next_item.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            break;
+                        };
+                        let result = work(item);
+                        results.lock().unwrap()[index] = Some(result);
+                    }
+                });
+            }
+        });
+        results.into_inner().unwrap().into_iter().map(|result| result.unwrap()).collect()
+    }
+}
+
+pub fn in_parallel() -> bool {
+    //! Whether the calling code is running on one of the jobs of a parallel `Jobs`, or has them installed.
+    rayon::current_thread_index().is_some() || INSTALLED_POOL.with_borrow(Option::is_some)
+}
+
+fn on_the_jobs<R: Send>(work: impl FnOnce() -> R + Send) -> Option<R> {
+    //! Run `work` on the jobs, `None` if the calling code is not using any.
+    if rayon::current_thread_index().is_some() {
+        return Some(work());
+    }
+    let pool = INSTALLED_POOL.with_borrow(Option::clone)?;
+    Some(pool.install(work))
+}
+
+pub fn map<T: Sync, R: Send>(items: &[T], work: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    //! `work` on every item, the results in the order of the `items` however many jobs shared it.
+    on_the_jobs(|| items.par_iter().map(&work).collect()).unwrap_or_else(|| items.iter().map(&work).collect())
+}
+
+pub fn map_init<T: Sync, S: Send, R: Send>(items: &[T], init: impl Fn() -> S + Sync + Send, work: impl Fn(&mut S, &T) -> R + Sync + Send) -> Vec<R> {
+    //! Like `map`, but `work` can also use some scratch state of its own, made by `init`. Every thread keeps
+    //! one state for all the items it works on, so that (say) a cache is shared by as many items as possible.
+    on_the_jobs(|| {
+        let states = (0..rayon::current_num_threads()).map(|_| Mutex::new(init())).collect::<Vec<_>>();
+        items.par_iter().map(|item| {
+            let thread_state = rayon::current_thread_index().and_then(|index| states.get(index)).and_then(|state| state.try_lock().ok());
+            match thread_state {
+                Some(mut state) => work(&mut state, item),
+                //Only if `work` itself waits on the jobs, letting the thread pick up another item in the meantime
+                None => work(&mut init(), item),
+            }
+        }).collect()
+    }).unwrap_or_else(|| {
+        let mut state = init();
+        items.iter().map(|item| work(&mut state, item)).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advent_solutions::{find_day, find_generator};
+
+    #[test]
+    fn results_keep_the_order_of_the_items() {
+        let items = (0..1000).collect::<Vec<u64>>();
+        let squares = items.iter().map(|item| item * item).collect::<Vec<_>>();
+        let jobs = Jobs::new(4).unwrap();
+        assert_eq!(jobs.count(), 4);
+        assert!(!in_parallel() && jobs.install(in_parallel));
+        assert_eq!(jobs.install(|| map(&items, |item| item * item)), squares);
+        assert_eq!(jobs.install(|| map_init(&items, || 0, |calls, item| { *calls += 1; item * item })), squares);
+        assert_eq!(jobs.map_blocking(&items, |item| item * item), squares);
+        assert_eq!(Jobs::sequential().map_blocking(&items, |item| item * item), squares);
+        assert_eq!(Jobs::new(0).unwrap_err().to_string(), "At least one job is needed");
+    }
+
+    #[test]
+    fn answers_do_not_depend_on_the_number_of_jobs() {
+        for (day, size) in [(6, 40), (7, 200), (22, 40), (23, 60)] {
+            let input = find_generator(day).unwrap().generate(size, 2024).unwrap();
+            let day_entry = find_day(day).unwrap();
+            for part in [1, 2] {
+                let sequential = day_entry.solve(part, &input).unwrap();
+                for count in [2, 4] {
+                    let jobs = Jobs::new(count).unwrap();
+                    assert_eq!(jobs.install(|| day_entry.solve(part, &input)).unwrap(), sequential, "Day {} part {} with {} jobs", day, part, count);
+                }
+            }
+        }
+    }
+}
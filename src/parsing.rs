@@ -0,0 +1,173 @@
+use std::str::FromStr;
+
+use crate::input_error::{parse_number, InputError};
+
+///A block of consecutive non-blank lines. Inputs made of several sections
+/// (or several records) separate them with blank lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Paragraph<'a> {
+    ///The lines of the paragraph, a slice of the text it was split from
+    pub text: &'a str,
+    ///1-based line number of the paragraph's first line in that text
+    pub first_line: usize,
+}
+
+impl<'a> Paragraph<'a> {
+    pub fn parse<T>(&self, parser: impl FnOnce(&'a str) -> Result<T, InputError>) -> Result<T, InputError> {
+        //! Parse the paragraph with `parser`, which locates errors within the paragraph, shifting them to
+        //! where the paragraph starts.
+        parser(self.text).map_err(|error| error.on_line(self.first_line))
+    }
+}
+
+pub fn paragraphs(text: &str) -> impl Iterator<Item = Paragraph<'_>> {
+    //! Split `text` on blank lines, skipping any run of them.
+    let mut found = Vec::new();
+    let mut current: Option<(usize, usize, usize)> = None; //(start offset, end offset, first line)
+    for (line_index, line) in text.lines().enumerate() {
+        let start = line.as_ptr() as usize - text.as_ptr() as usize;
+        if line.trim().is_empty() {
+            found.extend(current.take());
+            continue;
+        }
+        let end = start + line.len();
+        current = Some(match current {
+            Some((paragraph_start, _, first_line)) => (paragraph_start, end, first_line),
+            None => (start, end, line_index + 1),
+        });
+    }
+    found.extend(current);
+    found.into_iter().map(move |(start, end, first_line)| Paragraph { text: &text[start..end], first_line })
+}
+
+pub fn exact_paragraphs<'a, const N: usize>(day: u8, text: &'a str, expected: &str) -> Result<[Paragraph<'a>; N], InputError> {
+    //! Split `text` into exactly `N` paragraphs, reporting `expected` if there are too few.
+    let mut found = paragraphs(text);
+    let split = exact(&mut found, || InputError::at_end(day, text, expected))?;
+    match found.next() {
+        Some(extra) => Err(InputError::at(day, text, extra.text.lines().next().unwrap_or_default(), "the end of the input")),
+        None => Ok(split),
+    }
+}
+
+pub fn exact_lines<'a, const N: usize>(day: u8, text: &'a str, expected: &str) -> Result<[&'a str; N], InputError> {
+    //! Split `text` into exactly `N` lines, reporting `expected` if there are too few.
+    let mut found = text.lines();
+    let lines = exact(&mut found, || InputError::at_end(day, text, expected))?;
+    match found.next() {
+        Some(extra) => Err(InputError::at(day, text, extra, format!("only {} lines", N))),
+        None => Ok(lines),
+    }
+}
+
+fn exact<T, const N: usize>(items: &mut impl Iterator<Item = T>, missing: impl Fn() -> InputError) -> Result<[T; N], InputError> {
+    let items = items.take(N).collect::<Vec<_>>();
+    items.try_into().map_err(|_| missing())
+}
+
+pub fn integer_fragments(text: &str) -> impl Iterator<Item = &str> {
+    //! Every integer written in `text`, such as `-3` and `42` in `p=-3,42`, ignoring whatever separates them.
+    //! A `-` is only part of an integer when it comes right before a digit.
+    let bytes = text.as_bytes();
+    let mut position = 0;
+    std::iter::from_fn(move || {
+        while position < bytes.len() {
+            let start = position;
+            let digits_start = if bytes[position] == b'-' { position + 1 } else { position };
+            let digits_end = bytes[digits_start..].iter().position(|byte| !byte.is_ascii_digit()).map_or(bytes.len(), |length| digits_start + length);
+            if digits_end > digits_start {
+                position = digits_end;
+                return Some(&text[start..digits_end]);
+            }
+            position += 1;
+        }
+        None
+    })
+}
+
+pub fn integers<T: FromStr>(day: u8, source: &str, text: &str) -> Result<Vec<T>, InputError> {
+    //! Parse every integer in `text` (a slice of `source`), pointing at any that do not fit in a `T`.
+    integer_fragments(text).map(|fragment| parse_number(day, source, fragment)).collect()
+}
+
+pub fn capture<'a, const N: usize>(day: u8, source: &str, line: &'a str, pattern: &str) -> Result<[&'a str; N], InputError> {
+    //! Match `line` (a slice of `source`) against `pattern`, where each `{}` captures the text up to the next
+    //! literal part of the pattern, so `Button A: X+{}, Y+{}` captures `94` and `34` from `Button A: X+94, Y+34`.
+    //! Anything that differs from the literal parts is pointed at.
+    let mut literals = pattern.split("{}");
+    let first_literal = literals.next().unwrap_or_default();
+    let literals = literals.collect::<Vec<_>>();
+    assert_eq!(literals.len(), N, "Pattern `{}` does not have {} fields", pattern, N);
+    let mut rest = line.strip_prefix(first_literal).ok_or_else(|| {
+        //Point at the first character that differs from the start of the pattern
+        let matching = line.char_indices().zip(first_literal.chars())
+            .find(|((_, found), expected)| found != expected)
+            .map_or(line.len().min(first_literal.len()), |((offset, _), _)| offset);
+        InputError::at(day, source, &line[matching..], format!("`{}`", &first_literal[matching..]))
+    })?;
+    let mut fields = Vec::with_capacity(N);
+    for literal in literals {
+        if literal.is_empty() {
+            //A field at the end of the pattern takes the rest of the line
+            fields.push(rest);
+            rest = &rest[rest.len()..];
+            continue;
+        }
+        let field_end = rest.find(literal)
+            .ok_or_else(|| InputError::at(day, source, rest, format!("`{}`", literal)))?;
+        fields.push(&rest[..field_end]);
+        rest = &rest[field_end + literal.len()..];
+    }
+    if !rest.is_empty() {
+        return Err(InputError::at(day, source, rest, "the end of the line"));
+    }
+    Ok(fields.try_into().unwrap())
+}
+
+pub fn capture_numbers<T: FromStr, const N: usize>(day: u8, source: &str, line: &str, pattern: &str) -> Result<[T; N], InputError> {
+    //! `capture` the fields of `line` and parse each of them as a number.
+    let fields = capture::<N>(day, source, line, pattern)?;
+    let numbers = fields.iter().map(|field| parse_number(day, source, field)).collect::<Result<Vec<T>, _>>()?;
+    Ok(numbers.try_into().unwrap_or_else(|_| unreachable!("There is a number for every field")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paragraphs_know_their_first_line() {
+        let text = "a\nb\n\n\nc\n\nd\ne";
+        let found = paragraphs(text).map(|paragraph| (paragraph.text, paragraph.first_line)).collect::<Vec<_>>();
+        assert_eq!(found, vec![("a\nb", 1), ("c", 5), ("d\ne", 7)]);
+        let error = paragraphs(text).nth(2).unwrap().parse(|text| parse_number::<u8>(0, text, &text[2..])).unwrap_err();
+        assert_eq!((error.line, error.column), (8, 1));
+        let error = exact_paragraphs::<2>(0, text, "two sections").unwrap_err();
+        assert_eq!((error.line, error.found.as_str()), (7, "d"));
+        assert_eq!(exact_lines::<2>(0, "a\nb", "two lines").unwrap(), ["a", "b"]);
+        assert_eq!(exact_lines::<3>(0, "a\nb", "three lines").unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn signed_integers_are_extracted() {
+        let text = "p=-3,42 v=7,-0 - 5-";
+        assert_eq!(integer_fragments(text).collect::<Vec<_>>(), vec!["-3", "42", "7", "-0", "5"]);
+        assert_eq!(integers::<i64>(0, text, text).unwrap(), vec![-3, 42, 7, 0, 5]);
+        let error = integers::<u8>(0, text, text).unwrap_err();
+        assert_eq!((error.column, error.found.as_str()), (3, "-3"));
+    }
+
+    #[test]
+    fn fields_are_captured_between_literals() {
+        let line = "Button A: X+94, Y+34";
+        assert_eq!(capture(0, line, line, "Button A: X+{}, Y+{}").unwrap(), ["94", "34"]);
+        assert_eq!(capture_numbers::<i64, 3>(0, line, line, "Button {}: X+{}, Y+{}").unwrap_err().found, "A");
+        assert_eq!(capture::<2>(0, line, line, "Prize: X={}, Y={}").unwrap_err().column, 1);
+        let error = capture::<2>(0, line, line, "Button A: X-{}, Y-{}").unwrap_err();
+        assert_eq!((error.column, error.expected.as_str(), error.found.as_str()), (12, "`-`", "+94, Y+34"));
+        let error = capture::<2>(0, line, line, "Button A: X+{}; Y+{}").unwrap_err();
+        assert_eq!((error.column, error.found.as_str()), (13, "94, Y+34"));
+        let error = capture::<1>(0, line, line, "Button A: X+{},").unwrap_err();
+        assert_eq!((error.column, error.found.as_str()), (16, " Y+34"));
+    }
+}
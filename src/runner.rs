@@ -0,0 +1,265 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use serde::Serialize;
+
+use crate::advent_solutions::{Answer, DayEntry, REGISTRY};
+use crate::allocation;
+use crate::input::InputSource;
+use crate::parallel::Jobs;
+
+///Where puzzle inputs live unless told otherwise. Inputs are not committed
+/// (as requested by the creator of the problems), so they must be placed here by hand.
+pub const DEFAULT_INPUT_DIR: &str = "inputs";
+
+///Solutions run on their own thread so that they can be timed out. Give
+/// them the same stack size the main thread would have had.
+pub(crate) const SOLUTION_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+pub fn input_path_for(input_dir: &Path, day: u8) -> PathBuf {
+    //! The conventional location of a day's puzzle input inside `input_dir`.
+    input_dir.join(format!("day{day}.txt"))
+}
+
+pub fn default_input_path(day: u8) -> PathBuf {
+    input_path_for(Path::new(DEFAULT_INPUT_DIR), day)
+}
+
+///How running a single part of a days puzzle turned out.
+#[derive(Debug)]
+pub enum PartOutcome {
+    ///The solution returned an answer
+    Solved(Answer),
+    ///The solution returned an error (or panicked)
+    Failed(anyhow::Error),
+    ///The solution did not finish within the allotted time
+    TimedOut,
+    ///The part has not been solved yet, so there was nothing to run
+    Unsolved,
+}
+
+impl PartOutcome {
+    pub fn status(&self) -> &'static str {
+        match self {
+            PartOutcome::Solved(_) => "ok",
+            PartOutcome::Failed(_) => "error",
+            PartOutcome::TimedOut => "timeout",
+            PartOutcome::Unsolved => "unimplemented",
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        //! A single line description of the outcome, suitable for a table cell.
+        match self {
+            PartOutcome::Solved(Answer::Grid { caption, .. }) => caption.clone(),
+            PartOutcome::Solved(answer) => answer.to_string(),
+            //Input errors render the offending line below the message, which does not fit in a cell
+            PartOutcome::Failed(error) => format!("{:#}", error).lines().next().unwrap_or_default().to_string(),
+            PartOutcome::TimedOut => "did not finish in time".to_string(),
+            PartOutcome::Unsolved => "not solved yet".to_string(),
+        }
+    }
+}
+
+///The result of running a single part of a days puzzle,
+/// along with how long it took and how much memory it needed.
+#[derive(Debug)]
+pub struct PartReport {
+    pub day: u8,
+    pub part: u8,
+    pub title: &'static str,
+    pub outcome: PartOutcome,
+    ///Where the input came from, such as its file path
+    pub input: String,
+    ///Wall-clock time of the solution. The input has already been read by then.
+    pub elapsed: Duration,
+    ///The most bytes allocated at once while the part ran
+    pub peak_allocation: usize,
+}
+
+impl PartReport {
+    fn new(day_entry: &DayEntry, part: u8, input_source: &InputSource) -> Self {
+        //! A report for a part that has not run (yet).
+        PartReport {
+            day: day_entry.day,
+            part,
+            title: day_entry.title,
+            outcome: PartOutcome::Unsolved,
+            input: input_source.to_string(),
+            elapsed: Duration::ZERO,
+            peak_allocation: 0,
+        }
+    }
+
+    pub fn to_record(&self) -> PartRecord<'_> {
+        //! The machine readable form of this report.
+        let (answer, error) = match &self.outcome {
+            PartOutcome::Solved(answer) => (Some(answer), Vec::new()),
+            PartOutcome::Failed(error) => (None, error.chain().map(|cause| cause.to_string()).collect()),
+            PartOutcome::TimedOut | PartOutcome::Unsolved => (None, Vec::new()),
+        };
+        PartRecord {
+            day: self.day,
+            part: self.part,
+            title: self.title,
+            status: self.outcome.status(),
+            answer,
+            elapsed_seconds: self.elapsed.as_secs_f64(),
+            peak_allocation_bytes: self.peak_allocation,
+            input: &self.input,
+            error,
+        }
+    }
+}
+
+///A `PartReport` flattened for JSON output, one object per day/part.
+#[derive(Debug, Serialize)]
+pub struct PartRecord<'a> {
+    pub day: u8,
+    pub part: u8,
+    pub title: &'a str,
+    ///One of `ok`, `error`, `timeout` or `unimplemented`
+    pub status: &'static str,
+    ///Only present when the status is `ok`
+    pub answer: Option<&'a Answer>,
+    pub elapsed_seconds: f64,
+    pub peak_allocation_bytes: usize,
+    pub input: &'a str,
+    ///The error followed by each of its causes, empty unless the status is `error`
+    pub error: Vec<String>,
+}
+
+pub fn run_part(day_entry: &DayEntry, part: u8, input_source: &InputSource, input: &str, timeout: Duration, jobs: &Jobs) -> PartReport {
+    //! Run a single part against the puzzle `input` text (read from `input_source`) on its own thread, with its `map`s spread over the `jobs`,
+    //! giving up on it after `timeout`. A timed out solution cannot be killed, so its thread is left running in the background until the process exits.
+    let mut report = PartReport::new(day_entry, part, input_source);
+    let Some(solve_fn) = day_entry.part(part) else {
+        return report;
+    };
+    let (input, jobs) = (input.to_string(), jobs.clone());
+    let (sender, receiver) = mpsc::channel();
+    let peak_window = allocation::PeakWindow::open();
+    let start = Instant::now();
+    let spawned = thread::Builder::new().name(format!("day{}-part{}", day_entry.day, part)).stack_size(SOLUTION_STACK_SIZE).spawn(move || {
+        //The receiver is gone if the part timed out, in which case nobody cares about the answer
+        let _ = sender.send(jobs.install(|| solve_fn(&input)));
+    });
+    report.outcome = match spawned {
+        Err(error) => PartOutcome::Failed(anyhow::Error::new(error).context("Failed to spawn solution thread")),
+        Ok(_) => match receiver.recv_timeout(timeout) {
+            Ok(Ok(answer)) => PartOutcome::Solved(answer),
+            Ok(Err(error)) => PartOutcome::Failed(error),
+            Err(RecvTimeoutError::Timeout) => PartOutcome::TimedOut,
+            Err(RecvTimeoutError::Disconnected) => PartOutcome::Failed(anyhow!("Solution panicked")),
+        },
+    };
+    report.elapsed = start.elapsed();
+    report.peak_allocation = peak_window.peak();
+    report
+}
+
+pub fn run_all(input_dir: &Path, timeout: Duration, jobs: &Jobs) -> Vec<PartReport> {
+    //! Run both parts of every registered day against its input in `input_dir`, as many days at once as there are `jobs`.
+    //! If a days input cannot be read, each of its solved parts is reported as failed. Reports are in calendar order either way.
+    //! Each part's peak allocation is its own high-water mark, but it also counts whatever days run at the same time allocate.
+    jobs.map_blocking(&REGISTRY, |day_entry| run_day(day_entry, input_dir, timeout, jobs)).into_iter().flatten().collect()
+}
+
+fn run_day(day_entry: &DayEntry, input_dir: &Path, timeout: Duration, jobs: &Jobs) -> Vec<PartReport> {
+    //! Run both parts of a day against its input in `input_dir`, one after the other.
+    let input_source = InputSource::File(input_path_for(input_dir, day_entry.day));
+    let input = input_source.read();
+    let mut reports = Vec::new();
+    for part in [1, 2] {
+        let report = match &input {
+            Ok(input) => run_part(day_entry, part, &input_source, input, timeout, jobs),
+            Err(error) => {
+                let mut report = PartReport::new(day_entry, part, &input_source);
+                if day_entry.part(part).is_some() {
+                    report.outcome = PartOutcome::Failed(anyhow!("{:#}", error));
+                }
+                report
+            }
+        };
+        reports.push(report);
+    }
+    reports
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    //! Human readable duration with a sensible unit.
+    let seconds = duration.as_secs_f64();
+    if seconds >= 1.0 {
+        format!("{:.2} s", seconds)
+    } else if seconds >= 1e-3 {
+        format!("{:.2} ms", seconds * 1e3)
+    } else {
+        format!("{:.2} µs", seconds * 1e6)
+    }
+}
+
+pub fn format_bytes(bytes: usize) -> String {
+    //! Human readable byte count with a binary unit.
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_day;
+
+    #[test]
+    fn records_carry_the_status_and_error_chain() {
+        let day_entry = find_day(11).unwrap();
+        let input_source = InputSource::Inline("125 17".to_string());
+        let mut report = run_part(day_entry, 1, &input_source, "125 17", Duration::MAX, &Jobs::sequential());
+        let record = serde_json::to_value(report.to_record()).unwrap();
+        assert_eq!(record["status"], "ok");
+        assert_eq!(record["answer"], 55312);
+        assert_eq!(record["input"], "<inline>");
+
+        report.outcome = PartOutcome::Failed(anyhow!("bad digit").context("Failed to parse stones"));
+        let record = serde_json::to_value(report.to_record()).unwrap();
+        assert_eq!(record["status"], "error");
+        assert!(record["answer"].is_null());
+        assert_eq!(record["error"], serde_json::json!(["Failed to parse stones", "bad digit"]));
+    }
+
+    #[test]
+    fn parts_that_never_finish_do_not_hold_up_the_jobs() {
+        let never_finishes = DayEntry {
+            day: 1,
+            title: "Never finishes",
+            parse: None,
+            part1: Some(|_| loop {
+                thread::sleep(Duration::from_secs(1));
+            }),
+            part2: None,
+        };
+        let jobs = Jobs::new(2).unwrap();
+        let input_source = InputSource::Inline(String::new());
+        //More runaway parts than there are jobs, and then a day that spreads its equations over the jobs
+        for _ in 0..3 {
+            let report = run_part(&never_finishes, 1, &input_source, "", Duration::from_millis(10), &jobs);
+            assert_eq!(report.outcome.status(), "timeout");
+        }
+        let equations = "190: 10 19\n3267: 81 40 27\n83: 17 5\n156: 15 6\n7290: 6 8 6 15\n292: 11 6 16 20";
+        let report = run_part(find_day(7).unwrap(), 1, &input_source, equations, Duration::from_secs(10), &jobs);
+        assert_eq!(report.outcome.status(), "ok");
+        assert_eq!(report.outcome.summary(), "3749");
+    }
+}
@@ -0,0 +1,298 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+///The outcome of a search: the lowest cost to every state that was settled, and every
+/// predecessor each state can be reached from at that cost, so that all of the optimal
+/// paths (not just one) can be recovered afterwards.
+///
+/// When the search stops at a goal, every state costing no more than the goal is settled,
+/// so the paths to the goal are complete.
+#[derive(Debug, Clone)]
+pub struct SearchResult<S> {
+    start: S,
+    distances: HashMap<S, usize>,
+    predecessors: HashMap<S, Vec<S>>,
+    ///Every goal state reached at the lowest goal cost
+    goals: Vec<S>,
+}
+
+impl<S: Clone + Eq + Hash> SearchResult<S> {
+    fn new(start: S) -> Self {
+        let mut distances = HashMap::new();
+        distances.insert(start.clone(), 0);
+        SearchResult {
+            start,
+            distances,
+            predecessors: HashMap::new(),
+            goals: Vec::new(),
+        }
+    }
+
+    fn relax(&mut self, from: &S, to: S, cost: usize) -> bool {
+        //! Record reaching `to` from `from` at `cost`. Returns true if that is a new best cost.
+        //! Predecessors never form a cycle, which zero cost moves could otherwise close (back to the
+        //! start, or around any other states), leaving `path_to` and `all_paths_to` walking forever.
+        match self.distances.get(&to) {
+            Some(best) if cost > *best => false,
+            Some(_) if to == self.start => false,
+            Some(best) if cost == *best => {
+                if self.distance(from) == Some(cost) && self.states_on_paths_to(from).contains(&to) {
+                    //A zero cost move back to a state `from` is itself reached through
+                    return false;
+                }
+                let predecessors = self.predecessors.entry(to).or_default();
+                if !predecessors.contains(from) {
+                    predecessors.push(from.clone());
+                }
+                false
+            }
+            _ => {
+                self.distances.insert(to.clone(), cost);
+                self.predecessors.insert(to, vec![from.clone()]);
+                true
+            }
+        }
+    }
+
+    fn retain_settled(&mut self, settled: &HashSet<S>) {
+        //! Forget the states left on the frontier when the search stopped early, whose costs are not final.
+        self.distances.retain(|state, _| settled.contains(state));
+        self.predecessors.retain(|state, _| settled.contains(state));
+    }
+
+    pub fn start(&self) -> &S {
+        &self.start
+    }
+
+    pub fn distance(&self, state: &S) -> Option<usize> {
+        //! The lowest cost from the start to `state`, if it was reached.
+        self.distances.get(state).copied()
+    }
+
+    pub fn distances(&self) -> &HashMap<S, usize> {
+        &self.distances
+    }
+
+    pub fn into_distances(self) -> HashMap<S, usize> {
+        self.distances
+    }
+
+    pub fn predecessors(&self, state: &S) -> &[S] {
+        //! Every state that `state` can be reached from on an optimal path.
+        self.predecessors.get(state).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn goals(&self) -> &[S] {
+        &self.goals
+    }
+
+    pub fn goal_distance(&self) -> Option<usize> {
+        //! The cost of the cheapest goal, or `None` if no goal was reachable.
+        self.goals.first().and_then(|goal| self.distance(goal))
+    }
+
+    pub fn path_to(&self, state: &S) -> Option<Vec<S>> {
+        //! One optimal path from the start to `state`, both included.
+        self.distances.get(state)?;
+        let mut path = vec![state.clone()];
+        while let Some(predecessor) = self.predecessors(path.last().unwrap()).first() {
+            path.push(predecessor.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    pub fn all_paths_to(&self, state: &S) -> Vec<Vec<S>> {
+        //! Every optimal path from the start to `state`, both included. There can be exponentially many,
+        //! so prefer `states_on_paths_to` when only the states involved matter.
+        if !self.distances.contains_key(state) {
+            return Vec::new();
+        }
+        let mut paths = Vec::new();
+        //Walk backwards from `state`, branching at every state with more than one predecessor
+        let mut partial_paths = vec![vec![state.clone()]];
+        while let Some(partial_path) = partial_paths.pop() {
+            let predecessors = self.predecessors(partial_path.last().unwrap());
+            if predecessors.is_empty() {
+                paths.push(partial_path.into_iter().rev().collect());
+                continue;
+            }
+            for predecessor in predecessors {
+                let mut extended_path = partial_path.clone();
+                extended_path.push(predecessor.clone());
+                partial_paths.push(extended_path);
+            }
+        }
+        paths
+    }
+
+    pub fn states_on_paths_to(&self, state: &S) -> HashSet<S> {
+        //! Every state lying on at least one optimal path from the start to `state`.
+        let mut on_paths = HashSet::new();
+        if self.distances.contains_key(state) {
+            let mut stack = vec![state.clone()];
+            while let Some(current) = stack.pop() {
+                if on_paths.insert(current.clone()) {
+                    stack.extend(self.predecessors(&current).iter().cloned());
+                }
+            }
+        }
+        on_paths
+    }
+
+    pub fn states_on_optimal_paths(&self) -> HashSet<S> {
+        //! Every state lying on at least one optimal path from the start to any of the `goals`.
+        self.goals.iter().flat_map(|goal| self.states_on_paths_to(goal)).collect()
+    }
+}
+
+pub fn bfs<S, I>(start: S, mut neighbors: impl FnMut(&S) -> I, mut is_goal: impl FnMut(&S) -> bool) -> SearchResult<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    //! Breadth first search where every step costs 1. Stops once every goal at the lowest goal distance is found,
+    //! or explores everything reachable when `is_goal` never matches.
+    let mut result = SearchResult::new(start.clone());
+    let mut settled = HashSet::new();
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((state, distance)) = queue.pop_front() {
+        if result.goal_distance().is_some_and(|goal_distance| distance > goal_distance) {
+            break;
+        }
+        settled.insert(state.clone());
+        if is_goal(&state) {
+            result.goals.push(state.clone());
+        }
+        for neighbor in neighbors(&state) {
+            if result.relax(&state, neighbor.clone(), distance + 1) {
+                queue.push_back((neighbor, distance + 1));
+            }
+        }
+    }
+    result.retain_settled(&settled);
+    result
+}
+
+pub fn dijkstra<S, I>(start: S, successors: impl FnMut(&S) -> I, is_goal: impl FnMut(&S) -> bool) -> SearchResult<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    //! Lowest cost search where `successors` yields each next state with the cost of moving to it.
+    astar(start, successors, |_| 0, is_goal)
+}
+
+pub fn astar<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> usize,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> SearchResult<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    //! `dijkstra` guided by a `heuristic` estimate of the remaining cost to a goal. The heuristic must never
+    //! overestimate (and never drop by more than the cost of a move), or the result may not be optimal.
+    let mut result = SearchResult::new(start.clone());
+    let mut settled = HashSet::new();
+    //The heap holds indexes into `pending`, so that states need not be `Ord`
+    let mut pending = vec![start.clone()];
+    let mut frontier = BinaryHeap::from([Reverse((heuristic(&start), 0, 0))]);
+    while let Some(Reverse((priority, cost, index))) = frontier.pop() {
+        if result.goal_distance().is_some_and(|goal_distance| priority > goal_distance) {
+            break;
+        }
+        let state = pending[index].clone();
+        if result.distance(&state).is_some_and(|best| cost > best) || !settled.insert(state.clone()) {
+            //A cheaper route to this state was already found
+            continue;
+        }
+        if is_goal(&state) {
+            result.goals.push(state.clone());
+        }
+        for (next_state, step_cost) in successors(&state) {
+            let next_cost = cost + step_cost;
+            if result.relax(&state, next_state.clone(), next_cost) {
+                frontier.push(Reverse((next_cost + heuristic(&next_state), next_cost, pending.len())));
+                pending.push(next_state);
+            }
+        }
+    }
+    result.retain_settled(&settled);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Point;
+    use crate::grid::Grid;
+
+    const MAZE: &str = "\
+...#
+.#..
+....";
+
+    fn open_neighbors(maze: &Grid<char>) -> impl FnMut(&Point) -> Vec<Point> + '_ {
+        |position| maze.neighbors4(*position).filter(|neighbor| maze[*neighbor] == '.').collect()
+    }
+
+    #[test]
+    fn bfs_finds_every_shortest_path() {
+        let maze = Grid::parse_chars(0, MAZE).unwrap();
+        let corner = Point::new(2, 3);
+        let result = bfs(Point::ORIGIN, open_neighbors(&maze), |position| *position == corner);
+        assert_eq!(result.goal_distance(), Some(5));
+        assert_eq!(result.path_to(&corner).unwrap().len(), 6);
+        //Down the left edge, or along the top and then 2 ways around the bottom right
+        assert_eq!(result.all_paths_to(&corner).len(), 3);
+        assert_eq!(result.states_on_optimal_paths().len(), 10);
+        assert_eq!(result.distance(&Point::new(0, 3)), None);
+    }
+
+    #[test]
+    fn bfs_without_a_goal_explores_everything() {
+        let maze = Grid::parse_chars(0, MAZE).unwrap();
+        let result = bfs(Point::ORIGIN, open_neighbors(&maze), |_| false);
+        assert_eq!(result.goal_distance(), None);
+        assert_eq!(result.distances().len(), 10);
+        assert_eq!(result.all_paths_to(&Point::ORIGIN), vec![vec![Point::ORIGIN]]);
+    }
+
+    #[test]
+    fn dijkstra_and_astar_weigh_the_moves() {
+        //Stepping right is cheap, stepping down is expensive
+        let maze = Grid::parse_chars(0, MAZE).unwrap();
+        let corner = Point::new(2, 3);
+        let successors = |position: &Point| {
+            open_neighbors(&maze)(position).into_iter()
+                .map(|next| (next, if next.row != position.row { 10 } else { 1 }))
+                .collect::<Vec<_>>()
+        };
+        let result = dijkstra(Point::ORIGIN, successors, |position| *position == corner);
+        assert_eq!(result.goal_distance(), Some(23));
+        assert_eq!(result.all_paths_to(&corner).len(), 3);
+        let guided = astar(Point::ORIGIN, successors, |position| position.manhattan_distance(corner) as usize, |position| *position == corner);
+        assert_eq!(guided.goal_distance(), Some(23));
+        assert_eq!(guided.states_on_optimal_paths(), result.states_on_optimal_paths());
+    }
+
+    #[test]
+    fn zero_cost_cycles_do_not_become_paths() {
+        //0 -> 1 and 1 -> 2 are free, and each of them leads freely back; 2 -> 3 costs 1
+        let successors = |state: &u32| match state {
+            0 => vec![(1, 0)],
+            1 => vec![(0, 0), (2, 0)],
+            2 => vec![(1, 0), (0, 0), (3, 1)],
+            _ => vec![],
+        };
+        let result = dijkstra(0, successors, |state| *state == 3);
+        assert_eq!(result.goal_distance(), Some(1));
+        assert_eq!(result.predecessors(&0), &[] as &[u32]);
+        assert_eq!(result.path_to(&3), Some(vec![0, 1, 2, 3]));
+        assert_eq!(result.all_paths_to(&3), vec![vec![0, 1, 2, 3]]);
+    }
+}
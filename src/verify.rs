@@ -0,0 +1,132 @@
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::advent_solutions::{find_day, read_input_file};
+use crate::input::InputSource;
+use crate::parallel::Jobs;
+use crate::runner::{default_input_path, run_part, PartOutcome};
+
+///The default location of the answers file. Like the inputs, it is
+/// specific to one account's puzzles and so is not committed.
+pub const DEFAULT_ANSWERS_FILE: &str = "answers.toml";
+
+///An expected answer as written in the answers file. Whole numbers can be
+/// written bare, anything else (such as Day 17's out buffer) must be quoted.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ExpectedAnswer {
+    Integer(i64),
+    Text(String),
+}
+
+impl Display for ExpectedAnswer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExpectedAnswer::Integer(value) => write!(f, "{}", value),
+            ExpectedAnswer::Text(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+///A single `[[answer]]` entry of the answers file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnswerEntry {
+    pub day: u8,
+    pub part: u8,
+    ///The input the answer belongs to. Defaults to `inputs/day<DAY>.txt`
+    pub input: Option<PathBuf>,
+    pub expected: ExpectedAnswer,
+}
+
+impl AnswerEntry {
+    pub fn input_path(&self) -> PathBuf {
+        self.input.clone().unwrap_or_else(|| default_input_path(self.day))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AnswersFile {
+    #[serde(default, rename = "answer")]
+    answers: Vec<AnswerEntry>,
+}
+
+pub fn load_answers(path: &Path) -> anyhow::Result<Vec<AnswerEntry>> {
+    //! Parse and validate the answers file at `path`.
+    let file_contents = read_input_file(path).with_context(|| format!("Could not read answers file {}", path.display()))?;
+    let answers_file: AnswersFile = toml::from_str(&file_contents).with_context(|| format!("Answers file {} is malformed", path.display()))?;
+    for entry in answers_file.answers.iter() {
+        find_day(entry.day)?;
+        anyhow::ensure!(entry.part == 1 || entry.part == 2, "Day {} has an answer for part {}, which does not exist", entry.day, entry.part);
+    }
+    Ok(answers_file.answers)
+}
+
+///The outcome of checking a single answers file entry.
+#[derive(Debug)]
+pub enum Verdict {
+    ///The solution produced the expected answer
+    Pass,
+    ///The solution produced a different answer, or no answer at all.
+    /// Holds a description of what went wrong.
+    Fail(String),
+    ///The entry could not be checked because its input file is missing
+    Missing,
+}
+
+pub fn diff_answers(expected: &str, actual: &str) -> String {
+    //! Describe how `actual` differs from `expected`. Single line answers are shown side by side,
+    //! multi-line answers (such as drawn grids) only show the lines that differ.
+    if !expected.contains('\n') && !actual.contains('\n') {
+        return format!("expected {}, got {}", expected, actual);
+    }
+    let expected_lines = expected.lines().collect::<Vec<_>>();
+    let actual_lines = actual.lines().collect::<Vec<_>>();
+    let mut diff = Vec::new();
+    for line_number in 0..expected_lines.len().max(actual_lines.len()) {
+        let expected_line = expected_lines.get(line_number);
+        let actual_line = actual_lines.get(line_number);
+        if expected_line == actual_line {
+            continue;
+        }
+        if let Some(expected_line) = expected_line {
+            diff.push(format!("line {}: - {}", line_number + 1, expected_line));
+        }
+        if let Some(actual_line) = actual_line {
+            diff.push(format!("line {}: + {}", line_number + 1, actual_line));
+        }
+    }
+    diff.join("\n")
+}
+
+fn judge(expected: &ExpectedAnswer, outcome: &PartOutcome) -> Verdict {
+    match outcome {
+        PartOutcome::Solved(answer) => {
+            let (expected, actual) = (expected.to_string(), answer.to_string());
+            if expected == actual {
+                Verdict::Pass
+            } else {
+                Verdict::Fail(diff_answers(&expected, &actual))
+            }
+        }
+        PartOutcome::Failed(error) => Verdict::Fail(format!("solution failed: {:#}", error)),
+        PartOutcome::TimedOut => Verdict::Fail("solution did not finish in time".to_string()),
+        PartOutcome::Unsolved => Verdict::Fail("part is no longer solved".to_string()),
+    }
+}
+
+pub fn verify_entry(entry: &AnswerEntry, timeout: Duration) -> anyhow::Result<Verdict> {
+    //! Run the solution for `entry` and compare it against the expected answer.
+    let input_path = entry.input_path();
+    if !input_path.is_file() {
+        return Ok(Verdict::Missing);
+    }
+    let day_entry = find_day(entry.day)?;
+    let input_source = InputSource::File(input_path);
+    let input = input_source.read()?;
+    let report = run_part(day_entry, entry.part, &input_source, &input, timeout, &Jobs::sequential());
+    Ok(judge(&entry.expected, &report.outcome))
+}
@@ -0,0 +1,261 @@
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::geometry::Point;
+use crate::grid::Grid;
+
+///A 24-bit RGB color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::new(0, 0, 0);
+    pub const WHITE: Color = Color::new(255, 255, 255);
+    pub const WALL: Color = Color::new(96, 96, 96);
+    pub const FLOOR: Color = Color::new(24, 24, 24);
+    pub const RED: Color = Color::new(230, 57, 70);
+    pub const GREEN: Color = Color::new(87, 204, 153);
+    pub const BLUE: Color = Color::new(69, 123, 237);
+    pub const YELLOW: Color = Color::new(255, 209, 102);
+    pub const ORANGE: Color = Color::new(244, 140, 6);
+    pub const PURPLE: Color = Color::new(157, 78, 221);
+    pub const CYAN: Color = Color::new(72, 202, 228);
+    pub const PINK: Color = Color::new(247, 37, 133);
+
+    ///Colors distinct enough to tell neighboring overlays (such as Day 12's regions) apart.
+    pub const PALETTE: [Color; 8] = [
+        Color::RED, Color::GREEN, Color::BLUE, Color::YELLOW,
+        Color::ORANGE, Color::PURPLE, Color::CYAN, Color::PINK,
+    ];
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b }
+    }
+
+    pub fn from_palette(index: usize) -> Self {
+        //! A color from the `PALETTE`, cycling through it for indexes past its end.
+        Color::PALETTE[index % Color::PALETTE.len()]
+    }
+
+    fn for_symbol(symbol: char) -> Self {
+        //! The color a map symbol is drawn in when it is not highlighted.
+        //! Walls are gray, open space is near black, digits (heights) get lighter as they grow,
+        //! and any other symbol gets a dim color of its own.
+        match symbol {
+            '#' => Color::WALL,
+            '.' | ' ' => Color::FLOOR,
+            '0'..='9' => {
+                let shade = 40 + 20 * (symbol as u8 - b'0');
+                Color::new(shade, shade, shade)
+            }
+            _ => {
+                let Color { r, g, b } = Color::from_palette(symbol as usize);
+                Color::new(r / 2, g / 2, b / 2)
+            }
+        }
+    }
+}
+
+///A set of cells to highlight in a single color, such as a path or a region.
+#[derive(Debug, Clone)]
+pub struct Overlay {
+    ///What the cells are, shown in the legend
+    pub label: String,
+    pub color: Color,
+    pub cells: HashSet<Point>,
+}
+
+///A map of symbols with any number of highlighted overlays on top of it, which can be
+/// printed to a terminal with ANSI colors or saved as an image.
+#[derive(Debug, Clone)]
+pub struct Scene {
+    base: Grid<char>,
+    ///Drawn in order, so later overlays cover earlier ones where they overlap
+    overlays: Vec<Overlay>,
+}
+
+impl Scene {
+    pub fn new(base: Grid<char>) -> Self {
+        Scene { base, overlays: Vec::new() }
+    }
+
+    pub fn overlay(mut self, label: impl Into<String>, color: Color, cells: impl IntoIterator<Item = Point>) -> Self {
+        //! Highlight `cells` in `color` on top of everything added so far.
+        self.overlays.push(Overlay {
+            label: label.into(),
+            color,
+            cells: cells.into_iter().collect(),
+        });
+        self
+    }
+
+    pub fn base(&self) -> &Grid<char> {
+        &self.base
+    }
+
+    pub fn overlays(&self) -> &[Overlay] {
+        &self.overlays
+    }
+
+    pub fn highlight(&self, position: Point) -> Option<Color> {
+        //! The color of the topmost overlay covering `position`, if any.
+        self.overlays.iter().rev().find(|overlay| overlay.cells.contains(&position)).map(|overlay| overlay.color)
+    }
+
+    pub fn legend(&self) -> Vec<(&str, Color, usize)> {
+        //! Each distinct overlay label with its (first) color and the number of cells under that label.
+        let mut legend: Vec<(&str, Color, usize)> = Vec::new();
+        for overlay in self.overlays.iter() {
+            match legend.iter_mut().find(|(label, _, _)| *label == overlay.label) {
+                Some((_, _, cell_count)) => *cell_count += overlay.cells.len(),
+                None => legend.push((&overlay.label, overlay.color, overlay.cells.len())),
+            }
+        }
+        legend
+    }
+
+    pub fn to_ansi(&self) -> String {
+        //! Draw the map with highlighted cells given a 24-bit background color, followed by the legend.
+        let mut drawing = String::new();
+        for row in 0..self.base.rows() as i64 {
+            for col in 0..self.base.cols() as i64 {
+                let position = Point::new(row, col);
+                let symbol = self.base[position];
+                match self.highlight(position) {
+                    Some(Color { r, g, b }) => write!(drawing, "\x1b[48;2;{};{};{}m\x1b[30m{}\x1b[0m", r, g, b, symbol).unwrap(),
+                    None => drawing.push(symbol),
+                }
+            }
+            drawing.push('\n');
+        }
+        for (label, Color { r, g, b }, cell_count) in self.legend() {
+            writeln!(drawing, "\x1b[48;2;{};{};{}m  \x1b[0m {} ({} cells)", r, g, b, label, cell_count).unwrap();
+        }
+        drawing
+    }
+
+    pub fn render(&self, cell_size: usize) -> Image {
+        //! Draw every cell as a `cell_size` square, in its overlay color or else the color of its symbol.
+        let mut image = Image::new(self.base.cols() * cell_size, self.base.rows() * cell_size);
+        for (position, symbol) in self.base.iter() {
+            let color = self.highlight(position).unwrap_or_else(|| Color::for_symbol(*symbol));
+            image.fill_square(position.col as usize * cell_size, position.row as usize * cell_size, cell_size, color);
+        }
+        image
+    }
+
+    pub fn save(&self, path: &Path, cell_size: usize) -> anyhow::Result<()> {
+        //! Render the scene and save it as a `.ppm` or `.png` file, going by the extension of `path`.
+        self.render(cell_size).save(path)
+    }
+}
+
+///An RGB image, 3 bytes per pixel stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        //! A black image.
+        Image { width, height, pixels: vec![0; width * height * 3] }
+    }
+
+    fn fill_square(&mut self, left: usize, top: usize, size: usize, color: Color) {
+        for row in top..top + size {
+            let start = (row * self.width + left) * 3;
+            for pixel in self.pixels[start..start + size * 3].chunks_exact_mut(3) {
+                pixel.copy_from_slice(&[color.r, color.g, color.b]);
+            }
+        }
+    }
+
+    pub fn write_ppm(&self, mut writer: impl Write) -> std::io::Result<()> {
+        //! Write the image in the binary (P6) PPM format.
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        writer.write_all(&self.pixels)
+    }
+
+    pub fn write_png(&self, writer: impl Write) -> anyhow::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut png_writer = encoder.write_header()?;
+        png_writer.write_image_data(&self.pixels)?;
+        png_writer.finish()?;
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        //! Save the image as a `.ppm` or `.png` file, going by the extension of `path`.
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default().to_ascii_lowercase();
+        anyhow::ensure!(extension == "ppm" || extension == "png", "Cannot save {}, images are saved as `.ppm` or `.png` files", path.display());
+        let file = File::create(path).with_context(|| format!("Could not create {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        match extension.as_str() {
+            "ppm" => self.write_ppm(&mut writer)?,
+            _ => self.write_png(&mut writer)?,
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene() -> Scene {
+        let base = Grid::parse_chars(0, "#..\n.#.").unwrap();
+        Scene::new(base)
+            .overlay("path", Color::RED, [Point::new(0, 1), Point::new(0, 2)])
+            .overlay("end", Color::BLUE, [Point::new(0, 2)])
+    }
+
+    #[test]
+    fn later_overlays_win() {
+        let scene = scene();
+        assert_eq!(scene.highlight(Point::new(0, 1)), Some(Color::RED));
+        assert_eq!(scene.highlight(Point::new(0, 2)), Some(Color::BLUE));
+        assert_eq!(scene.highlight(Point::new(1, 1)), None);
+        assert_eq!(scene.legend(), vec![("path", Color::RED, 2), ("end", Color::BLUE, 1)]);
+    }
+
+    #[test]
+    fn ansi_output_colors_only_highlighted_cells() {
+        let drawing = scene().to_ansi();
+        let mut lines = drawing.lines();
+        assert_eq!(lines.next(), Some("#\x1b[48;2;230;57;70m\x1b[30m.\x1b[0m\x1b[48;2;69;123;237m\x1b[30m.\x1b[0m"));
+        assert_eq!(lines.next(), Some(".#."));
+        assert!(lines.next().unwrap().ends_with("path (2 cells)"));
+    }
+
+    #[test]
+    fn images_are_scaled_by_the_cell_size() {
+        let image = scene().render(2);
+        assert_eq!((image.width, image.height), (6, 4));
+        //The second pixel of the top row is still the wall, the third is the path
+        assert_eq!(&image.pixels[3..9], &[96, 96, 96, 230, 57, 70]);
+        let mut ppm = Vec::new();
+        image.write_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n6 4\n255\n"));
+        assert_eq!(ppm.len(), 11 + 6 * 4 * 3);
+        let mut png = Vec::new();
+        image.write_png(&mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        //The IHDR chunk holds the width and height
+        assert_eq!(&png[16..24], &[0, 0, 0, 6, 0, 0, 0, 4]);
+    }
+}
@@ -0,0 +1,24 @@
+use advent_of_code_2024::day13::ClawMachine;
+use advent_of_code_2024::day14::Robot;
+use advent_of_code_2024::day24::{GateJunction, GateType};
+use advent_of_code_2024::{find_day, Point, REGISTRY};
+
+#[test]
+fn parsers_are_usable_outside_the_crate() {
+    let junction = GateJunction::from_line("x00 AND y00 -> z00").unwrap();
+    assert_eq!((junction.in_1.as_str(), junction.in_2.as_str(), junction.out.as_str()), ("x00", "y00", "z00"));
+    assert!(matches!(junction.gate_type, GateType::And));
+
+    let robot = Robot::try_new("p=0,4 v=3,-3").unwrap();
+    assert_eq!((robot.position, robot.velocity), (Point::new(4, 0), Point::new(-3, 3)));
+
+    let claw_machines = ClawMachine::construct_claws("Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=5400").unwrap();
+    assert_eq!(claw_machines.len(), 1);
+    assert_eq!(claw_machines[0].try_win_prize_using_algebra(), Some(280));
+}
+
+#[test]
+fn registry_is_public() {
+    assert_eq!(REGISTRY.len(), 25);
+    assert_eq!(find_day(24).unwrap().title, "Crossed Wires");
+}